@@ -0,0 +1,99 @@
+//! Throughput of the landmask against GEOS on the same large set of points. GEOS prepared
+//! geometries are what shapely uses for `contains_xy` and `STRtree.query`, and what most other
+//! landmask tools built on the GSHHG polygons end up calling. Run with:
+//!
+//! ```sh
+//! cargo bench --bench comparison
+//! ```
+//!
+//! The points are drawn uniformly over the sphere from a fixed seed, so every run on every
+//! machine checks the same points. The absolute numbers depend on the hardware, but the ratio
+//! between the landmask and GEOS in the same run does not much, and is the number to compare.
+//! To catch regressions, save a baseline with `-- --save-baseline main` and compare a later
+//! run against it with `-- --baseline main`. `tests/test_comparison.py` does the same against
+//! shapely from Python.
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use numpy::PyArray;
+use pyo3::prelude::*;
+use roaring_landmask::{Gshhg, RoaringLandmask};
+
+/// Number of points, few enough that the serial GEOS baseline finishes in seconds.
+const N: usize = 100_000;
+
+/// The seed of the points, keep it fixed so that runs are comparable.
+const SEED: u64 = 42;
+
+/// `n` points (longitude, latitude) drawn uniformly over the sphere from `seed`, with
+/// SplitMix64 so that they do not depend on the version of any random number crate.
+fn points(n: usize, seed: u64) -> (Vec<f64>, Vec<f64>) {
+    let mut state = seed;
+    let mut uniform = move || {
+        state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^= z >> 31;
+        (z >> 11) as f64 / (1u64 << 53) as f64
+    };
+
+    (0..n)
+        .map(|_| {
+            let x = 360. * uniform() - 180.;
+            let y = (2. * uniform() - 1.).asin().to_degrees();
+            (x, y)
+        })
+        .unzip()
+}
+
+fn comparison(c: &mut Criterion) {
+    pyo3::prepare_freethreaded_python();
+    Python::with_gil(|py| {
+        let landmask = RoaringLandmask::new(py).unwrap();
+        let shapes = Gshhg::new(py).unwrap();
+
+        let (xs, ys) = points(N, SEED);
+        let x = PyArray::from_vec(py, xs.clone()).to_dyn();
+        let y = PyArray::from_vec(py, ys.clone()).to_dyn();
+
+        let mut g = c.benchmark_group("comparison");
+        g.sample_size(10);
+        g.throughput(Throughput::Elements(N as u64));
+
+        g.bench_function(BenchmarkId::new("landmask", "serial"), |b| {
+            b.iter(|| {
+                landmask
+                    .contains_many(py, x.readonly(), y.readonly())
+                    .unwrap()
+            })
+        });
+        g.bench_function(BenchmarkId::new("landmask", "parallel"), |b| {
+            b.iter(|| {
+                landmask
+                    .contains_many_par(py, x.readonly(), y.readonly())
+                    .unwrap()
+            })
+        });
+
+        // The shapes alone are a prepared GEOS geometry, as in shapely.
+        g.bench_function(BenchmarkId::new("geos", "serial"), |b| {
+            b.iter(|| {
+                xs.iter()
+                    .zip(&ys)
+                    .filter(|(x, y)| shapes.contains(**x, **y))
+                    .count()
+            })
+        });
+        g.bench_function(BenchmarkId::new("geos", "parallel"), |b| {
+            b.iter(|| {
+                shapes
+                    .contains_many_par(py, x.readonly(), y.readonly())
+                    .unwrap()
+            })
+        });
+
+        g.finish();
+    })
+}
+
+criterion_group!(benches, comparison);
+criterion_main!(benches);
@@ -0,0 +1,181 @@
+//! Benchmarks comparing the stages of the landmask and the batch methods over different input
+//! sizes. Run with:
+//!
+//! ```sh
+//! cargo bench --bench landmask
+//! ```
+//!
+//! The points are on a regular global grid, so results are comparable across runs.
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use numpy::PyArray;
+use pyo3::prelude::*;
+use roaring_landmask::{Gshhg, RoaringLandmask, RoaringMask};
+
+/// A regular global grid with about `n` points.
+fn grid(n: usize) -> (Vec<f64>, Vec<f64>) {
+    let ny = ((n / 2) as f64).sqrt().max(1.) as usize;
+    let nx = n / ny;
+
+    (0..nx)
+        .flat_map(|i| {
+            (0..ny).map(move |j| {
+                (
+                    -180. + 360. * i as f64 / nx as f64,
+                    -89.9 + 179.8 * j as f64 / ny as f64,
+                )
+            })
+        })
+        .unzip()
+}
+
+fn contains(c: &mut Criterion) {
+    pyo3::prepare_freethreaded_python();
+    Python::with_gil(|py| {
+        let landmask = RoaringLandmask::new(py).unwrap();
+        let mask = RoaringMask::new().unwrap();
+        let shapes = Gshhg::new(py).unwrap();
+        let instant = RoaringLandmask::instant();
+
+        let mut g = c.benchmark_group("contains");
+
+        for (name, (x, y)) in [("land", (15., 65.6)), ("ocean", (5., 65.6))] {
+            g.bench_with_input(BenchmarkId::new("landmask", name), &(x, y), |b, (x, y)| {
+                b.iter(|| landmask.contains(*x, *y))
+            });
+            g.bench_with_input(
+                BenchmarkId::new("landmask_raw", name),
+                &(x, y),
+                |b, (x, y)| b.iter(|| landmask.contains_raw(*x, *y)),
+            );
+            g.bench_with_input(BenchmarkId::new("mask", name), &(x, y), |b, (x, y)| {
+                b.iter(|| mask.contains(*x, *y))
+            });
+            g.bench_with_input(BenchmarkId::new("shapes", name), &(x, y), |b, (x, y)| {
+                b.iter(|| shapes.contains(*x, *y))
+            });
+            g.bench_with_input(BenchmarkId::new("instant", name), &(x, y), |b, (x, y)| {
+                b.iter(|| instant.contains(*x, *y))
+            });
+        }
+
+        g.finish();
+    })
+}
+
+fn contains_many(c: &mut Criterion) {
+    pyo3::prepare_freethreaded_python();
+    Python::with_gil(|py| {
+        let landmask = RoaringLandmask::new(py).unwrap();
+
+        let mut g = c.benchmark_group("contains_many");
+        g.sample_size(10);
+
+        for n in [100, 10_000, 1_000_000] {
+            let (x, y) = grid(n);
+            let x = PyArray::from_vec(py, x).to_dyn();
+            let y = PyArray::from_vec(py, y).to_dyn();
+
+            g.throughput(Throughput::Elements(x.len() as u64));
+
+            g.bench_with_input(BenchmarkId::new("serial", n), &n, |b, _| {
+                b.iter(|| {
+                    landmask
+                        .contains_many(py, x.readonly(), y.readonly())
+                        .unwrap()
+                })
+            });
+            g.bench_with_input(BenchmarkId::new("parallel", n), &n, |b, _| {
+                b.iter(|| {
+                    landmask
+                        .contains_many_par(py, x.readonly(), y.readonly())
+                        .unwrap()
+                })
+            });
+        }
+
+        g.finish();
+    })
+}
+
+fn track(c: &mut Criterion) {
+    pyo3::prepare_freethreaded_python();
+    Python::with_gil(|py| {
+        let landmask = RoaringLandmask::new(py).unwrap();
+
+        // From the Norwegian Sea onto Norway, with many points in each cell.
+        let track = (0..10_000)
+            .map(|i| (5. + 10. * i as f64 / 10_000., 65.6))
+            .collect::<Vec<_>>();
+
+        let mut g = c.benchmark_group("track");
+        g.throughput(Throughput::Elements(track.len() as u64));
+
+        g.bench_function("contains", |b| {
+            b.iter(|| {
+                track
+                    .iter()
+                    .filter(|(x, y)| landmask.contains(*x, *y))
+                    .count()
+            })
+        });
+        g.bench_function("context", |b| {
+            b.iter(|| {
+                let mut ctx = landmask.context();
+                track.iter().filter(|(x, y)| ctx.contains(*x, *y)).count()
+            })
+        });
+
+        g.finish();
+    })
+}
+
+fn distance_to_shore(c: &mut Criterion) {
+    pyo3::prepare_freethreaded_python();
+    Python::with_gil(|py| {
+        let landmask = RoaringLandmask::new(py).unwrap();
+
+        let mut g = c.benchmark_group("distance_to_shore");
+
+        for (name, (x, y)) in [("coast", (5., 65.6)), ("open_ocean", (-123.4, -48.9))] {
+            g.bench_with_input(BenchmarkId::from_parameter(name), &(x, y), |b, (x, y)| {
+                b.iter(|| landmask.distance_to_shore(*x, *y))
+            });
+        }
+
+        g.finish();
+    })
+}
+
+fn distance_to_shore_many(c: &mut Criterion) {
+    pyo3::prepare_freethreaded_python();
+    Python::with_gil(|py| {
+        // A dense grid off the coast of Norway, as for a coastal distance field.
+        let points = (0..10_000)
+            .map(|i| (4. + (i % 100) as f64 * 0.01, 60. + (i / 100) as f64 * 0.01))
+            .collect::<Vec<_>>();
+
+        let mut g = c.benchmark_group("distance_to_shore_many");
+        g.throughput(Throughput::Elements(points.len() as u64));
+
+        for cache in [false, true] {
+            let landmask = RoaringLandmask::new(py).unwrap().with_distance_cache(cache);
+            let name = if cache { "cached" } else { "uncached" };
+
+            g.bench_function(name, |b| {
+                b.iter(|| landmask.distance_to_shore_many_blocking(&points))
+            });
+        }
+
+        g.finish();
+    })
+}
+
+criterion_group!(
+    benches,
+    contains,
+    contains_many,
+    track,
+    distance_to_shore,
+    distance_to_shore_many
+);
+criterion_main!(benches);
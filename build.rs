@@ -4,12 +4,22 @@ use std::io::prelude::*;
 use std::path::Path;
 use path_slash::PathExt;
 
+/// Release of GSHHG that the shapes and the mask are made from, update it together with the
+/// checksums of the data.
+pub static GSHHG_VERSION: &str = "2.3.7";
+
 pub static GSHHS_F: &str = "gshhs_f_-180.000000E-90.000000N180.000000E90.000000N.wkb.xz";
 pub static GSHHS_F_CS: &str = "05bdf3089407b9829a7a5be7ee43f1e4205f2bbc641e4778af77e4814be216da";
 
 pub static MASK: &str = "mask.tbmap.xz";
 pub static MASK_CS: &str = "5ea0e772ffc6ca8ad10c5de02be50670cbaedcff20b3541df6b78d3e1fdf48a1";
 
+pub static CRUDE: &str = "crude.bmap.xz";
+pub static CRUDE_CS: &str = "904907d050572533c62593b7ab5de747ae5fca701d9fdacd12dfc0a78e5687e2";
+
+pub static TILES: &str = "mask.tiles";
+pub static TILES_CS: &str = "735d44a9128f78d6a1bb497ec29b057a3ec2611c56939d2612783dc383a7a188";
+
 fn main() {
     println!("hello");
 
@@ -18,38 +28,110 @@ fn main() {
 
     let assets_dir = Path::new(&out_dir).join("gshhs");
 
+    // The data is only embedded with the `embed` feature, without it the library is small and
+    // the data has to be loaded from files or downloaded.
+    let embed = env::var_os("CARGO_FEATURE_EMBED").is_some();
+
+    // The tiled mask is a second copy of the mask, it is only embedded with the `tiles` feature.
+    let tiles = env::var_os("CARGO_FEATURE_TILES").is_some();
+
     // write assets script
     let assets = Path::new(&out_dir).join("gshhs.rs");
     {
         let mut fd = fs::File::create(assets).unwrap();
-        write!(
-            fd,
-            "
+        if embed || tiles {
+            write!(
+                fd,
+                "
 use rust_embed::RustEmbed;
 #[derive(RustEmbed)]
 #[folder = \"{}\"]
 pub struct GsshgData;
+            ",
+                assets_dir.to_slash().unwrap(),
+            )
+            .unwrap();
+        } else {
+            write!(
+                fd,
+                "
+/// A file embedded in the library.
+pub struct EmbeddedFile {{
+    pub data: std::borrow::Cow<'static, [u8]>,
+}}
+
+/// Built without the `embed` feature: no data is embedded.
+pub struct GsshgData;
+
+impl GsshgData {{
+    pub fn get(_name: &str) -> Option<EmbeddedFile> {{
+        None
+    }}
+}}
+            "
+            )
+            .unwrap();
+        }
 
+        write!(
+            fd,
+            "
+pub static GSHHG_VERSION: &str = \"{}\";
+pub static GSHHS_F_SHA256: &str = \"{}\";
+pub static MASK_SHA256: &str = \"{}\";
         ",
-            assets_dir.to_slash().unwrap()
+            GSHHG_VERSION, GSHHS_F_CS, MASK_CS,
         )
         .unwrap();
     }
 
+    // Seconds since the epoch when the data was embedded, respecting reproducible builds.
+    let build_time = env::var("SOURCE_DATE_EPOCH")
+        .ok()
+        .and_then(|t| t.parse::<u64>().ok())
+        .unwrap_or_else(|| {
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_secs()
+        });
+    println!("cargo:rustc-env=ROARING_LANDMASK_BUILD_TIME={}", build_time);
+
     let gshhs = Path::new(&out_dir).join("gshhs");
     if !gshhs.exists() {
         fs::create_dir(gshhs).unwrap();
     }
 
     // copy or download files
-    if env::var("DOCS_RS").is_err() {
-        copy_or_download(GSHHS_F, GSHHS_F_CS);
-        copy_or_download(MASK, MASK_CS);
+    if !embed && !tiles {
+        println!("not embedding any data without the embed or tiles features.");
+    } else if env::var("DOCS_RS").is_err() {
+        if embed {
+            copy_or_download(GSHHS_F, GSHHS_F_CS);
+            copy_or_download(MASK, MASK_CS);
+            copy(CRUDE, CRUDE_CS);
+        }
+
+        if tiles {
+            copy(TILES, TILES_CS);
+        }
     } else {
         println!("not downloading anything when on docs.rs.");
     }
 }
 
+/// Copy a file which is not in the upstream repository, it is shipped in the package instead.
+fn copy(from: impl AsRef<Path>, csum: &str) {
+    let from = from.as_ref();
+    let full_from = Path::new("gshhs").join(&from);
+
+    if !full_from.exists() {
+        panic!("{:?} is missing from the package..", &from);
+    }
+
+    copy_or_download(from, csum);
+}
+
 fn copy_or_download(from: impl AsRef<Path>, csum: &str) {
     let from = from.as_ref();
 
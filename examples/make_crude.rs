@@ -0,0 +1,23 @@
+//! Make the crude mask embedded in the library from the full mask:
+//!
+//! ```sh
+//! cargo run --release --example make_crude -- gshhs/crude.bmap.xz
+//! ```
+//!
+//! Update `CRUDE_CS` in `build.rs` with the new checksum.
+
+use roaring_landmask::{CrudeMask, RoaringMask};
+use std::fs::File;
+use std::io;
+
+fn main() -> io::Result<()> {
+    let path = std::env::args()
+        .nth(1)
+        .unwrap_or_else(|| "gshhs/crude.bmap.xz".to_string());
+
+    println!("making crude mask from the full mask..");
+    let crude = CrudeMask::from_mask(&RoaringMask::new()?);
+
+    println!("writing {}..", path);
+    crude.write(File::create(path)?)
+}
@@ -0,0 +1,39 @@
+//! Make the table of the instant mask compiled into the library from the full mask, and write
+//! it into `src/instant.rs`:
+//!
+//! ```sh
+//! cargo run --release --example make_instant -- src/instant.rs
+//! ```
+
+use roaring_landmask::{InstantMask, RoaringMask};
+use std::io;
+
+fn main() -> io::Result<()> {
+    let path = std::env::args()
+        .nth(1)
+        .unwrap_or_else(|| "src/instant.rs".to_string());
+
+    println!("making table from the full mask..");
+    let table = InstantMask::table_from_mask(&RoaringMask::new()?);
+
+    let rows = table
+        .chunks(4)
+        .map(|words| {
+            let words = words
+                .iter()
+                .map(|w| format!("0x{:016x},", w))
+                .collect::<Vec<_>>();
+            format!("    {}\n", words.join(" "))
+        })
+        .collect::<String>();
+
+    println!("writing table to {}..", path);
+    let source = std::fs::read_to_string(&path)?;
+
+    let start = "static TABLE: [u64; (NX * NY + 63) / 64] = [\n";
+    let invalid = || io::Error::new(io::ErrorKind::InvalidData, "cannot find the table");
+    let a = source.find(start).ok_or_else(invalid)? + start.len();
+    let b = a + source[a..].find("];\n").ok_or_else(invalid)?;
+
+    std::fs::write(&path, [&source[..a], &rows, &source[b..]].concat())
+}
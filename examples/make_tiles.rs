@@ -0,0 +1,22 @@
+//! Make the tiled mask embedded with the `tiles` feature from the full mask:
+//!
+//! ```sh
+//! cargo run --release --example make_tiles -- gshhs/mask.tiles
+//! ```
+//!
+//! Update `TILES_CS` in `build.rs` with the new checksum.
+
+use roaring_landmask::{RoaringMask, TiledMask};
+use std::io;
+
+fn main() -> io::Result<()> {
+    let path = std::env::args()
+        .nth(1)
+        .unwrap_or_else(|| "gshhs/mask.tiles".to_string());
+
+    println!("tiling the full mask..");
+    TiledMask::write(&RoaringMask::new()?, &path)?;
+
+    println!("wrote {}.", path);
+    Ok(())
+}
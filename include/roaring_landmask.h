@@ -0,0 +1,53 @@
+#ifndef ROARING_LANDMASK_H
+#define ROARING_LANDMASK_H
+
+/* Generated with cbindgen from src/capi.rs, do not edit. */
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+typedef struct RoaringLandmask RoaringLandmask;
+
+/**
+ * Make a landmask from the embedded mask and shapes. Returns `NULL` if it cannot be made.
+ *
+ * The data is decoded on the first call and shared by all landmasks, see
+ * `RoaringLandmask::new`.
+ */
+struct RoaringLandmask *rlm_new(void);
+
+/**
+ * Check if point (`lon`, `lat`) is on land.
+ *
+ * # Safety
+ *
+ * `handle` must be a landmask from `rlm_new` which has not been freed.
+ */
+bool rlm_contains(const struct RoaringLandmask *handle, double lon, double lat);
+
+/**
+ * Check which of the `n` points (`xs[i]`, `ys[i]`) are on land, in parallel, and write the
+ * result to `out`. Returns 0 on success and -1 if any pointer is `NULL`.
+ *
+ * # Safety
+ *
+ * `handle` must be a landmask from `rlm_new` which has not been freed, and `xs`, `ys` and `out`
+ * must each point to `n` values.
+ */
+int32_t rlm_contains_many(const struct RoaringLandmask *handle,
+                          const double *xs,
+                          const double *ys,
+                          size_t n,
+                          bool *out);
+
+/**
+ * Release a landmask made with `rlm_new`. Does nothing for `NULL`.
+ *
+ * # Safety
+ *
+ * `handle` must be `NULL` or a landmask from `rlm_new` which has not been freed.
+ */
+void rlm_free(struct RoaringLandmask *handle);
+
+#endif /* ROARING_LANDMASK_H */
@@ -0,0 +1,2 @@
+from .roaring_landmask import *
+from .interop import to_xarray, contains_geodataframe
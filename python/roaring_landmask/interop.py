@@ -0,0 +1,58 @@
+# Helpers for using the landmask together with other Python libraries. The
+# libraries are imported when needed, they are not dependencies of the package.
+
+from .roaring_landmask import RoaringLandmask
+
+def to_xarray(mask, step = 1):
+    """
+    Get the mask as an `xarray.DataArray` with latitude and longitude coordinates
+    of the cell centers, sampled every `step` cell.
+
+    `mask` is either a `RoaringLandmask` or a `RoaringMask`.
+    """
+    import xarray as xr
+
+    if isinstance(mask, RoaringLandmask):
+        mask = mask.mask
+
+    return xr.DataArray(
+        mask.to_numpy(step),
+        dims = ('lat', 'lon'),
+        coords = {
+            'lat': ('lat', mask.lats(step), { 'units': 'degrees_north', 'standard_name': 'latitude' }),
+            'lon': ('lon', mask.lons(step), { 'units': 'degrees_east', 'standard_name': 'longitude' }),
+        },
+        attrs = {
+            'crs': 'EPSG:4326',
+            'dx': mask.dx * step,
+            'dy': mask.dy * step,
+            'description': 'GSHHG landmask, true on land or close to the shore',
+        },
+        name = 'land',
+    )
+
+def contains_geodataframe(gdf, mask = None):
+    """
+    Check which points in the `geopandas.GeoDataFrame` (or `GeoSeries`) `gdf`
+    are on land. Returns a boolean `pandas.Series` with the same index as `gdf`.
+
+    The points are reprojected to longitude and latitude (EPSG:4326) if `gdf`
+    has another CRS, all geometries must be points.
+
+    `mask` is a `RoaringLandmask` (or `RoaringMask`), a new `RoaringLandmask` is
+    loaded if it is not given.
+    """
+    import numpy as np
+    import pandas as pd
+
+    if mask is None:
+        mask = RoaringLandmask.new()
+
+    if gdf.crs is not None and gdf.crs.to_epsg() != 4326:
+        gdf = gdf.to_crs(epsg = 4326)
+
+    points = gdf.geometry
+    x = np.ascontiguousarray(points.x, dtype = np.float64)
+    y = np.ascontiguousarray(points.y, dtype = np.float64)
+
+    return pd.Series(mask.contains_many_par(x, y), index = gdf.index, name = 'land')
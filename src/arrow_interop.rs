@@ -0,0 +1,84 @@
+//! Batch queries on Apache Arrow arrays, with the `arrow` feature.
+//!
+//! The coordinates are read straight from the buffers of the input arrays, and the result is an
+//! Arrow `BooleanArray` which can be handed to Polars or pyarrow without converting it from
+//! numpy. From Python the arrays are passed through the Arrow C data interface:
+//!
+//! ```python
+//! import pyarrow as pa
+//! import polars as pl
+//!
+//! x = pa.array([15., 5.])
+//! y = pa.array([65.6, 65.6])
+//! land = pl.from_arrow(landmask.contains_many_arrow(x, y))
+//! ```
+//!
+//! The feature pulls in the core crates of `arrow` (buffers, arrays, schema and the FFI), which
+//! adds a few hundred kilobytes to the library and noticeably to the build time, so it is off by
+//! default.
+
+use arrow::array::{Array, ArrayData, BooleanArray, Float64Array};
+use arrow::buffer::{BooleanBuffer, NullBuffer};
+use arrow::datatypes::DataType;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+
+use crate::RoaringLandmask;
+
+impl RoaringLandmask {
+    /// Check which of the points (`x[i]`, `y[i]`) are on land, in parallel, see
+    /// `contains_many_blocking`. A point where `x` or `y` is null is null in the result.
+    ///
+    /// Panics if `x` and `y` have different lengths.
+    pub fn contains_many_arrow(&self, x: &Float64Array, y: &Float64Array) -> BooleanArray {
+        assert_eq!(x.len(), y.len());
+
+        // The values behind nulls are undefined, so they are not checked.
+        let nulls = NullBuffer::union(x.nulls(), y.nulls());
+        let points = (0..x.len())
+            .map(|i| match &nulls {
+                Some(nulls) if nulls.is_null(i) => (0., 0.),
+                _ => (x.value(i), y.value(i)),
+            })
+            .collect::<Vec<_>>();
+
+        let land = self.contains_many_blocking(&points);
+        BooleanArray::new(BooleanBuffer::collect_bool(land.len(), |i| land[i]), nulls)
+    }
+}
+
+/// The `float64` array in `data`, or a `ValueError` naming the argument.
+pub(crate) fn float64(data: ArrayData, name: &str) -> PyResult<Float64Array> {
+    if data.data_type() != &DataType::Float64 {
+        return Err(PyValueError::new_err(format!(
+            "{} must be a float64 array, got {}",
+            name,
+            data.data_type()
+        )));
+    }
+
+    Ok(Float64Array::from(data))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn contains_many_arrow() {
+        pyo3::prepare_freethreaded_python();
+        let landmask = pyo3::Python::with_gil(|py| RoaringLandmask::new(py).unwrap());
+
+        let x = Float64Array::from(vec![Some(15.), Some(5.), None, Some(15.)]);
+        let y = Float64Array::from(vec![Some(65.6), Some(65.6), Some(65.6), None]);
+
+        let land = landmask.contains_many_arrow(&x, &y);
+        assert_eq!(
+            land.iter().collect::<Vec<_>>(),
+            vec![Some(true), Some(false), None, None]
+        );
+
+        let data = arrow::array::Int32Array::from(vec![1]).into_data();
+        assert!(float64(data, "x").is_err());
+    }
+}
@@ -0,0 +1,250 @@
+//! A single file with both the mask and the shapes of a landmask.
+//!
+//! The bundle starts with a header with the format version, the SHA-256 of the shapes (as WKB),
+//! the grid size of the mask and the CRC32 of the payload, followed by the xz-compressed mask and
+//! shapes. A bundle written by another version, whose shapes do not match the SHA-256, for
+//! another grid or which has been corrupted is rejected when loading rather than giving wrong
+//! answers. Another version or shapes give a `LandmaskError` inside the `io::Error`.
+//!
+//! ```no_run
+//! # pyo3::prepare_freethreaded_python();
+//! # pyo3::Python::with_gil(|py| {
+//! use roaring_landmask::RoaringLandmask;
+//!
+//! let landmask = RoaringLandmask::new(py).unwrap();
+//! landmask.save_bundle("landmask.bundle").unwrap();
+//!
+//! let landmask = RoaringLandmask::load_bundle("landmask.bundle").unwrap();
+//! assert!(landmask.contains(15., 65.6));
+//! # });
+//! ```
+//!
+//! Only the mask and shapes are stored: shallow water from `with_bathymetry` and a custom
+//! `PointInPolygon` are not part of the bundle.
+
+use roaring::RoaringTreemap;
+use std::convert::TryInto;
+use std::fs::File;
+use std::io::{self, prelude::*};
+use std::path::Path;
+
+use crate::error::LandmaskError;
+use crate::mask::{RoaringMask, NX, NY};
+use crate::shapes::Gshhg;
+use crate::RoaringLandmask;
+
+static MAGIC: &[u8; 4] = b"RLMB";
+const VERSION: u32 = 2;
+
+/// Length of the hex SHA-256 of the shapes.
+const SOURCE: usize = 64;
+
+/// Magic, version, source, NX, NY, length of mask and shapes, and the CRC.
+const HEADER: usize = 4 + 4 + SOURCE + 8 + 8 + 8 + 8 + 4;
+
+/// The source data of the bundled shapes: the hex SHA-256 of their WKB.
+fn source(wkb: &[u8]) -> String {
+    use ring::digest;
+
+    digest::digest(&digest::SHA256, wkb)
+        .as_ref()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+fn invalid(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+fn compress(buf: &[u8]) -> io::Result<Vec<u8>> {
+    let mut enc = xz2::write::XzEncoder::new(Vec::new(), 9);
+    enc.write_all(buf)?;
+    enc.finish()
+}
+
+fn u32_at(buf: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(buf[offset..offset + 4].try_into().unwrap())
+}
+
+fn u64_at(buf: &[u8], offset: usize) -> u64 {
+    u64::from_le_bytes(buf[offset..offset + 8].try_into().unwrap())
+}
+
+/// Write the mask and shapes of `landmask` to `path`.
+pub fn save<P: AsRef<Path>>(landmask: &RoaringLandmask, path: P) -> io::Result<()> {
+    let mut mask = Vec::new();
+    landmask.mask.tmap.serialize_into(&mut mask)?;
+    let mask = compress(&mask)?;
+    let wkb = landmask.shapes.to_wkb()?;
+    let shapes = compress(&wkb)?;
+
+    let mut crc = crc32fast::Hasher::new();
+    crc.update(&mask);
+    crc.update(&shapes);
+
+    let mut fd = io::BufWriter::new(File::create(path)?);
+    fd.write_all(MAGIC)?;
+    fd.write_all(&VERSION.to_le_bytes())?;
+    fd.write_all(source(&wkb).as_bytes())?;
+    fd.write_all(&NX.to_le_bytes())?;
+    fd.write_all(&NY.to_le_bytes())?;
+    fd.write_all(&(mask.len() as u64).to_le_bytes())?;
+    fd.write_all(&(shapes.len() as u64).to_le_bytes())?;
+    fd.write_all(&crc.finalize().to_le_bytes())?;
+    fd.write_all(&mask)?;
+    fd.write_all(&shapes)?;
+    fd.flush()
+}
+
+/// Read a landmask written by `save` from `path`.
+pub fn load<P: AsRef<Path>>(path: P) -> io::Result<RoaringLandmask> {
+    let mut buf = Vec::new();
+    File::open(path)?.read_to_end(&mut buf)?;
+
+    if buf.len() < HEADER || &buf[..4] != MAGIC {
+        return Err(invalid("not a landmask bundle"));
+    }
+
+    let version = u32_at(&buf, 4);
+    if version != VERSION {
+        return Err(LandmaskError::IncompatibleVersion {
+            found: version,
+            expected: VERSION,
+        }
+        .into());
+    }
+
+    let h = 8 + SOURCE;
+    if u64_at(&buf, h) != NX || u64_at(&buf, h + 8) != NY {
+        return Err(invalid("landmask bundle has a different grid"));
+    }
+
+    let mask_len = u64_at(&buf, h + 16) as usize;
+    let shapes_len = u64_at(&buf, h + 24) as usize;
+    let payload = &buf[HEADER..];
+
+    if mask_len.checked_add(shapes_len) != Some(payload.len()) {
+        return Err(invalid("landmask bundle is truncated"));
+    }
+
+    if crc32fast::hash(payload) != u32_at(&buf, h + 32) {
+        return Err(invalid("landmask bundle is corrupt"));
+    }
+
+    let (mask, shapes) = payload.split_at(mask_len);
+
+    let tmap = RoaringTreemap::deserialize_from(xz2::read::XzDecoder::new(mask))?;
+
+    let mut wkb = Vec::new();
+    xz2::read::XzDecoder::new(shapes).read_to_end(&mut wkb)?;
+
+    let found = &buf[8..8 + SOURCE];
+    let expected = source(&wkb);
+    if found != expected.as_bytes() {
+        return Err(LandmaskError::IncompatibleSource {
+            found: String::from_utf8_lossy(found).into_owned(),
+            expected,
+        }
+        .into());
+    }
+
+    Ok(RoaringLandmask::from_parts(
+        RoaringMask::from(tmap),
+        Gshhg::from_wkb(&wkb)?,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use geos::Geometry;
+
+    fn landmask() -> RoaringLandmask {
+        let mut tmap = RoaringTreemap::new();
+        for y in 34560..34800 {
+            tmap.insert_range(y * NX + 45600..y * NX + 45840);
+        }
+
+        let g = Geometry::new_from_wkt("POLYGON((10 54, 11 54, 11 55, 10 55, 10 54))").unwrap();
+
+        RoaringLandmask::from_parts(RoaringMask::from(tmap), Gshhg::from_geom(g).unwrap())
+    }
+
+    #[test]
+    fn save_and_load() {
+        let landmask = landmask();
+        let path = std::env::temp_dir().join("roaring-landmask-test.bundle");
+        save(&landmask, &path).unwrap();
+
+        let loaded = load(&path).unwrap();
+        assert_eq!(loaded.mask.tmap, landmask.mask.tmap);
+        assert_eq!(loaded.shapes.polygons(), 1);
+        assert!(loaded.contains(10.5, 54.5));
+        assert!(!loaded.contains(12., 54.5));
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn corrupt() {
+        let path = std::env::temp_dir().join("roaring-landmask-test-corrupt.bundle");
+        save(&landmask(), &path).unwrap();
+
+        let mut buf = std::fs::read(&path).unwrap();
+        let n = buf.len();
+        buf[n - 10] ^= 0xff;
+        std::fs::write(&path, &buf).unwrap();
+        let err = load(&path).err().unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert!(err.to_string().contains("corrupt"));
+
+        buf[4] = VERSION as u8 + 1;
+        std::fs::write(&path, &buf).unwrap();
+        assert!(load(&path).err().unwrap().to_string().contains("version"));
+
+        std::fs::write(&path, &buf[..20]).unwrap();
+        assert!(load(&path).is_err());
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn incompatible() {
+        let path = std::env::temp_dir().join("roaring-landmask-test-incompatible.bundle");
+        save(&landmask(), &path).unwrap();
+        let buf = std::fs::read(&path).unwrap();
+
+        let mut bumped = buf.clone();
+        bumped[4..8].copy_from_slice(&(VERSION + 1).to_le_bytes());
+        std::fs::write(&path, &bumped).unwrap();
+
+        let err = load(&path).err().unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(
+            LandmaskError::from_io(&err),
+            Some(&LandmaskError::IncompatibleVersion {
+                found: VERSION + 1,
+                expected: VERSION,
+            })
+        );
+
+        let mut other = buf.clone();
+        other[8..8 + SOURCE].copy_from_slice(&[b'0'; SOURCE]);
+        std::fs::write(&path, &other).unwrap();
+
+        let wkb = landmask().shapes.to_wkb().unwrap();
+        let err = load(&path).err().unwrap();
+        assert!(matches!(
+            LandmaskError::from_io(&err),
+            Some(LandmaskError::IncompatibleSource { expected, .. }) if *expected == source(&wkb)
+        ));
+        assert_eq!(&buf[8..8 + SOURCE], source(&wkb).as_bytes());
+
+        // The unchanged bundle still loads.
+        std::fs::write(&path, &buf).unwrap();
+        assert!(load(&path).is_ok());
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,122 @@
+//! A C interface to the landmask, enabled with the `capi` feature.
+//!
+//! The functions are exported from the `cdylib`, and declared in `include/roaring_landmask.h`.
+//! The header is generated with [cbindgen](https://github.com/mozilla/cbindgen):
+//!
+//! ```sh
+//! cbindgen --config cbindgen.toml --output include/roaring_landmask.h
+//! ```
+//!
+//! A landmask is made with `rlm_new` and must be released with `rlm_free`:
+//!
+//! ```c
+//! #include "roaring_landmask.h"
+//!
+//! RoaringLandmask *mask = rlm_new();
+//! bool land = rlm_contains(mask, 15., 65.6);
+//! rlm_free(mask);
+//! ```
+//!
+//! Points with a latitude beyond ±90 or NaN are in the ocean (`OutOfDomain::ReturnFalse`), so
+//! that no call panics across the interface.
+
+use std::slice;
+
+use crate::{OutOfDomain, RoaringLandmask};
+
+/// Make a landmask from the embedded mask and shapes. Returns `NULL` if it cannot be made.
+///
+/// The data is decoded on the first call and shared by all landmasks, see
+/// `RoaringLandmask::new`.
+#[no_mangle]
+pub extern "C" fn rlm_new() -> *mut RoaringLandmask {
+    match RoaringLandmask::embedded() {
+        Ok(landmask) => Box::into_raw(Box::new(
+            landmask.with_out_of_domain(OutOfDomain::ReturnFalse),
+        )),
+        Err(_) => std::ptr::null_mut(),
+    }
+}
+
+/// Check if point (`lon`, `lat`) is on land.
+///
+/// # Safety
+///
+/// `handle` must be a landmask from `rlm_new` which has not been freed.
+#[no_mangle]
+pub unsafe extern "C" fn rlm_contains(handle: *const RoaringLandmask, lon: f64, lat: f64) -> bool {
+    (*handle).contains(lon, lat)
+}
+
+/// Check which of the `n` points (`xs[i]`, `ys[i]`) are on land, in parallel, and write the
+/// result to `out`. Returns 0 on success and -1 if any pointer is `NULL`.
+///
+/// # Safety
+///
+/// `handle` must be a landmask from `rlm_new` which has not been freed, and `xs`, `ys` and `out`
+/// must each point to `n` values.
+#[no_mangle]
+pub unsafe extern "C" fn rlm_contains_many(
+    handle: *const RoaringLandmask,
+    xs: *const f64,
+    ys: *const f64,
+    n: usize,
+    out: *mut bool,
+) -> i32 {
+    if handle.is_null() || xs.is_null() || ys.is_null() || out.is_null() {
+        return -1;
+    }
+
+    let (xs, ys) = (slice::from_raw_parts(xs, n), slice::from_raw_parts(ys, n));
+    let out = slice::from_raw_parts_mut(out, n);
+
+    out.copy_from_slice(&(*handle).contains_slice_par(xs, ys));
+    0
+}
+
+/// Release a landmask made with `rlm_new`. Does nothing for `NULL`.
+///
+/// # Safety
+///
+/// `handle` must be `NULL` or a landmask from `rlm_new` which has not been freed.
+#[no_mangle]
+pub unsafe extern "C" fn rlm_free(handle: *mut RoaringLandmask) {
+    if !handle.is_null() {
+        drop(Box::from_raw(handle));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn roundtrip() {
+        unsafe {
+            let handle = rlm_new();
+            assert!(!handle.is_null());
+
+            assert!(rlm_contains(handle, 15., 65.6));
+            assert!(!rlm_contains(handle, 5., 65.6));
+            assert!(!rlm_contains(handle, 5., 100.));
+
+            let xs = [15., 5., 0.];
+            let ys = [65.6, 65.6, f64::NAN];
+            let mut out = [false; 3];
+            assert_eq!(
+                rlm_contains_many(handle, xs.as_ptr(), ys.as_ptr(), 3, out.as_mut_ptr()),
+                0
+            );
+            assert_eq!(out, [true, false, false]);
+
+            let null = std::ptr::null();
+            assert_eq!(
+                rlm_contains_many(handle, null, ys.as_ptr(), 3, out.as_mut_ptr()),
+                -1
+            );
+
+            rlm_free(handle);
+            rlm_free(std::ptr::null_mut());
+        }
+    }
+}
@@ -0,0 +1,95 @@
+//! Per-caller state for checking many points in a row.
+
+use crate::RoaringLandmask;
+
+/// Checks points against a landmask while remembering the last cell of the mask and the last
+/// point checked against the shapes, made by `RoaringLandmask::context`.
+///
+/// Consecutive points of a track often fall in the same cell of the mask, so most of them skip
+/// the lookup in the bitmap, and repeated points skip the shapes as well. The landmask itself is
+/// not changed and can be shared between threads, each with its own context.
+pub struct QueryContext<'a> {
+    landmask: &'a RoaringLandmask,
+
+    /// The index of the last cell looked up in the mask, and whether it has land.
+    cell: Option<(u64, bool)>,
+
+    /// The last point checked against the shapes, and whether it is inside.
+    point: Option<((f64, f64), bool)>,
+}
+
+impl<'a> QueryContext<'a> {
+    pub fn new(landmask: &'a RoaringLandmask) -> QueryContext<'a> {
+        QueryContext {
+            landmask,
+            cell: None,
+            point: None,
+        }
+    }
+
+    /// Check if point (x, y) is on land, the same as `RoaringLandmask::contains`.
+    pub fn contains(&mut self, x: f64, y: f64) -> bool {
+        let landmask = self.landmask;
+        let (cell, point) = (&mut self.cell, &mut self.point);
+
+        landmask
+            .contains_staged(
+                x,
+                y,
+                None,
+                true,
+                |x, y| {
+                    let index = landmask.mask.index_unchecked(x, y);
+                    match *cell {
+                        Some((i, land)) if i == index => land,
+                        _ => {
+                            let land = landmask.mask.contains_index(index);
+                            *cell = Some((index, land));
+                            land
+                        }
+                    }
+                },
+                |x, y| match *point {
+                    Some(((px, py), land)) if px == x && py == y => land,
+                    _ => {
+                        let land = landmask.shapes_contains(x, y);
+                        *point = Some(((x, y), land));
+                        land
+                    }
+                },
+            )
+            .unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pyo3::prelude::*;
+
+    #[test]
+    fn same_as_contains() {
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| {
+            let landmask = RoaringLandmask::new(py).unwrap();
+            let mut ctx = landmask.context();
+
+            // From the Norwegian Sea onto Norway in small steps, and back.
+            let track = (0..2000)
+                .map(|i| 5. + 10. * i as f64 / 2000.)
+                .chain((0..2000).rev().map(|i| 5. + 10. * i as f64 / 2000.))
+                .map(|x| (x, 65.6));
+
+            let mut land = 0;
+            for (x, y) in track {
+                assert_eq!(ctx.contains(x, y), landmask.contains(x, y), "{x}, {y}");
+                land += ctx.contains(x, y) as usize;
+            }
+            assert!(land > 0 && land < 4000);
+
+            assert!(!ctx.contains(0., 90.));
+            assert!(ctx.contains(0., -90.));
+            assert!(ctx.contains(375., 65.6) == landmask.contains(15., 65.6));
+        })
+    }
+}
@@ -0,0 +1,158 @@
+//! Contour lines of a field sampled on a regular grid, traced with marching squares.
+
+use ndarray::ArrayView2;
+use std::collections::BTreeMap;
+
+/// A line of (x, y) coordinates. Closed lines end with their first point.
+pub type Line = Vec<(f64, f64)>;
+
+/// The lines where `field` crosses `level`. The rows of `field` are at `ys` and the columns at
+/// `xs`. Squares of the grid with a NaN corner are skipped, and saddles are resolved by the mean
+/// of the corners.
+///
+/// Lines which reach the edge of the grid are open, the others are closed.
+pub fn marching_squares(field: ArrayView2<f32>, xs: &[f64], ys: &[f64], level: f64) -> Vec<Line> {
+    let (ny, nx) = field.dim();
+    assert_eq!(xs.len(), nx);
+    assert_eq!(ys.len(), ny);
+
+    // The crossings are identified by the edge of the grid they are on: the edge east of
+    // (j, i) is `2 * (j * nx + i)`, and the edge north of it is one more.
+    let east = |j: usize, i: usize| 2 * (j * nx + i);
+    let north = |j: usize, i: usize| 2 * (j * nx + i) + 1;
+
+    let mut points: BTreeMap<usize, (f64, f64)> = BTreeMap::new();
+    let mut links: BTreeMap<usize, Vec<usize>> = BTreeMap::new();
+
+    let v = |j: usize, i: usize| field[[j, i]] as f64;
+    let crossing = |(j0, i0): (usize, usize), (j1, i1): (usize, usize)| {
+        let (v0, v1) = (v(j0, i0), v(j1, i1));
+        let t = (level - v0) / (v1 - v0);
+        let t = if t.is_finite() { t.clamp(0., 1.) } else { 0.5 };
+
+        (
+            xs[i0] + t * (xs[i1] - xs[i0]),
+            ys[j0] + t * (ys[j1] - ys[j0]),
+        )
+    };
+
+    for j in 0..ny.saturating_sub(1) {
+        for i in 0..nx.saturating_sub(1) {
+            // Counter-clockwise from the south-west corner.
+            let corners = [(j, i), (j, i + 1), (j + 1, i + 1), (j + 1, i)];
+            let values = corners.map(|(j, i)| v(j, i));
+            if values.iter().any(|v| v.is_nan()) {
+                continue;
+            }
+
+            let above = values.map(|v| v >= level);
+
+            // The sides of the square, between corner k and k + 1.
+            let sides = [east(j, i), north(j, i + 1), east(j + 1, i), north(j, i)];
+            let crossed: Vec<usize> = (0..4).filter(|&k| above[k] != above[(k + 1) % 4]).collect();
+
+            let segments = match crossed.len() {
+                2 => vec![(crossed[0], crossed[1])],
+                4 => {
+                    // A saddle: cut off the two corners which are not connected through the
+                    // center.
+                    let mean = values.iter().sum::<f64>() / 4.;
+                    if (mean >= level) == above[0] {
+                        vec![(0, 1), (2, 3)]
+                    } else {
+                        vec![(3, 0), (1, 2)]
+                    }
+                }
+                _ => vec![],
+            };
+
+            for (a, b) in segments {
+                for k in [a, b] {
+                    points
+                        .entry(sides[k])
+                        .or_insert_with(|| crossing(corners[k], corners[(k + 1) % 4]));
+                }
+
+                links.entry(sides[a]).or_default().push(sides[b]);
+                links.entry(sides[b]).or_default().push(sides[a]);
+            }
+        }
+    }
+
+    // Every crossing is linked to one or two others. Follow the open lines from their ends
+    // first, what is left are closed lines.
+    let mut lines = Vec::new();
+    let mut visited = std::collections::BTreeSet::new();
+
+    let starts = links
+        .iter()
+        .filter(|(_, l)| l.len() == 1)
+        .chain(links.iter().filter(|(_, l)| l.len() != 1))
+        .map(|(e, _)| *e)
+        .collect::<Vec<_>>();
+
+    for start in starts {
+        if !visited.insert(start) {
+            continue;
+        }
+
+        let mut line = vec![points[&start]];
+        let mut at = start;
+        while let Some(&next) = links[&at].iter().find(|e| !visited.contains(*e)) {
+            visited.insert(next);
+            line.push(points[&next]);
+            at = next;
+        }
+
+        if links[&start].len() == 2 {
+            line.push(points[&start]);
+        }
+
+        lines.push(line);
+    }
+
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ndarray::Array2;
+
+    #[test]
+    fn circle() {
+        // The distance from the center of a grid of 21 by 21 points.
+        let xs: Vec<f64> = (0..21).map(|i| i as f64).collect();
+        let field = Array2::from_shape_fn((21, 21), |(j, i)| {
+            ((j as f64 - 10.).powi(2) + (i as f64 - 10.).powi(2)).sqrt() as f32
+        });
+
+        let lines = marching_squares(field.view(), &xs, &xs, 5.);
+        assert_eq!(lines.len(), 1);
+
+        let line = &lines[0];
+        assert_eq!(line.first(), line.last());
+        assert!(line.len() > 20);
+        for (x, y) in line {
+            let r = ((x - 10.).powi(2) + (y - 10.).powi(2)).sqrt();
+            assert!((r - 5.).abs() < 0.1);
+        }
+    }
+
+    #[test]
+    fn open_and_saddle() {
+        // A field increasing to the east crosses the grid as one open line.
+        let xs = [0., 1., 2.];
+        let field = Array2::from_shape_fn((3, 3), |(_, i)| i as f32);
+        let lines = marching_squares(field.view(), &xs, &xs, 0.5);
+        assert_eq!(lines, vec![vec![(0.5, 0.), (0.5, 1.), (0.5, 2.)]]);
+
+        // A saddle gives two lines.
+        let field = ndarray::arr2(&[[1f32, 0.], [0., 1.]]);
+        let lines = marching_squares(field.view(), &xs[..2], &xs[..2], 0.6);
+        assert_eq!(lines.len(), 2);
+
+        // Outside the range of the field there are none.
+        assert!(marching_squares(field.view(), &xs[..2], &xs[..2], 2.).is_empty());
+    }
+}
@@ -0,0 +1,162 @@
+//! A crude landmask which is embedded in the library.
+//!
+//! The crude mask has a resolution of `FACTOR` cells of the full mask, about 5.5 km at the
+//! equator, and is not checked against the shapes. It takes about 3 MB of memory and loads in
+//! milliseconds, for quick checks where an error of a few kilometers near the coast is fine.
+//!
+//! The embedded `crude.bmap.xz` is made from the full mask with `CrudeMask::from_mask`, see
+//! `examples/make_crude.rs`.
+
+use numpy::{PyArray, PyReadonlyArrayDyn};
+use pyo3::prelude::*;
+use roaring::RoaringBitmap;
+use std::borrow::Borrow;
+use std::io::{self, prelude::*};
+
+use crate::mask::{self, RoaringMask, TRANSFORM};
+
+/// Number of cells of the full mask along each side of a cell in the crude mask.
+pub const FACTOR: u64 = 12;
+
+/// Number of cells along longitude.
+pub const NX: u64 = mask::NX / FACTOR;
+
+/// Number of cells along latitude.
+pub const NY: u64 = mask::NY / FACTOR;
+
+/// A crude landmask. A cell is land if at least half of the cells of the full mask within it
+/// are land.
+#[pyclass]
+#[derive(Clone, Debug, Default)]
+pub struct CrudeMask {
+    bitmap: RoaringBitmap,
+}
+
+impl CrudeMask {
+    /// Make a crude mask from `mask`.
+    pub fn from_mask(mask: &RoaringMask) -> CrudeMask {
+        CrudeMask {
+            bitmap: mask.coarsen(FACTOR),
+        }
+    }
+
+    /// Write the mask xz-compressed, as it is embedded.
+    pub fn write(&self, mut out: impl Write) -> io::Result<()> {
+        let mut enc = xz2::write::XzEncoder::new(Vec::new(), 9);
+        self.bitmap.serialize_into(&mut enc)?;
+        out.write_all(&enc.finish()?)
+    }
+}
+
+#[pymethods]
+impl CrudeMask {
+    /// Load the embedded crude mask, this does not read any files.
+    #[staticmethod]
+    pub fn new() -> io::Result<CrudeMask> {
+        use crate::GsshgData;
+
+        let buf = GsshgData::get("crude.bmap.xz").ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                "cannot find crude mask, built without the embed feature?",
+            )
+        })?;
+        let buf: &[u8] = buf.data.borrow();
+
+        let fd = xz2::read::XzDecoder::new(buf);
+        let bitmap = RoaringBitmap::deserialize_unchecked_from(fd)?;
+
+        Ok(CrudeMask { bitmap })
+    }
+
+    #[getter]
+    pub fn dx(&self) -> f64 {
+        360. / NX as f64
+    }
+
+    #[getter]
+    pub fn dy(&self) -> f64 {
+        180. / NY as f64
+    }
+
+    /// Check if point (x, y) is on land.
+    ///
+    /// `x` is longitude, [-180, 180] east
+    /// `y` is latitude,  [- 90,  90] north
+    pub fn contains(&self, x: f64, y: f64) -> bool {
+        assert!(y >= -90. && y <= 90.);
+
+        if let Some(land) = crate::pole(y) {
+            return land;
+        }
+
+        let x = crate::modulate_longitude(x);
+
+        let (x, y) = TRANSFORM.apply(x, y);
+        let x = (x as u64 % mask::NX) / FACTOR;
+        let y = (y as u64).min(mask::NY - 1) / FACTOR;
+
+        self.bitmap.contains((y * NX + x) as u32)
+    }
+
+    pub fn contains_many(
+        &self,
+        py: Python,
+        x: PyReadonlyArrayDyn<f64>,
+        y: PyReadonlyArrayDyn<f64>,
+    ) -> PyResult<Py<PyArray<bool, numpy::Ix1>>> {
+        let x = x.as_array();
+        let y = y.as_array();
+        crate::check_shape(x.shape(), y.shape())?;
+
+        Ok(PyArray::from_iter(
+            py,
+            x.iter().zip(y.iter()).map(|(x, y)| self.contains(*x, *y)),
+        )
+        .to_owned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn contains() {
+        let crude = CrudeMask::new().unwrap();
+
+        assert!(crude.contains(15., 65.6));
+        assert!(!crude.contains(5., 65.6));
+        assert!(crude.contains(5., -90.));
+        assert!(!crude.contains(5., 90.));
+
+        assert_eq!(crude.contains(10., 60.), crude.contains(370., 60.));
+    }
+
+    #[test]
+    fn agrees_with_mask() {
+        let crude = CrudeMask::new().unwrap();
+        let mask = RoaringMask::new().unwrap();
+
+        let mut n = 0;
+        let mut agree = 0;
+        for x in (-180..180).map(f64::from) {
+            for y in (-89..90).map(f64::from) {
+                n += 1;
+                if crude.contains(x + 0.3, y + 0.3) == mask.contains(x + 0.3, y + 0.3) {
+                    agree += 1;
+                }
+            }
+        }
+
+        assert!(agree as f64 / n as f64 > 0.99, "agree: {} / {}", agree, n);
+    }
+
+    #[test]
+    fn generated_from_mask() {
+        let crude = CrudeMask::new().unwrap();
+        let mask = RoaringMask::new().unwrap();
+
+        assert_eq!(CrudeMask::from_mask(&mask).bitmap, crude.bitmap);
+    }
+}
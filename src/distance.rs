@@ -0,0 +1,329 @@
+//! Distances on the Earth.
+
+use pyo3::prelude::*;
+
+/// Mean radius of the Earth in meters.
+pub const EARTH_RADIUS: f64 = 6_371_008.8;
+
+/// Semi-major axis of the WGS84 ellipsoid in meters.
+pub const WGS84_A: f64 = 6_378_137.;
+
+/// Flattening of the WGS84 ellipsoid.
+pub const WGS84_F: f64 = 1. / 298.257_223_563;
+
+/// Unit of a distance.
+#[pyclass]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Unit {
+    Meters,
+    Kilometers,
+    NauticalMiles,
+}
+
+#[pymethods]
+impl Unit {
+    /// Number of meters in one unit.
+    pub fn meters(&self) -> f64 {
+        match self {
+            Unit::Meters => 1.,
+            Unit::Kilometers => 1000.,
+            Unit::NauticalMiles => 1852.,
+        }
+    }
+}
+
+/// The shape of the Earth for distances and areas, see `RoaringLandmask::with_earth`.
+///
+/// The sphere is off the WGS84 ellipsoid by up to about 0.6 % in distances and areas, depending
+/// on the latitude and direction. The ellipsoid takes an iterative solution for each distance,
+/// which makes it several times slower than the sphere.
+#[pyclass]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Earth {
+    /// A sphere with the mean radius `EARTH_RADIUS`, see `haversine`.
+    #[default]
+    Sphere,
+    /// The WGS84 ellipsoid, see `vincenty`.
+    Wgs84,
+}
+
+impl Earth {
+    /// Distance in meters between (x0, y0) and (x1, y1) given in degrees longitude and latitude.
+    pub fn distance(&self, x0: f64, y0: f64, x1: f64, y1: f64) -> f64 {
+        match self {
+            Earth::Sphere => haversine(x0, y0, x1, y1),
+            Earth::Wgs84 => vincenty(x0, y0, x1, y1),
+        }
+    }
+
+    /// Area in square meters of the closed `ring` (longitude, latitude), with the edges
+    /// following the parallels as they approach them. On the ellipsoid the latitudes are
+    /// replaced by authalic latitudes on the sphere of the same area, which keeps the area
+    /// between any two parallels and meridians exact.
+    pub fn ring_area_m2(&self, ring: &[(f64, f64)]) -> f64 {
+        let (r, sin) = match self {
+            Earth::Sphere => (EARTH_RADIUS, f64::sin as fn(f64) -> f64),
+            Earth::Wgs84 => (authalic_radius(), sin_authalic as fn(f64) -> f64),
+        };
+
+        let sum = ring
+            .windows(2)
+            .map(|w| {
+                let ((x0, y0), (x1, y1)) = (w[0], w[1]);
+                (x1 - x0).to_radians() * (sin(y0.to_radians()) + sin(y1.to_radians()))
+            })
+            .sum::<f64>();
+
+        sum.abs() * r * r / 2.
+    }
+}
+
+/// Distance in meters between (x0, y0) and (x1, y1) given in degrees longitude and latitude
+/// along the geodesic on the WGS84 ellipsoid, with the inverse method of Vincenty (1975),
+/// accurate to well below a millimeter.
+///
+/// The iteration does not converge for nearly antipodal points, for which the great-circle
+/// distance from `haversine` is returned instead.
+pub fn vincenty(x0: f64, y0: f64, x1: f64, y1: f64) -> f64 {
+    let (a, f) = (WGS84_A, WGS84_F);
+    let b = a * (1. - f);
+
+    let l = (x1 - x0).to_radians();
+    let u1 = ((1. - f) * y0.to_radians().tan()).atan();
+    let u2 = ((1. - f) * y1.to_radians().tan()).atan();
+    let (su1, cu1, su2, cu2) = (u1.sin(), u1.cos(), u2.sin(), u2.cos());
+
+    let mut lambda = l;
+    for _ in 0..200 {
+        let (sl, cl) = lambda.sin_cos();
+        let ss = ((cu2 * sl).powi(2) + (cu1 * su2 - su1 * cu2 * cl).powi(2)).sqrt();
+        if ss == 0. {
+            return 0.;
+        }
+
+        let cs = su1 * su2 + cu1 * cu2 * cl;
+        let sigma = ss.atan2(cs);
+        let sa = cu1 * cu2 * sl / ss;
+        let c2a = 1. - sa * sa;
+        // On the equator `c2a` is zero.
+        let c2sm = if c2a != 0. {
+            cs - 2. * su1 * su2 / c2a
+        } else {
+            0.
+        };
+
+        let c = f / 16. * c2a * (4. + f * (4. - 3. * c2a));
+        let prev = lambda;
+        lambda =
+            l + (1. - c) * f * sa * (sigma + c * ss * (c2sm + c * cs * (-1. + 2. * c2sm * c2sm)));
+
+        if (lambda - prev).abs() < 1e-12 {
+            let u2 = c2a * (a * a - b * b) / (b * b);
+            let aa = 1. + u2 / 16384. * (4096. + u2 * (-768. + u2 * (320. - 175. * u2)));
+            let bb = u2 / 1024. * (256. + u2 * (-128. + u2 * (74. - 47. * u2)));
+            let ds = bb
+                * ss
+                * (c2sm
+                    + bb / 4.
+                        * (cs * (-1. + 2. * c2sm * c2sm)
+                            - bb / 6. * c2sm * (-3. + 4. * ss * ss) * (-3. + 4. * c2sm * c2sm)));
+
+            return b * aa * (sigma - ds);
+        }
+    }
+
+    haversine(x0, y0, x1, y1)
+}
+
+/// `q` of the authalic latitude (Snyder, 1987, eq. 3-12) for latitude `phi` in radians.
+fn authalic_q(phi: f64) -> f64 {
+    let e2 = WGS84_F * (2. - WGS84_F);
+    let e = e2.sqrt();
+    let s = phi.sin();
+
+    (1. - e2) * (s / (1. - e2 * s * s) - (1. / (2. * e)) * ((1. - e * s) / (1. + e * s)).ln())
+}
+
+/// The sine of the authalic latitude of latitude `phi` in radians on the WGS84 ellipsoid.
+fn sin_authalic(phi: f64) -> f64 {
+    authalic_q(phi) / authalic_q(std::f64::consts::FRAC_PI_2)
+}
+
+/// Radius in meters of the sphere with the same area as the WGS84 ellipsoid.
+fn authalic_radius() -> f64 {
+    WGS84_A * (authalic_q(std::f64::consts::FRAC_PI_2) / 2.).sqrt()
+}
+
+/// Great-circle distance in meters between (x0, y0) and (x1, y1) given in degrees longitude and
+/// latitude.
+pub fn haversine(x0: f64, y0: f64, x1: f64, y1: f64) -> f64 {
+    let (y0, y1) = (y0.to_radians(), y1.to_radians());
+    let dy = y1 - y0;
+    let dx = (x1 - x0).to_radians();
+
+    let a = (dy / 2.).sin().powi(2) + y0.cos() * y1.cos() * (dx / 2.).sin().powi(2);
+    2. * EARTH_RADIUS * a.sqrt().min(1.).asin()
+}
+
+/// The largest difference in longitude (in degrees) between a point at latitude `y0` and a point
+/// at latitude `y1` for which the great-circle distance between them is at most `d` meters.
+/// Returns `None` if all longitudes are within `d` (or none are).
+pub(crate) fn max_dlon(y0: f64, y1: f64, d: f64) -> Option<f64> {
+    let hav = |a: f64| (a / 2.).sin().powi(2);
+
+    let c = d / EARTH_RADIUS;
+    if c >= std::f64::consts::PI {
+        return None;
+    }
+
+    let (y0, y1) = (y0.to_radians(), y1.to_radians());
+    let cc = y0.cos() * y1.cos();
+    if cc <= 1e-12 {
+        return None;
+    }
+
+    let h = (hav(c) - hav(y1 - y0)) / cc;
+    if h < 0. {
+        Some(0.)
+    } else if h >= 1. {
+        None
+    } else {
+        Some((2. * h.sqrt().asin()).to_degrees())
+    }
+}
+
+/// One-dimensional squared distance transform (Felzenszwalb and Huttenlocher, 2012), in linear
+/// time: `d[q] = min_p f[p] + (q - p)²`. Points where `f` is infinite are never nearest, if all
+/// are infinite so is the result.
+pub(crate) fn squared_distance_transform(f: &[f64]) -> Vec<f64> {
+    // The parabolas of the lower envelope, and where each of them starts to be the lowest.
+    let mut v: Vec<usize> = Vec::with_capacity(f.len());
+    let mut z: Vec<f64> = Vec::with_capacity(f.len());
+
+    for q in (0..f.len()).filter(|&q| f[q].is_finite()) {
+        let mut s = f64::NEG_INFINITY;
+
+        while let Some(&p) = v.last() {
+            let (pf, qf) = (p as f64, q as f64);
+            s = ((f[q] + qf * qf) - (f[p] + pf * pf)) / (2. * (qf - pf));
+
+            if s <= z[z.len() - 1] {
+                v.pop();
+                z.pop();
+                s = f64::NEG_INFINITY;
+            } else {
+                break;
+            }
+        }
+
+        v.push(q);
+        z.push(s);
+    }
+
+    let mut d = vec![f64::INFINITY; f.len()];
+    if v.is_empty() {
+        return d;
+    }
+
+    let mut k = 0;
+    for (q, dq) in d.iter_mut().enumerate() {
+        while k + 1 < v.len() && z[k + 1] < q as f64 {
+            k += 1;
+        }
+
+        let p = v[k];
+        *dq = f[p] + (q as f64 - p as f64).powi(2);
+    }
+
+    d
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn units() {
+        assert_eq!(1852. / Unit::Kilometers.meters(), 1.852);
+        assert_eq!(1852. / Unit::NauticalMiles.meters(), 1.);
+        assert_eq!(2. * Unit::NauticalMiles.meters(), 3704.);
+    }
+
+    #[test]
+    fn haversine_degree() {
+        let d = haversine(0., 0., 1., 0.);
+        assert!((d - 111_195.).abs() < 1.);
+
+        let d = haversine(179.5, 0., -179.5, 0.);
+        assert!((d - 111_195.).abs() < 1.);
+    }
+
+    #[test]
+    fn vincenty_baseline() {
+        // Flinders Peak to Buninyong, the example of Vincenty (1975).
+        let dms = |d: f64, m: f64, s: f64| d + m / 60. + s / 3600.;
+        let d = vincenty(
+            dms(144., 25., 29.5244),
+            -dms(37., 57., 3.7203),
+            dms(143., 55., 35.3839),
+            -dms(37., 39., 10.1561),
+        );
+        assert!((d - 54_972.271).abs() < 1e-3, "{d}");
+
+        // A quarter meridian, and a degree along the equator, also across the antimeridian.
+        assert!((vincenty(0., 0., 0., 90.) - 10_001_965.729).abs() < 1e-3);
+        assert!((vincenty(0., 0., 1., 0.) - 111_319.491).abs() < 1e-3);
+        assert!((vincenty(179.5, 0., -179.5, 0.) - 111_319.491).abs() < 1e-3);
+        assert_eq!(vincenty(5., 60., 5., 60.), 0.);
+
+        // The sphere is too long along the meridians and too short along the equator.
+        let sphere = Earth::Sphere.distance(0., 0., 0., 90.);
+        assert!(sphere / Earth::Wgs84.distance(0., 0., 0., 90.) > 1.0005);
+        assert!(haversine(0., 0., 1., 0.) / vincenty(0., 0., 1., 0.) < 0.999);
+
+        // Nearly antipodal points fall back to the sphere.
+        assert_eq!(vincenty(0., 0., 179.7, 0.3), haversine(0., 0., 179.7, 0.3));
+    }
+
+    #[test]
+    fn ring_area_m2() {
+        // The area of the ellipsoid.
+        let globe = 4. * std::f64::consts::PI * authalic_radius().powi(2) / 1e6;
+        assert!((globe - 510_065_621.724).abs() < 1e-2, "{globe}");
+
+        let square = |y: f64| [(0., y), (1., y), (1., y + 1.), (0., y + 1.), (0., y)];
+        let a = Earth::Wgs84.ring_area_m2(&square(0.)) / 1e6;
+        assert!((a - 12_308.464).abs() < 1e-2, "{a}");
+        let a = Earth::Wgs84.ring_area_m2(&square(60.)) / 1e6;
+        assert!((a - 6_123.141).abs() < 1e-2, "{a}");
+
+        let a = Earth::Sphere.ring_area_m2(&square(0.)) / 1e6;
+        assert!((a - 12_363.718).abs() < 1e-2, "{a}");
+    }
+
+    #[test]
+    fn max_dlon_inverse() {
+        let dlon = max_dlon(60., 60.5, 100_000.).unwrap();
+        assert!((haversine(0., 60., dlon, 60.5) - 100_000.).abs() < 1e-3);
+    }
+
+    #[test]
+    fn squared_distance_transform_brute_force() {
+        let inf = f64::INFINITY;
+        let f = [inf, 0., inf, inf, 2., inf, inf, inf, 0., inf, 30., inf];
+
+        let d = squared_distance_transform(&f);
+        for (q, d) in d.iter().enumerate() {
+            let expected = f
+                .iter()
+                .enumerate()
+                .map(|(p, f)| f + (q as f64 - p as f64).powi(2))
+                .fold(inf, f64::min);
+            assert_eq!(*d, expected);
+        }
+
+        assert!(squared_distance_transform(&[inf; 3])
+            .iter()
+            .all(|d| d.is_infinite()));
+    }
+}
@@ -0,0 +1,84 @@
+//! Fetch the data at runtime instead of using the copy embedded in the library.
+//!
+//! With the `download` feature the mask and shapes can be downloaded from the repository on
+//! first use and cached in the platform cache directory (e.g. `~/.cache/roaring-landmask` on
+//! Linux). Later calls use the cached files. Every file is checked against its SHA-256, both
+//! after downloading and when read from the cache, so a corrupt cache is downloaded again.
+//!
+//! ```no_run
+//! # use roaring_landmask::RoaringLandmask;
+//! let landmask = RoaringLandmask::from_downloaded("f").unwrap();
+//! assert!(landmask.contains(15., 65.6));
+//! ```
+//!
+//! The data is also embedded in the library unless it is built without the default `embed`
+//! feature, e.g. with `--no-default-features --features download,index`. The library is then much
+//! smaller, but `RoaringLandmask::new`, `RoaringMask::new`, `Gshhg::embedded` and
+//! `CrudeMask::new` fail with `NotFound` and the data must be downloaded.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Only the full mask and the shapes are hosted here, the crude and tiled masks are shipped with
+/// the package.
+static URL: &str = "https://github.com/gauteh/roaring-landmask/raw/main/gshhs";
+
+/// Directory where downloaded files are kept.
+pub fn cache_dir() -> io::Result<PathBuf> {
+    dirs::cache_dir()
+        .map(|d| d.join("roaring-landmask"))
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no cache directory"))
+}
+
+fn verify(path: &Path, sha256: &str) -> io::Result<bool> {
+    use ring::{digest, test};
+
+    let expected: Vec<u8> =
+        test::from_hex(sha256).map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+    let actual = digest::digest(&digest::SHA256, &fs::read(path)?);
+
+    Ok(expected == actual.as_ref())
+}
+
+/// Path to `name` in the cache, downloading it first if it is missing or does not match
+/// `sha256`.
+pub fn fetch(name: &str, sha256: &str) -> io::Result<PathBuf> {
+    let dir = cache_dir()?;
+    let path = dir.join(name);
+
+    if path.exists() && verify(&path, sha256)? {
+        return Ok(path);
+    }
+
+    fs::create_dir_all(&dir)?;
+
+    let url = format!("{}/{}", URL, name);
+    let download_error = |e: reqwest::Error| {
+        io::Error::new(
+            io::ErrorKind::Other,
+            format!("cannot download {}: {}", url, e),
+        )
+    };
+    let bytes = reqwest::blocking::get(&url)
+        .and_then(|r| r.error_for_status())
+        .and_then(|r| r.bytes())
+        .map_err(download_error)?;
+
+    // Write to a temporary file first, so that an interrupted download never ends up in the
+    // cache.
+    let tmp = dir.join(format!("{}.part", name));
+    fs::write(&tmp, &bytes)?;
+
+    if !verify(&tmp, sha256)? {
+        fs::remove_file(&tmp)?;
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("checksum mismatch for {}", url),
+        ));
+    }
+
+    fs::rename(&tmp, &path)?;
+
+    Ok(path)
+}
@@ -0,0 +1,47 @@
+//! Errors from loading serialized landmasks.
+//!
+//! The functions keep returning `io::Error`, with a `LandmaskError` inside where the cause is
+//! more specific than the `io::ErrorKind`. It can be recovered with `LandmaskError::from_io`.
+
+use std::fmt;
+use std::io;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum LandmaskError {
+    /// The file was written with another version of the format.
+    IncompatibleVersion { found: u32, expected: u32 },
+
+    /// The shapes in the file do not match the source data recorded in it (the SHA-256 of the
+    /// shapes as WKB).
+    IncompatibleSource { found: String, expected: String },
+}
+
+impl LandmaskError {
+    /// The `LandmaskError` inside `err`, if any.
+    pub fn from_io(err: &io::Error) -> Option<&LandmaskError> {
+        err.get_ref()?.downcast_ref()
+    }
+}
+
+impl fmt::Display for LandmaskError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LandmaskError::IncompatibleVersion { found, expected } => write!(
+                f,
+                "incompatible format version {found}, expected version {expected}"
+            ),
+            LandmaskError::IncompatibleSource { found, expected } => write!(
+                f,
+                "records source data {found}, but the shapes are {expected}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for LandmaskError {}
+
+impl From<LandmaskError> for io::Error {
+    fn from(err: LandmaskError) -> io::Error {
+        io::Error::new(io::ErrorKind::InvalidData, err)
+    }
+}
@@ -0,0 +1,49 @@
+//! Classification of a fixed grid that is queried repeatedly.
+
+use ndarray::{ArrayD, ArrayViewD, Zip};
+use numpy::{PyArray, PyArrayDyn};
+use pyo3::prelude::*;
+
+use crate::RoaringLandmask;
+
+/// The land classification of a grid of points, computed once with
+/// `RoaringLandmask::precompute_grid` and reused for as long as the grid does not change.
+#[pyclass]
+#[derive(Clone, Debug)]
+pub struct GridMask {
+    land: ArrayD<bool>,
+}
+
+impl GridMask {
+    /// Classify the points (x, y) with `landmask`. `x` and `y` must have the same shape.
+    pub fn new(landmask: &RoaringLandmask, x: ArrayViewD<f64>, y: ArrayViewD<f64>) -> GridMask {
+        let land = Zip::from(&x)
+            .and(&y)
+            .par_map_collect(|x, y| landmask.contains(*x, *y));
+
+        GridMask { land }
+    }
+
+    /// Whether each point in the grid is on land.
+    pub fn land(&self) -> &ArrayD<bool> {
+        &self.land
+    }
+}
+
+#[pymethods]
+impl GridMask {
+    /// Whether each point in the grid is on land, with the same shape as the grid.
+    pub fn contains(&self, py: Python) -> Py<PyArrayDyn<bool>> {
+        PyArray::from_array(py, &self.land).to_owned()
+    }
+
+    /// Number of points in the grid.
+    pub fn __len__(&self) -> usize {
+        self.land.len()
+    }
+
+    #[getter]
+    pub fn shape(&self) -> Vec<usize> {
+        self.land.shape().to_vec()
+    }
+}
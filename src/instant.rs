@@ -0,0 +1,401 @@
+//! A coarse landmask compiled into the library as a constant table.
+//!
+//! The table has one bit per 1° × 1° cell, which is land if at least half of the full mask
+//! within it is land. There is nothing to load or decode, so `InstantMask` is ready at once, for
+//! command line tools and tests that need a rough answer without the startup cost of
+//! `RoaringLandmask::new`. The error is up to a degree (about 100 km) near the coast, and islands
+//! smaller than a cell are missing. Use `CrudeMask` for an error of a few kilometers, or the full
+//! landmask for an exact answer.
+//!
+//! `TABLE` is made from the full mask with `InstantMask::table_from_mask`, see
+//! `examples/make_instant.rs`.
+
+use numpy::{PyArray, PyReadonlyArrayDyn};
+use pyo3::prelude::*;
+
+use crate::mask::{self, RoaringMask};
+
+/// Number of cells along longitude.
+pub const NX: usize = 360;
+
+/// Number of cells along latitude.
+pub const NY: usize = 180;
+
+/// A coarse landmask backed by a constant table, see the module documentation.
+#[pyclass]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct InstantMask;
+
+impl InstantMask {
+    /// Make the table of cells from `mask`, in the layout of the table in the library. A cell is
+    /// land if at least half of the mask within it is land.
+    pub fn table_from_mask(mask: &RoaringMask) -> Vec<u64> {
+        let mut table = vec![0u64; (NX * NY + 63) / 64];
+
+        for i in mask.coarsen(mask::NX / NX as u64) {
+            let i = i as usize;
+            table[i / 64] |= 1 << (i % 64);
+        }
+
+        table
+    }
+}
+
+#[pymethods]
+impl InstantMask {
+    /// The mask compiled into the library, this does no work.
+    #[staticmethod]
+    pub fn new() -> InstantMask {
+        InstantMask
+    }
+
+    #[getter]
+    pub fn dx(&self) -> f64 {
+        360. / NX as f64
+    }
+
+    #[getter]
+    pub fn dy(&self) -> f64 {
+        180. / NY as f64
+    }
+
+    /// Check if point (x, y) is on land.
+    ///
+    /// `x` is longitude, [-180, 180] east
+    /// `y` is latitude,  [- 90,  90] north
+    pub fn contains(&self, x: f64, y: f64) -> bool {
+        assert!(y >= -90. && y <= 90.);
+
+        if let Some(land) = crate::pole(y) {
+            return land;
+        }
+
+        let x = crate::modulate_longitude(x);
+        let col = ((x + 180.) as usize).min(NX - 1);
+        let row = ((y + 90.) as usize).min(NY - 1);
+
+        let i = row * NX + col;
+        TABLE[i / 64] & (1 << (i % 64)) != 0
+    }
+
+    pub fn contains_many(
+        &self,
+        py: Python,
+        x: PyReadonlyArrayDyn<f64>,
+        y: PyReadonlyArrayDyn<f64>,
+    ) -> PyResult<Py<PyArray<bool, numpy::Ix1>>> {
+        let x = x.as_array();
+        let y = y.as_array();
+        crate::check_shape(x.shape(), y.shape())?;
+
+        Ok(PyArray::from_iter(
+            py,
+            x.iter().zip(y.iter()).map(|(x, y)| self.contains(*x, *y)),
+        )
+        .to_owned())
+    }
+}
+
+/// The cells from the south-west corner, row by row, one bit each. Generated from the full mask.
+#[rustfmt::skip]
+static TABLE: [u64; (NX * NY + 63) / 64] = [
+    0xffffffffffffffff, 0xffffffffffffffff, 0xffffffffffffffff, 0xffffffffffffffff,
+    0xffffffffffffffff, 0xffffffffffffffff, 0xffffffffffffffff, 0xffffffffffffffff,
+    0xffffffffffffffff, 0xffffffffffffffff, 0xffffffffffffffff, 0xffffffffffffffff,
+    0xffffffffffffffff, 0xffffffffffffffff, 0xffffffffffffffff, 0xffffffffffffffff,
+    0xffffffffffffffff, 0xffffffffffffffff, 0xffffffffffffffff, 0xffffffffffffffff,
+    0xffffffffffffffff, 0xffffffffffffffff, 0xffffffffffffffff, 0xffffffffffffffff,
+    0xffffffffffffffff, 0xffffffffffffffff, 0xffffffffffffffff, 0xffffffffffffffff,
+    0xffffffffffffffff, 0xffffffffffffffff, 0xffffffffffffffff, 0xffffffffffffffff,
+    0xffffffffffffffff, 0xffffffffffffffff, 0xffffffffffffffff, 0xffffffffffffffff,
+    0xffffffffffffffff, 0xffffffffffffffff, 0xffffffffffffffff, 0xffffffffffffffff,
+    0xffffffffffffffff, 0xffffffffffffffff, 0xffffffffffffffff, 0xffffffffffffffff,
+    0xffffffffffffffff, 0xffffffffffffffff, 0xffffffffffffffff, 0xffffffffffffffff,
+    0xffffffffffffffff, 0xffffffffffffffff, 0xffffffffffffffff, 0xffffffffffffffff,
+    0xffffffffffffffff, 0xffffffffffffffff, 0xffffffffffffffff, 0xffffffffffffffff,
+    0xffffffffffffffff, 0xffffffffffffffff, 0xffffffffffffffff, 0xffffffffffffffff,
+    0xffffffffffffffff, 0xffffffffffffffff, 0xffffffffffffffff, 0xffffffffffffffff,
+    0xffffffffffffffff, 0xffffffffffffffff, 0xffffffffffffffff, 0xffe00000ffffffff,
+    0xffffffffffffffff, 0xfffc000fffffffff, 0xffffffffffffffff, 0xffffffffffffffff,
+    0xf8ffffffffffffff, 0xffffffe000000003, 0xffffffffffffffff, 0xffffffff8000001f,
+    0xffffffffffffffff, 0xffffffffffffffff, 0x000000007fffffff, 0xffffffffff800000,
+    0x000001ffffffffff, 0xfffffffffffffc00, 0xffffffffffffffff, 0xffffffffffffffff,
+    0x000000000000007f, 0xffffffc7ffffff80, 0xfff0000000007fff, 0xffffffffffffffff,
+    0xffffffffffffffff, 0x0001ffffffffffff, 0x01c0000000000000, 0x00ffffffffffc000,
+    0xffffffff00000000, 0xffffffffffffffff, 0xffffffffffffffff, 0x0000000007ffffff,
+    0x0fe0000000000000, 0x00000000fff8400e, 0xfffffffffffff800, 0xffffffffffffffff,
+    0xffffffffffffffff, 0x000000000000003f, 0xfe00000000000000, 0xff000000000000ff,
+    0xffffffffffffffff, 0xffffffffffffffff, 0x00003fffffffffff, 0x0000000000000000,
+    0x00007ffe00000000, 0xffffffe800000000, 0xffffffffffffffff, 0xffffffffffffffff,
+    0x0000000000007fff, 0x0000000000000000, 0x0000000000ffb800, 0xf9ec000400080000,
+    0xffffffffbfffffff, 0x000fffffffffffff, 0x0000000000000000, 0xfe00000000000000,
+    0x0000000000000001, 0xffffffe000000000, 0xfffffffffffffc03, 0x00000000003fffff,
+    0x0000000000000000, 0x000000ff80000000, 0x0000000000000000, 0xffc00009fffc0000,
+    0x003fffffffffffff, 0x0000000000000000, 0x0000000000000000, 0x0000000000007c00,
+    0x8000000000000000, 0xfc7ff81fc000001f, 0x00000000003fc7e0, 0x0000000000000000,
+    0x00f0000000000000, 0x0000000000000000, 0x0000000000000000, 0x0000000019300000,
+    0x0000000000000000, 0x0000000000000000, 0x00000001c0000000, 0x0000000000000000,
+    0x0000000000000000, 0x0000000000000000, 0x0000000000000000, 0x0000000000000000,
+    0x0000000000000000, 0x0000000000000000, 0x0000000000000000, 0x0000000000000000,
+    0x0000000000000000, 0x0000000000000000, 0x0000000000000000, 0x0000000000000000,
+    0x0000000000000000, 0x0000000000000000, 0x0000000000000000, 0x0000000000000000,
+    0x0000000000000000, 0x0000000000000000, 0x0000000000000000, 0x0000000000000000,
+    0x0000000000000000, 0x0000000000000000, 0x0000000000000000, 0x0000000000000000,
+    0x0000000000000000, 0x0000000000000000, 0x0000000000000000, 0x0000000000000000,
+    0x0000000000000000, 0x0000000000000000, 0x0000000000000000, 0x0000000000000000,
+    0x0000000000000000, 0x0000000000000000, 0x0000000000000000, 0x0000000000000000,
+    0x0000000000000000, 0x0000000000000000, 0x0000000000000000, 0x0000000000000000,
+    0x0000000000000000, 0x0000000000000000, 0x0000000000000000, 0x0000000000000000,
+    0x0000000000000000, 0x0000000000000000, 0x0000000000000000, 0x0000000000000000,
+    0x0000000000000000, 0x0000000000000000, 0x0000000000000000, 0x0000000000000000,
+    0x0000000000000000, 0x0000000000000000, 0x000003f000000000, 0x0000000000000000,
+    0x0000000000000000, 0x0000000000000000, 0x0000000000000000, 0x0000000000000000,
+    0x000000000000dc00, 0x0000000000000000, 0x0000000000000000, 0x0000000000000000,
+    0x0000000000000000, 0x007c000000000000, 0x0000000000000000, 0x0000000000000000,
+    0x0000000000000000, 0x0000000000000000, 0x0000000000000000, 0x000002007e000000,
+    0x0000000000000000, 0x0000000000000000, 0x0000000000000000, 0x0000000000000000,
+    0x0000000000000000, 0x000000000000007e, 0x0000000000000000, 0x0000000000000000,
+    0x0000000000000000, 0x0000000000000000, 0x0000fe0000000000, 0x0000000000000000,
+    0x0200000000000000, 0x0000000000000000, 0x0000000000000000, 0x0000000000000000,
+    0x0000000001ff0000, 0x0000000000000000, 0x0000000000000000, 0x0000000000000000,
+    0x0000000000000000, 0xfe00000000000000, 0x0000000000000003, 0x0000000000000000,
+    0x0000000000000000, 0x0000000000000000, 0x0000000000000000, 0x000001fe00000000,
+    0x0000000000000000, 0x0000000000000000, 0x0000000000000000, 0x0000000000300000,
+    0x0000000000000000, 0x000000000001fe00, 0x0000000000000000, 0x0000000000000000,
+    0x7800000000000000, 0x0000000000000000, 0x07fc000000000000, 0x0000000000000000,
+    0x0000000000000000, 0x0000000000000000, 0x0000007000000000, 0x0000000000000000,
+    0x00000007f8000000, 0x0000000000000000, 0x0000000000000000, 0x0040000000000000,
+    0x000000000001c000, 0x0000000000000000, 0x00000000000007fc, 0x0000000000000000,
+    0x0000000000000000, 0x03800000e0000000, 0x0000000000000000, 0x0007fc0000000000,
+    0x0000000000000000, 0x0000000000000000, 0x0000000000000000, 0x00000003000000e0,
+    0x0000000000000000, 0x000000003ffc0000, 0x0000000000000000, 0x0000000000000000,
+    0x0000000000000000, 0x0000000000001800, 0xf800000000000000, 0x000000000000003f,
+    0x0000000000000000, 0x0000000000000000, 0x001c000000000000, 0x0000000000000000,
+    0x0003fff800000000, 0x0000000000000000, 0x0000000000000000, 0x6800000000000000,
+    0x0000000038000000, 0x0000000000000000, 0x0000000007fffc00, 0x0000000000000000,
+    0x0000000000000000, 0x000003ff00000000, 0x0000000000000008, 0xfff8000000000000,
+    0x0000000000000007, 0x0000000000000000, 0x0000000000000000, 0x000004000003ff00,
+    0x0000000000000000, 0x000007fff8000000, 0x0000000000000000, 0x0000000000000000,
+    0x03ff800000000000, 0x0000000000020000, 0x0000000000000000, 0x00000000003bfff0,
+    0x0000000180000000, 0x0000000000000000, 0x00000007ffc80007, 0x0000000000000000,
+    0x7ffff00000000000, 0x0000000000000000, 0x0000000000007fc0, 0xd800ff8000000000,
+    0x00000000000007ff, 0x0000000000000000, 0x000000ffffe00000, 0x01ffc00000000000,
+    0x0000000000000000, 0x000ffffc03ff0000, 0x0000000000000000, 0xf000000000000000,
+    0x000000000000ffff, 0x00000003ffc00000, 0xff00000000000000, 0x000000001fffffff,
+    0x0000000000000000, 0x01fffff000000000, 0xe000000000000000, 0x00000000000007ff,
+    0xffffffff80000000, 0x000000000000001f, 0x0000000000000000, 0x00000003ffffe000,
+    0x0007ffe000000000, 0x0000000000000000, 0x00001fffffffff80, 0x0000000000000000,
+    0xffe0000000000000, 0x00000000000007ff, 0x000000000ffff000, 0xffffc00000000000,
+    0x00000000003fffff, 0x0000000000000000, 0x0007ffffe0000000, 0xfff8000000000000,
+    0x000000000000001f, 0x1fffffffffc00000, 0x0000000000000000, 0x0000000000000000,
+    0x0000000007ffffe0, 0x00001ffff8000000, 0xc000000000000000, 0x0000001fffffffff,
+    0x0000000000000000, 0xffffe00000000000, 0x000000000000000f, 0x00000002001ffff8,
+    0xffffffc000000000, 0x0000000000001fff, 0x0000000000000000, 0x00001fffffe00000,
+    0x7ffff80000000000, 0x0000000000000700, 0x000fffffffffe000, 0x0000000000000000,
+    0xe000000000000000, 0x00000000007fffff, 0x000f007ffffc0000, 0xffc0000000000000,
+    0x0000000007ffffff, 0x0000000000000000, 0xffffffc000000000, 0xfc00000000000003,
+    0x000000000f80ffff, 0xffffffffc0000000, 0x0000000000000007, 0x0000000000000000,
+    0x000007ffffffc000, 0x007ffffc00000000, 0x000000000000000f, 0x020001ffffffff80,
+    0x0000000000000000, 0xffc0000000000000, 0x00000000000fffff, 0x00001f007ffffe00,
+    0xfffc000000000000, 0x000000000001ffff, 0x0000000000000000, 0x0fffffffc0000000,
+    0xfffe000000000000, 0x00000000001f007f, 0x007fffffe0000000, 0x0000000000000000,
+    0x0000000000000000, 0x0000000fffffffc0, 0x1f01ffffff000000, 0x0000000000000000,
+    0x000000003fffffc0, 0x0000000000000000, 0xffffe00000000000, 0x0000000000001fff,
+    0x0000001f03ffffff, 0xffffc00000000000, 0x000000000000003e, 0x0000000000000000,
+    0x001ffffffff80000, 0xffffff0000000000, 0x0000000000003e0f, 0x00003e3fff000000,
+    0x0000000000000000, 0xfe00000000000000, 0x000000001fffffff, 0x00380fffffff0000,
+    0x0000000000000000, 0x00000000001e0ffe, 0x0000000000000000, 0xffffffff00000000,
+    0xff0000000000001f, 0x00000000301fffff, 0x1c0fec0000000000, 0x0000000000000000,
+    0x0000000000000000, 0x00001fffffffff00, 0x1ffffffe00000000, 0x0000000000000030,
+    0x0000000c0fc00000, 0x0000000000000000, 0xffff800000000000, 0x00000000003fffff,
+    0x0000201ffffffe00, 0x8000000000000000, 0x000000000000041f, 0x0000000000000000,
+    0x7fffffffff800000, 0xfffc000000000000, 0x0000000000000fff, 0x0004000000000000,
+    0x0000000000000000, 0xc000000000000000, 0x0000007fffffffff, 0x000ffffffc000000,
+    0x0000000000000000, 0x0000000000000000, 0x0000000000000000, 0xffffffc000000000,
+    0x000000000000ffff, 0x000000000ffffffe, 0x0001000000000000, 0x0000000000000380,
+    0x0000000000000000, 0x01ffffffffffe000, 0xfffffe0000000000, 0x0000000000000007,
+    0x0000c70000120000, 0x0000000000000000, 0xfff0000000000000, 0x00000001ffffffff,
+    0x000007fffffe0000, 0x001f800000000000, 0x00000000007fc000, 0x0000000000000000,
+    0xfffffffff0000000, 0xfe000000000001ff, 0x000000000007ffff, 0xff80000000c00000,
+    0x0000000000000000, 0x0000000000000000, 0x0001fffffffffff8, 0x07ffffff00000000,
+    0x3000000000000000, 0x00000c3fc0000000, 0x0000000000000000, 0xfffff80000000000,
+    0x00000000003fffff, 0x00000007ffffff00, 0x0040003800000000, 0x0000000000101ff0,
+    0x0000000000000000, 0x1ffffffffff00000, 0xffff800000000000, 0x0000000000000fff,
+    0x000ffe0428c03c00, 0x0000000000000000, 0xf800000000000000, 0x00000001ffffffff,
+    0x001fffffffc00000, 0xfc3e000000000000, 0x0000000001f30038, 0x0000000000000000,
+    0xfffffff800000000, 0xe00000000000007f, 0x000000003fffffff, 0x038039fc0e000000,
+    0x0000000000000000, 0x0000000000000000, 0x000007fffffff000, 0xffffffe000000000,
+    0x000000000000003f, 0x000000010001fe0f, 0x0000000000000000, 0xfff0000000000000,
+    0x000000000003ffff, 0x00007fffffffe000, 0xa3fe0f8000000000, 0x0000000000000000,
+    0x0000000000000000, 0x03ffffffe0000000, 0xffc0000000000000, 0x0000000001ffffff,
+    0x00000003fe0b8000, 0x0000000000000000, 0x0000000000000000, 0x00000001ffffffc0,
+    0xffffffffc0000000, 0x0cc0000000000003, 0x00000000000003f8, 0x0000000000000000,
+    0xffff800000000000, 0x00000000000001ff, 0x000007ffffffffc0, 0x0003e00660000000,
+    0x0000000000000000, 0x0000000000000000, 0x0000ffffff800000, 0xffffec0010000000,
+    0x00000000000fffff, 0x0000000003c00630, 0x0000000000000000, 0x8000000000000000,
+    0x00000000007fffff, 0x1ffffffffffe0ffc, 0x0007000000000000, 0x0000000000000007,
+    0x0000000000000000, 0x03ffff8000000000, 0xfffffe0000000000, 0x0000001fffffffff,
+    0x0003030003000030, 0x0000000000000000, 0x0000000000000000, 0x00000001ffffc800,
+    0xffffffffffff8000, 0x8000300000003fff, 0x0000000007400000, 0x0000000000000000,
+    0xffee000000000000, 0xff800000000000ff, 0x003fffffffffffff, 0x800000c000120000,
+    0x0000000000000003, 0x0000000000000000, 0x00007fef01000000, 0xffffffffc0000000,
+    0x070000007fffffff, 0x0000000000204000, 0x0000000000000000, 0xc000000000000000,
+    0x000000000013fe01, 0xffffffffffffffe0, 0x7040000f0000007e, 0x0000000000008000,
+    0x0000000000000000, 0x004800c000000000, 0xfffff00000000000, 0x0000407fffffffff,
+    0x004001f880000f00, 0x0000000000000000, 0x0000000000000000, 0x000000000000e000,
+    0xfffffffffff80000, 0x000f800000007fff, 0x000000000001fcc0, 0x0000000000000000,
+    0x00f8000000000000, 0xf800000000000000, 0x07bfffffffffffff, 0x01ffc0000f800000,
+    0x0000000000000080, 0x0000000000000000, 0x00000001ff000000, 0xfffffff800000000,
+    0xc000001f9fffffff, 0x00002001ffc0000f, 0x0000000000000000, 0x8000000000000000,
+    0x00000000000000ff, 0xfffffffffffff800, 0xc0000fc00000ff8f, 0x00000000003001ff,
+    0x0000000000000000, 0x00000ffe00000000, 0xfff0000000000000, 0x01ff87ffffffffff,
+    0x3000ffec003fe000, 0x0000000000000000, 0x0000000000000000, 0x00000000000fff80,
+    0xfffffffff0000000, 0x7fe00007ffc7ffff, 0x00000030007ff800, 0x0000000000000000,
+    0x0f9ff00000000000, 0x0000000000007800, 0xe3fffffffffffff0, 0x3ffc00ffe0001fff,
+    0x0000000000000002, 0x0000000100000000, 0x0038000e0ff80000, 0xfffff00000000000,
+    0x003fffe1ffffffff, 0x0000063ffc01ffe0, 0x0000000000000000, 0xf800000000000000,
+    0x0000000001c01c07, 0xfffffffffff00000, 0x07ffe0003ffff1ff, 0x0000000000007ffe,
+    0x0000000000000000, 0x601803f800000000, 0xf800000000000000, 0xfff9ffffffffffff,
+    0x06ffff07fffc007f, 0x0000000000000000, 0x0000000000000000, 0x0000001d0003fc00,
+    0xfffffff800000000, 0xfe00fffff8ffffff, 0x0000103fffffffff, 0x0000000000000000,
+    0x03fe000000000000, 0x0000000000000000, 0xfffffffffffff000, 0xffffffff007ffff8,
+    0x000000000011ffff, 0x0000000000000000, 0x00000003ff100000, 0xffe0000000000000,
+    0x1cfffe7fffffffff, 0x23ffffffffffff80, 0x0000000000000000, 0x9000000000000000,
+    0x00000000080007ff, 0xffffffffe0000000, 0xffffff80fffe7fff, 0x00000007ffffffff,
+    0x0000000000000000, 0x0007ff8800000000, 0x000000000000000c, 0xff3fffffffffffc0,
+    0xffffffffffffe03f, 0x0000000000000fff, 0x0000000000000000, 0x00000e0007ffec00,
+    0xffff800000000000, 0xfffe1fff3fffffff, 0x001fffffffffffff, 0x0000000000000000,
+    0xfff4000000000000, 0x000000000006000f, 0xfffffffffe000000, 0xffffffffff8fffff,
+    0x000000003fffffff, 0x0000000000000000, 0x06021ffff2000000, 0x0000000000000000,
+    0x8ffffffffffffffc, 0xffffffffffffffff, 0x000000000000003f, 0x0000000000000000,
+    0x00000007fffffff9, 0xfffffc0000000000, 0xffffffffffffff7f, 0x00001fffffffffff,
+    0x0000000000000000, 0xfffffd8000000000, 0x00000000000007ff, 0xc07f0ffffffc0000,
+    0xffffffffffffffff, 0x00000000403fffff, 0x0000000000000000, 0x000fffffffff8000,
+    0xf800000000000000, 0xffffff800607ffff, 0x1fffffffffffffff, 0x00000000000000c0,
+    0xffc0000000000000, 0x000000001fffffff, 0x007ffff000000000, 0xffffffffffff8000,
+    0x0003c01fffffffff, 0x0000000000000000, 0xfffffffff0000000, 0x000000000000007f,
+    0xff0000003fffe000, 0xffffffffffffffff, 0x000000001f840fff, 0x0000000000000000,
+    0x0000fffffffffff8, 0xf840000000000000, 0xffffffff0000007f, 0x1c0fffffffffffff,
+    0x00000000000000fe, 0xfffffc0000000000, 0x0000000000ffffff, 0x00007fe040000000,
+    0xffffffffffffff32, 0x0001f01c1fffffff, 0x0000000000000000, 0xfffffffffffc0000,
+    0x0000000000000000, 0xffffff86060007f8, 0xffffffffffffffff, 0x0000000001801c37,
+    0xfe00000000000000, 0x000001ffffffffff, 0x000ff80000000000, 0xffffffffffff8e00,
+    0x000e03ffffffffff, 0x0000000000000001, 0xffffffff00000000, 0x000000000003ffff,
+    0xffc710300ff80000, 0xffffffffffffffff, 0x000003000e47ffff, 0x0000000000000000,
+    0x03ffffffffffff00, 0xf800000000000000, 0xfffffffecfb8301f, 0xdfffffffffffffff,
+    0x000000000003001f, 0xffff000000000000, 0x0000001fffffffff, 0x8e003ff800000000,
+    0xffffffffffe0f0ff, 0x00003fffffffffff, 0x0000000000000000, 0xffffffffff000000,
+    0x0000000000001fff, 0xe000ffc3807ff800, 0xffffffffffffffff, 0x0000000d007fffff,
+    0x0000000000000000, 0x003fffffffffffff, 0xfc70000000000000, 0xfffffff000fff3c7,
+    0xffffffffffffffff, 0x0000000000001e07, 0xffffff0000000000, 0x0000000cffffffff,
+    0xfff8fff800000000, 0xfffffffffffffc01, 0x00040fffffffffff, 0x0000000000000000,
+    0xffffffffffff0000, 0x0000000000007fff, 0xfffe63fffffff800, 0xffffffffffffffff,
+    0x00000000001fffff, 0xff00000000000000, 0x008fffffffffffff, 0xfffc000000000000,
+    0xfffffffffcf7ffff, 0x3fffffffffffffff, 0x0000000000000004, 0xffffffff00000000,
+    0x0000007007ffffff, 0xfffffffffe000000, 0xffffffffffffffff, 0x0000047fffffffff,
+    0x0000000000000000, 0xffffffffffffff80, 0x0000000000003e0f, 0xffffffffffffffff,
+    0xffffffffffffffff, 0x000000000004ffff, 0xffffe00000000000, 0x003c01ffffffffff,
+    0xfffff00000000000, 0xffffffffffffffff, 0x0cffffffffffffff, 0x0000000000000000,
+    0xfffffffffff00000, 0x0000000008ffffff, 0xffffffffffc18000, 0xffffffffffffffff,
+    0x0000000dffffffff, 0xf000000000000000, 0xffffefffffffffff, 0x1f0000000000000f,
+    0xffffffffffffffff, 0xffffffffffffffff, 0x00000000020005ff, 0xfffffff800000000,
+    0x00000fffffe3ffff, 0xfffffe3f3c000000, 0xffffffffffffffff, 0x0005ffffffffffff,
+    0x0000000000000003, 0xe3fffffffffffc80, 0x00000000000fffff, 0xfffffffffff80e3c,
+    0xffffffffffffffff, 0x00000f0005ffffff, 0xfffe000000000000, 0x03ffffe3ffffffff,
+    0x2006380000000000, 0xffffffffffffff60, 0x1fffffffffffffff, 0x04000000001f0000,
+    0xffffffffff800000, 0x00000000ffff803f, 0xfffe02b003800000, 0xffffffffffffffff,
+    0x3f00000fffffffff, 0xc000003000000000, 0xff800fffffffffff, 0xc00000000000007f,
+    0xfffffffffe0e3001, 0xffffffffffffffff, 0x0000007f00003fff, 0xfffffff00004c000,
+    0x00003fff80007fff, 0x1f0003c000000000, 0xfffffffffffffff4, 0x00ffffffffffffff,
+    0x03c0000000007e00, 0x007ffffffffff800, 0x00000000001effc0, 0xfffff41f9c008000,
+    0xffffffffffffffff, 0x00380001ffffffff, 0xfffe0033fc000000, 0x0c3fc0001fffffff,
+    0x0000000000000000, 0xffffffffff607ffe, 0xffffffffffffffff, 0x800000006039ffff,
+    0xfffffffffffcefff, 0x01c000003fc0001f, 0xfe3ffe0000000000, 0xffffffffffffffff,
+    0x7fffffffffffffff, 0xffffffc000006fc0, 0xc0003fffffffffff, 0x00000003f800000f,
+    0xfffffffe1ffe0000, 0xffffffffffffffff, 0x01ff31ffffffffff, 0xffffffffffff8000,
+    0x0003804100ffffff, 0xfc000000000003fc, 0xfffffffffffffe3f, 0xffffffffffffffff,
+    0xf800005fffffffff, 0xffffffffffffffff, 0x0007fe0007f000c3, 0xfffcfff000000300,
+    0xffffffffffffffff, 0xffffffffffffffff, 0xfffffff800407fff, 0xffc3cfffffffffff,
+    0x001fc00007ff0007, 0xfffffe7ff1ff8000, 0xffffffffffffffff, 0xf43fffffffffffff,
+    0xffffffffffffffe0, 0xff8019fc00dfffff, 0xff0000003fe0001f, 0xfffffffffff07fe3,
+    0xffffffffffffffff, 0xfffc81ffffffffff, 0xffffffffffffffff, 0x0001ffffc03ffc01,
+    0x1fbffffe00000000, 0xffffffffefffffff, 0xffffffffffffffff, 0xffffffffff001fff,
+    0x0ff983efffffffff, 0x0000000007ffffc0, 0xfffff11ffffff800, 0xffffffffffffffdf,
+    0xc003ffffffffffff, 0x0709ffffffffffff, 0xffff8001fe03cfcc, 0xfff80000000000ff,
+    0xfffd9efffb0203ff, 0xffffffffffffffff, 0xfffffe0000ffffff, 0x8381ec3fff8effa1,
+    0x000ffffffe4001ff, 0x00001fffc0000000, 0xffffffffffdf0f00, 0x3f9fffffffffffff,
+    0x800000004ff00000, 0xc0007ffff8f01fff, 0x0000000037ffffff, 0xdf8000000003f800,
+    0xffffffffffffffff, 0x00000300000fffff, 0x720fffc700000000, 0xffffffa0000ffffc,
+    0x000000000000003f, 0xffffffcf000f0000, 0x0007ffdb1fffffff, 0x0000000000000000,
+    0x00fffc27c77fdf80, 0x00003ffffffff000, 0x0e00000000000000, 0xffffffffffe01e00,
+    0x00000000001e001f, 0x00ff000000000000, 0xfff000007f31f786, 0x00000000007fffff,
+    0xe000001c00000000, 0x06000303ffffffff, 0x0000000000000000, 0x3000000804000000,
+    0xfffffffff8000000, 0x0000000000000000, 0xfffffc0000007000, 0x0000000000000017,
+    0x0000000000000000, 0x00000fdf3383ff80, 0x000000fffffffffc, 0x07c0000000000000,
+    0x00003fffffc00000, 0x0000000000001fe0, 0x00c2dc0000000000, 0xffffffff801ffbb3,
+    0x0000000000007fff, 0x000001e000000000, 0x00000000000ffe00, 0x0000000000000000,
+    0x3ff0000018000000, 0x01ffffffffffffe0, 0x000000043c000000, 0x0018000000000000,
+    0x0000000000000000, 0x0000000000000000, 0xfffff1ffef8cf020, 0x000000007fffffff,
+    0x00000000000002ff, 0x000000001f000000, 0x0000000000000000, 0xf000000000000000,
+    0xfffffffff803ffff, 0x003f7780000001ff, 0xfc00000000000000, 0x0000000000000000,
+    0x0000000000000000, 0x7fffdff000000000, 0x0007fffffffffffe, 0x3000000000000000,
+    0x0000001e00000000, 0x0000000000000000, 0x0000000000000000, 0xffffc7fffffc0000,
+    0x000000007f9fffff, 0x0000000000000000, 0x0000000000000000, 0x0000000000000000,
+    0x0000000000000000, 0x1ffffff2003ffffc, 0x0000000000000000, 0x0000000000000000,
+    0x0000000000000000, 0x0000000000000000, 0x0000000000000000, 0x000000001fc00000,
+    0x0000000000000000, 0x0000000000000000, 0x0000000000000000, 0x0000000000000000,
+    0x0000000000000000, 0x0000000000000000, 0x0000000000000000, 0x0000000000000000,
+    0x0000000000000000, 0x0000000000000000, 0x0000000000000000, 0x0000000000000000,
+    0x0000000000000000, 0x0000000000000000, 0x0000000000000000, 0x0000000000000000,
+    0x0000000000000000, 0x0000000000000000, 0x0000000000000000, 0x0000000000000000,
+    0x0000000000000000, 0x0000000000000000, 0x0000000000000000, 0x0000000000000000,
+    0x0000000000000000, 0x0000000000000000, 0x0000000000000000, 0x0000000000000000,
+    0x0000000000000000, 0x0000000000000000, 0x0000000000000000, 0x0000000000000000,
+    0x0000000000000000, 0x0000000000000000, 0x0000000000000000, 0x0000000000000000,
+    0x0000000000000000,
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn contains() {
+        let instant = InstantMask::new();
+
+        assert!(instant.contains(15., 65.6));
+        assert!(!instant.contains(5., 65.6));
+        assert!(instant.contains(5., -90.));
+        assert!(!instant.contains(5., 90.));
+        assert!(instant.contains(5., -89.5));
+
+        assert_eq!(instant.contains(10., 60.), instant.contains(370., 60.));
+        assert_eq!(instant.contains(180., 66.), instant.contains(-180., 66.));
+    }
+
+    #[test]
+    fn agrees_with_mask() {
+        let instant = InstantMask::new();
+        let mask = RoaringMask::new().unwrap();
+
+        let mut n = 0;
+        let mut agree = 0;
+        for x in (-180..180).map(f64::from) {
+            for y in (-89..90).map(f64::from) {
+                n += 1;
+                if instant.contains(x + 0.3, y + 0.3) == mask.contains(x + 0.3, y + 0.3) {
+                    agree += 1;
+                }
+            }
+        }
+
+        assert!(agree as f64 / n as f64 > 0.97, "agree: {} / {}", agree, n);
+    }
+
+    #[test]
+    fn generated_from_mask() {
+        let mask = RoaringMask::new().unwrap();
+
+        assert_eq!(InstantMask::table_from_mask(&mask), TABLE);
+    }
+}
@@ -0,0 +1,131 @@
+//! Lazy classification of points from Python.
+
+use numpy::{PyArray, PyArrayDyn};
+use pyo3::prelude::*;
+
+use crate::RoaringLandmask;
+
+/// A Python iterator over whether each point is on land, made by
+/// `RoaringLandmask.contains_iter`. The points are checked one at a time as the iterator is
+/// advanced, so no output array is allocated.
+#[pyclass]
+pub struct ContainsIter {
+    landmask: Py<RoaringLandmask>,
+    x: Py<PyArrayDyn<f64>>,
+    y: Py<PyArrayDyn<f64>>,
+    i: usize,
+}
+
+impl ContainsIter {
+    /// Iterate over the points (x, y), which must have the same shape. The points are visited in
+    /// C order, arrays which are not C-contiguous (e.g. Fortran-ordered or strided) are copied
+    /// so that the elements of x and y are paired up the same way.
+    pub(crate) fn new(
+        py: Python,
+        landmask: Py<RoaringLandmask>,
+        x: &PyArrayDyn<f64>,
+        y: &PyArrayDyn<f64>,
+    ) -> ContainsIter {
+        let c_order = |a: &PyArrayDyn<f64>| {
+            if a.is_c_contiguous() {
+                a.to_owned()
+            } else {
+                let a = a.to_owned_array().as_standard_layout().into_owned();
+                PyArray::from_owned_array(py, a).to_owned()
+            }
+        };
+
+        ContainsIter {
+            landmask,
+            x: c_order(x),
+            y: c_order(y),
+            i: 0,
+        }
+    }
+}
+
+#[pymethods]
+impl ContainsIter {
+    fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    fn __next__(mut slf: PyRefMut<'_, Self>) -> PyResult<Option<bool>> {
+        let py = slf.py();
+
+        let land = {
+            let landmask = slf.landmask.borrow(py);
+            landmask.check_open()?;
+
+            let x = slf.x.as_ref(py).readonly();
+            let y = slf.y.as_ref(py).readonly();
+
+            match (x.as_slice()?.get(slf.i), y.as_slice()?.get(slf.i)) {
+                (Some(x), Some(y)) => landmask.contains(*x, *y),
+                _ => return Ok(None),
+            }
+        };
+
+        slf.i += 1;
+        Ok(Some(land))
+    }
+
+    /// Number of points that have not been checked yet.
+    fn __length_hint__(&self, py: Python) -> usize {
+        self.x.as_ref(py).len() - self.i
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn contains_iter() {
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| {
+            let landmask = Py::new(py, RoaringLandmask::new(py).unwrap()).unwrap();
+
+            let x = PyArray::from_vec(py, vec![15., 5., 10.]).to_dyn();
+            let y = PyArray::from_vec(py, vec![65.6, 65.6, 60.]).to_dyn();
+
+            let it = RoaringLandmask::contains_iter(landmask.borrow(py), x, y).unwrap();
+            let it = Py::new(py, it).unwrap();
+
+            let land = it
+                .as_ref(py)
+                .iter()
+                .unwrap()
+                .map(|l| l.unwrap().extract::<bool>().unwrap())
+                .collect::<Vec<_>>();
+            assert_eq!(land, vec![true, false, true]);
+        })
+    }
+
+    #[test]
+    fn contains_iter_fortran_order() {
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| {
+            let landmask = Py::new(py, RoaringLandmask::new(py).unwrap()).unwrap();
+
+            // The same 2 x 2 points, with x in Fortran order and y in C order.
+            let x = ndarray::arr2(&[[15., 5.], [10., 0.]]);
+            let x = x.t().as_standard_layout().into_owned().reversed_axes();
+            let y = ndarray::arr2(&[[65.6, 65.6], [60., 60.]]);
+            let x = PyArray::from_owned_array(py, x.into_dyn());
+            let y = PyArray::from_owned_array(py, y.into_dyn());
+            assert!(x.is_fortran_contiguous() && !x.is_c_contiguous());
+
+            let it = RoaringLandmask::contains_iter(landmask.borrow(py), x, y).unwrap();
+            let it = Py::new(py, it).unwrap();
+
+            let land = it
+                .as_ref(py)
+                .iter()
+                .unwrap()
+                .map(|l| l.unwrap().extract::<bool>().unwrap())
+                .collect::<Vec<_>>();
+            assert_eq!(land, vec![true, false, true, false]);
+        })
+    }
+}
@@ -66,54 +66,531 @@ extern crate lazy_static;
 // correct flags to the linker.
 // extern crate link_cplusplus;
 
-use numpy::{PyArray, PyReadonlyArrayDyn};
+use numpy::{PyArray, PyArrayDyn, PyReadonlyArray2, PyReadonlyArrayDyn};
+use pyo3::exceptions::PyValueError;
 use pyo3::prelude::*;
+use std::borrow::Cow;
+use std::convert::TryFrom;
 use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock, Weak};
 
+#[cfg(feature = "arrow")]
+pub mod arrow_interop;
+pub mod bundle;
+#[cfg(feature = "capi")]
+pub mod capi;
+pub mod context;
+pub mod contours;
+pub mod crude;
+pub mod distance;
+#[cfg(feature = "download")]
+pub mod download;
+pub mod error;
+pub mod grid;
+pub mod instant;
+pub mod iter;
 pub mod mask;
+pub mod metadata;
+pub mod nearest;
+#[cfg(feature = "tokio")]
+pub mod offload;
+pub mod path;
+pub mod reference;
+pub mod rotated;
 pub mod shapes;
+pub mod tiles;
 
-pub use mask::RoaringMask;
-pub use shapes::Gshhg;
+pub use context::QueryContext;
+pub use crude::CrudeMask;
+pub use distance::{Earth, Unit};
+pub use error::LandmaskError;
+pub use grid::GridMask;
+pub use instant::InstantMask;
+pub use iter::ContainsIter;
+pub use mask::{CellRule, RoaringMask, Summary};
+pub use metadata::Metadata;
+pub use nearest::PointSet;
+pub use shapes::{Antarctica, BBox, Gshhg, PointInPolygon, PolygonId};
+pub use tiles::TiledMask;
 
 include!(concat!(env!("OUT_DIR"), "/gshhs.rs"));
 
+/// Class of points in the ocean outside all regions, see `RoaringLandmask::classify_regions`.
+pub const OCEAN: i32 = -1;
+
+/// Class of points on land outside all regions, see `RoaringLandmask::classify_regions`.
+pub const LAND: i32 = -2;
+
+/// Default number of samples along each side of the area in `RoaringLandmask::land_fraction` and
+/// `RoaringLandmask::classify_polygon`.
+pub const COVERAGE_SAMPLES: usize = 16;
+
+/// Number of samples along each side of a cell of the mask in `RoaringLandmask::land_weight`.
+pub const WEIGHT_SAMPLES: usize = 4;
+
+/// Distance in meters from the shore within which `RoaringLandmask::signed_distance_to_shore`
+/// measures to the coastline of the shapes rather than to the cells of the mask.
+pub const COASTLINE_DISTANCE: f64 = 50_000.;
+
+/// The embedded mask and shapes, see `shared`.
+type SharedData = (RoaringMask, Gshhg);
+
+lazy_static! {
+    /// The embedded mask and shapes, shared by every landmask made with `RoaringLandmask::new`.
+    /// Only the landmasks hold on to the data, so it is freed once they are all closed or
+    /// dropped.
+    static ref SHARED: Mutex<Weak<SharedData>> = Mutex::new(Weak::new());
+}
+
+/// The embedded mask and shapes kept by `preload_shared`.
+static PRELOADED: OnceLock<Arc<SharedData>> = OnceLock::new();
+
+/// The embedded mask and shapes held by the landmasks made with `RoaringLandmask::new`, or
+/// decoded again if there are none.
+fn shared() -> io::Result<Arc<SharedData>> {
+    shared_in(&SHARED, || Ok((RoaringMask::new()?, Gshhg::embedded()?)))
+}
+
+/// The data in `slot` if it is still held by anyone, otherwise that of `load`, which is then
+/// kept in `slot` for as long as it is held.
+fn shared_in(
+    slot: &Mutex<Weak<SharedData>>,
+    load: impl FnOnce() -> io::Result<SharedData>,
+) -> io::Result<Arc<SharedData>> {
+    // The lock is held while loading, so that concurrent callers wait for the same data.
+    let mut slot = slot.lock().unwrap_or_else(|e| e.into_inner());
+
+    if let Some(data) = slot.upgrade() {
+        return Ok(data);
+    }
+
+    let data = Arc::new(load()?);
+    *slot = Arc::downgrade(&data);
+    Ok(data)
+}
+
+/// Decode the embedded mask and shapes now rather than on the first `RoaringLandmask.new()`,
+/// e.g. before forking workers. The data is then kept for the lifetime of the process, even
+/// after all landmasks are closed.
+#[pyfunction]
+pub fn preload_shared(_py: Python) -> io::Result<()> {
+    let data = shared()?;
+    PRELOADED.get_or_init(|| data);
+    Ok(())
+}
+
+/// The points along the path through `coords` (longitude, latitude), sampled at most `step_m`
+/// meters apart along the great circle of each leg, see `path::densify_track`.
+#[pyfunction]
+#[pyo3(signature = (coords, step_m = path::STEP))]
+pub fn densify_track(coords: Vec<(f64, f64)>, step_m: f64) -> PyResult<Vec<(f64, f64)>> {
+    if !(step_m > 0.) {
+        return Err(PyValueError::new_err("step_m must be greater than zero"));
+    }
+
+    Ok(path::densify_track(&coords, step_m))
+}
+
 #[pymodule]
 fn roaring_landmask(_py: Python, m: &PyModule) -> PyResult<()> {
+    m.add_function(wrap_pyfunction!(preload_shared, m)?)?;
+    m.add_function(wrap_pyfunction!(densify_track, m)?)?;
+    m.add("OCEAN", OCEAN)?;
+    m.add("LAND", LAND)?;
+    m.add("COVERAGE_SAMPLES", COVERAGE_SAMPLES)?;
     m.add_class::<mask::Affine>()?;
+    m.add_class::<Unit>()?;
+    m.add_class::<Earth>()?;
+    m.add_class::<Antarctica>()?;
+    m.add_class::<Decision>()?;
+    m.add_class::<QueryStats>()?;
+    m.add_class::<Tri>()?;
+    m.add_class::<Coverage>()?;
+    m.add_class::<OutOfDomain>()?;
+    m.add_class::<Sampling>()?;
+    m.add_class::<EnclosedWater>()?;
+    m.add_class::<GridMask>()?;
+    m.add_class::<ContainsIter>()?;
     m.add_class::<RoaringMask>()?;
+    m.add_class::<CrudeMask>()?;
+    m.add_class::<InstantMask>()?;
     m.add_class::<Gshhg>()?;
+    m.add_class::<Metadata>()?;
+    m.add_class::<PointSet>()?;
     m.add_class::<RoaringLandmask>()?;
+    m.add_class::<TiledMask>()?;
 
     Ok(())
 }
 
+/// Which stage of the landmask decided the result of a query, see
+/// `RoaringLandmask::contains_explain`.
+#[pyclass]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Decision {
+    /// The mask has no land in the cell, the shapes were not consulted.
+    OceanByMask,
+    /// The mask has land in the cell, and the shapes confirmed it.
+    LandByMaskAndShapes,
+    /// The mask has land in the cell, but the point is in the ocean according to the shapes.
+    OceanByShapesNearShore,
+    /// The point is in shallow water which counts as land, see `RoaringLandmask::with_bathymetry`.
+    LandByBathymetry,
+    /// The point is outside the landmask and in the ocean by `OutOfDomain::ReturnFalse`.
+    OutsideLandmask,
+}
+
+#[pymethods]
+impl Decision {
+    /// Whether the point is on land.
+    pub fn land(&self) -> bool {
+        matches!(
+            self,
+            Decision::LandByMaskAndShapes | Decision::LandByBathymetry
+        )
+    }
+
+    /// Whether the shapes were consulted.
+    pub fn shapes_consulted(&self) -> bool {
+        !matches!(self, Decision::OceanByMask | Decision::OutsideLandmask)
+    }
+}
+
+/// How many points of a batch were decided at each stage of the landmask, see
+/// `RoaringLandmask::try_contains_slice_stats`. The counters are atomic, so the same stats can be
+/// shared by the parallel batch methods and accumulated over several batches.
+///
+/// Points outside the landmask and at the poles only count towards `total`. Points in shallow
+/// water from `with_bathymetry` are counted at the stage they passed, and in `land_by_bathymetry`.
+#[pyclass]
+#[derive(Debug, Default)]
+pub struct QueryStats {
+    total: AtomicU64,
+    rejected_by_mask: AtomicU64,
+    passed_mask: AtomicU64,
+    land_by_shapes: AtomicU64,
+    ocean_by_shapes: AtomicU64,
+    land_by_bathymetry: AtomicU64,
+}
+
+impl QueryStats {
+    fn add(counter: &AtomicU64) {
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+#[pymethods]
+impl QueryStats {
+    #[new]
+    pub fn new() -> QueryStats {
+        QueryStats::default()
+    }
+
+    /// All points checked.
+    #[getter]
+    pub fn total(&self) -> u64 {
+        self.total.load(Ordering::Relaxed)
+    }
+
+    /// Points in the ocean according to the mask, the shapes were not consulted.
+    #[getter]
+    pub fn rejected_by_mask(&self) -> u64 {
+        self.rejected_by_mask.load(Ordering::Relaxed)
+    }
+
+    /// Points on land according to the mask, which were checked with the shapes.
+    #[getter]
+    pub fn passed_mask(&self) -> u64 {
+        self.passed_mask.load(Ordering::Relaxed)
+    }
+
+    /// Points which passed the mask and are on land according to the shapes.
+    #[getter]
+    pub fn land_by_shapes(&self) -> u64 {
+        self.land_by_shapes.load(Ordering::Relaxed)
+    }
+
+    /// Points which passed the mask, but are in the ocean according to the shapes.
+    #[getter]
+    pub fn ocean_by_shapes(&self) -> u64 {
+        self.ocean_by_shapes.load(Ordering::Relaxed)
+    }
+
+    /// Points in the ocean which count as land because of shallow water.
+    #[getter]
+    pub fn land_by_bathymetry(&self) -> u64 {
+        self.land_by_bathymetry.load(Ordering::Relaxed)
+    }
+
+    /// Set all counters to zero.
+    pub fn reset(&self) {
+        for counter in [
+            &self.total,
+            &self.rejected_by_mask,
+            &self.passed_mask,
+            &self.land_by_shapes,
+            &self.ocean_by_shapes,
+            &self.land_by_bathymetry,
+        ] {
+            counter.store(0, Ordering::Relaxed);
+        }
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "QueryStats(total={}, rejected_by_mask={}, passed_mask={}, land_by_shapes={}, ocean_by_shapes={}, land_by_bathymetry={})",
+            self.total(),
+            self.rejected_by_mask(),
+            self.passed_mask(),
+            self.land_by_shapes(),
+            self.ocean_by_shapes(),
+            self.land_by_bathymetry()
+        )
+    }
+}
+
+/// Land, ocean, or too close to the coast to tell, see `RoaringLandmask::contains_tri`.
+#[pyclass]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Tri {
+    Land,
+    Ocean,
+    Uncertain,
+}
+
+/// Whether an area is all land, all ocean or both, see `RoaringLandmask::classify_polygon`.
+#[pyclass]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Coverage {
+    Land,
+    Ocean,
+    Mixed,
+}
+
+/// What to do with a query outside the extent of the landmask, i.e. with a latitude beyond ±90
+/// or NaN. See `RoaringLandmask::with_out_of_domain`.
+#[pyclass]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OutOfDomain {
+    /// Panic, this is the default.
+    #[default]
+    Panic,
+    /// The point is in the ocean.
+    ReturnFalse,
+    /// Return an error from `try_contains`, the other `try_` methods and the Python methods
+    /// (`ValueError`). `contains` and the other methods without `try_` panic.
+    Error,
+}
+
+/// Where the query points are within the cells of a grid at the resolution of the mask, see
+/// `RoaringLandmask::with_sampling`.
+#[pyclass]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Sampling {
+    /// The points are cell centers, this is the default.
+    #[default]
+    Center,
+    /// The points are the south-west corners of the cells, so they are moved half a cell north
+    /// and east before the lookup.
+    Corner,
+}
+
+impl Sampling {
+    /// The center of the cell that (x, y) samples.
+    fn center(&self, x: f64, y: f64) -> (f64, f64) {
+        match self {
+            Sampling::Center => (x, y),
+            Sampling::Corner => {
+                let half = 90. / mask::NY as f64;
+                (x + half, (y + half).min(90.))
+            }
+        }
+    }
+}
+
+/// How the shapes of a landmask treat water enclosed by land, i.e. GSHHG levels 2 (lakes), 3
+/// (islands in lakes) and 4 (ponds on such islands). See `RoaringLandmask::with_enclosed_water`.
+///
+/// The bundled shapes only hold level 1, with the lakes as part of the land, so the policy only
+/// matters for shapes with lakes: with the embedded data Lake Victoria is land under every
+/// policy. GSHHG distributes the other levels as separate files (e.g. `GSHHS_f_L2.shp` for the
+/// lakes). Cut them out of the land as holes (or add them as polygons nested within it), load the
+/// result with `Gshhg::from_wkb`, and make the landmask with `RoaringLandmask::from_parts` and
+/// the embedded mask (`RoaringLandmask.from_shapes` in Python), which has land in the lakes.
+#[pyclass]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum EnclosedWater {
+    /// Every level is land: lakes and ponds count as land.
+    AsLand,
+    /// Only level 1 is land: lakes are water like the ocean, and so is everything within them,
+    /// including islands in lakes.
+    AsOcean,
+    /// The levels alternate as in GSHHG: lakes and ponds are water and islands in lakes are
+    /// land. This is the default, and what the point-in-polygon test of the shapes gives.
+    #[default]
+    AsSeparate,
+}
+
 #[pyclass]
 pub struct RoaringLandmask {
     #[pyo3(get)]
     pub mask: RoaringMask,
     #[pyo3(get)]
     pub shapes: shapes::Gshhg,
+    /// Shallow water which counts as land, see `with_bathymetry`.
+    shallow: Option<RoaringMask>,
+    /// What to do with queries outside the landmask, see `with_out_of_domain`.
+    #[pyo3(get, set)]
+    out_of_domain: OutOfDomain,
+    /// Whether query points are cell centers or corners, see `with_sampling`.
+    sampling: Sampling,
+    /// Whether lakes and ponds count as land, see `with_enclosed_water`.
+    enclosed_water: EnclosedWater,
+    /// The shape of the Earth for distances, see `with_earth`.
+    #[pyo3(get, set)]
+    earth: Earth,
+    /// Labeled polygons, see `load_zones`.
+    zones: Vec<(String, Gshhg)>,
+    /// The mask of the major landmasses, see `contains_major`.
+    major: OnceLock<RoaringMask>,
+    /// The classification of the whole-degree grid, see `contains_int`.
+    integer_grid: OnceLock<Vec<bool>>,
+    /// The embedded data this landmask was made from, held so that the next landmask from `new`
+    /// can share it, see `shared`.
+    shared: Option<Arc<SharedData>>,
+    closed: bool,
 }
 
-#[pymethods]
 impl RoaringLandmask {
-    #[staticmethod]
-    pub fn new(py: Python) -> io::Result<RoaringLandmask> {
-        let mask = RoaringMask::new()?;
-        let shapes = Gshhg::new(py)?;
+    /// Make a landmask from a mask and shapes. The mask must have land wherever the shapes have
+    /// land.
+    pub fn from_parts(mask: RoaringMask, shapes: Gshhg) -> RoaringLandmask {
+        RoaringLandmask {
+            mask,
+            shapes,
+            shallow: None,
+            out_of_domain: OutOfDomain::default(),
+            sampling: Sampling::default(),
+            enclosed_water: EnclosedWater::default(),
+            earth: Earth::default(),
+            zones: Vec::new(),
+            major: OnceLock::new(),
+            integer_grid: OnceLock::new(),
+            shared: None,
+            closed: false,
+        }
+    }
 
-        Ok(RoaringLandmask { mask, shapes })
+    /// Use the landmasses of at least `min_area_km2` square kilometers for `contains_major`
+    /// instead of those of `MAJOR_LANDMASS_KM2`, and rasterize them now.
+    pub fn with_major_landmasses(mut self, min_area_km2: f64) -> RoaringLandmask {
+        self.major = OnceLock::from(major_landmasses(&self.shapes, min_area_km2));
+        self
     }
 
-    #[getter]
-    pub fn dx(&self) -> f64 {
-        self.mask.dx()
+    /// Replace the shapes with `shapes`, e.g. from `Gshhg::simplify`, and rasterize the mask from
+    /// them (see `RoaringMask::from_shapes`), so that the mask covers all of their land.
+    /// Rasterizing the mask at full resolution takes a while.
+    pub fn with_shapes(mut self, shapes: Gshhg) -> RoaringLandmask {
+        self.mask = RoaringMask::from_shapes(&shapes);
+        self.shapes = shapes;
+        self.major = OnceLock::new();
+        self.clear_integer_grid();
+        self
     }
 
-    #[getter]
-    pub fn dy(&self) -> f64 {
-        self.mask.dy()
+    /// Same as `new`, but without needing Python, e.g. for the C interface in `capi`.
+    pub fn embedded() -> io::Result<RoaringLandmask> {
+        let shared = shared()?;
+
+        let mut landmask = RoaringLandmask::from_parts(shared.0.clone(), shared.1.clone());
+        landmask.shared = Some(shared);
+        Ok(landmask)
+    }
+
+    /// Use `policy` for queries outside the landmask.
+    pub fn with_out_of_domain(mut self, policy: OutOfDomain) -> RoaringLandmask {
+        self.out_of_domain = policy;
+        self
+    }
+
+    /// Use `policy` for water enclosed by land in `contains` and the methods based on it, e.g.
+    /// `AsOcean` for hydrology where lakes are not land.
+    ///
+    /// Policies other than the default `EnclosedWater::AsSeparate` look up the level of the
+    /// point with `Gshhg::level`, which scans the polygons by their bounding boxes and is slower
+    /// than the point-in-polygon test. With `AsLand` the mask must have land in the lakes, as
+    /// a mask made from shapes without lakes has.
+    ///
+    /// The policy needs shapes with levels 2 to 4, the bundled shapes have none, see
+    /// `EnclosedWater` for how to load them.
+    pub fn with_enclosed_water(mut self, policy: EnclosedWater) -> RoaringLandmask {
+        self.enclosed_water = policy;
+        self.clear_integer_grid();
+        self
+    }
+
+    /// Keep the candidate coastline cells of the tiles queried by `distance_to_shore` and the
+    /// methods based on it between calls if `enabled`, see `RoaringMask::with_distance_cache`.
+    /// With the cache `distance_to_shore` also looks up single points in the tiles, so repeated
+    /// queries around the same coast, e.g. a gridded distance field computed point by point or
+    /// in chunks, do not search the mask again.
+    pub fn with_distance_cache(mut self, enabled: bool) -> RoaringLandmask {
+        self.mask = self.mask.with_distance_cache(enabled);
+        self
+    }
+
+    /// Measure distances on `earth`, e.g. `Earth::Wgs84` where the error of up to 0.6 % of the
+    /// sphere matters. This applies to `distance_to_shore`, `signed_distance_to_shore` and the
+    /// methods based on them, and to the lengths of `land_time_fraction` and
+    /// `crossed_onto_land`. For areas see `Gshhg::polygon_area_m2_on`.
+    ///
+    /// The nearest cell of the mask is still searched for on the sphere, and only the distance to
+    /// it is measured on the ellipsoid. Another cell which is almost as near may be nearer on the
+    /// ellipsoid, so the distance can be slightly too long, by less than the error of the
+    /// sphere. The batch methods then search point by point instead of by tiles (see
+    /// `RoaringMask::nearest_land_distances`) and are much slower for dense points.
+    pub fn with_earth(mut self, earth: Earth) -> RoaringLandmask {
+        self.earth = earth;
+        self
+    }
+
+    /// Use `sampling` for the points given to `contains` and the batch methods based on it.
+    ///
+    /// When coupling with gridded data at the resolution of the mask whose values are defined at
+    /// the cell corners, the point should be looked up half a cell away, in the cell it covers.
+    /// `level`, `contains_explain`, `contains_exact` and `contains_raw` look up the same cell as
+    /// `contains`. The other queries, e.g. `distance_to_shore`, use the points as given.
+    pub fn with_sampling(mut self, sampling: Sampling) -> RoaringLandmask {
+        self.sampling = sampling;
+        self.clear_integer_grid();
+        self
+    }
+
+    /// Drop the table of `contains_int` after changing anything that `contains` depends on, so
+    /// that it is made again with the new settings.
+    fn clear_integer_grid(&mut self) {
+        self.integer_grid = OnceLock::new();
+    }
+
+    /// The result for (x, y) outside the landmask, according to the policy.
+    fn outside(&self, x: f64, y: f64) -> io::Result<bool> {
+        let msg = || format!("({}, {}) is outside the landmask", x, y);
+
+        match self.out_of_domain {
+            OutOfDomain::Panic => panic!("{}", msg()),
+            OutOfDomain::ReturnFalse => Ok(false),
+            OutOfDomain::Error => Err(io::Error::new(io::ErrorKind::InvalidInput, msg())),
+        }
+    }
+
+    /// Whether (x, y) is in shallow water that counts as land.
+    fn shallow(&self, x: f64, y: f64) -> bool {
+        self.shallow
+            .as_ref()
+            .map_or(false, |shallow| shallow.contains_unchecked(x, y))
     }
 
     /// Check if point (x, y) is on land.
@@ -122,114 +599,4791 @@ impl RoaringLandmask {
     /// `y` is latitude,  [- 90,  90] north
     ///
     ///
-    /// Returns `true` if the point is on land or close to the shore.
+    /// Returns `true` if the point is on land or close to the shore. Panics after `close`, see
+    /// `try_contains`.
+    ///
+    /// At the poles the longitude is irrelevant: the North Pole is always in the ocean and the
+    /// South Pole is always on land.
     pub fn contains(&self, x: f64, y: f64) -> bool {
-        assert!(y >= -90. && y <= 90.);
+        self.contains_counted(x, y, None, true).unwrap()
+    }
+
+    /// Same as `contains`, but only checks the mask and never the shapes. This is much faster,
+    /// but over-reports land near the shore: the mask is land in every cell which touches the
+    /// coastline, so points up to about a cell (~460 m) out at sea may be reported as land. Points
+    /// reported as ocean are always ocean.
+    pub fn contains_approx(&self, x: f64, y: f64) -> bool {
+        self.contains_counted(x, y, None, false).unwrap()
+    }
+
+    /// Same as `contains`, but only runs the shapes for points within `cells` cells of a
+    /// boundary between land and ocean in the mask, and otherwise trusts the mask. Points deep
+    /// inland then skip the point-in-polygon test entirely, which is most of the cost of
+    /// `contains` on land. Points in the ocean according to the mask are not affected.
+    ///
+    /// A cell of the mask has land if any land touches it, so cells surrounded by land can still
+    /// hold water smaller than `cells` cells (about 460 m each): lakes, rivers and narrow fjords
+    /// are then reported as land. With `cells = 0` the result is that of the mask alone
+    /// (`RoaringMask::contains`). The neighbourhood is checked cell by cell, so the cost grows
+    /// with the square of `cells`.
+    pub fn contains_tol(&self, x: f64, y: f64, cells: u32) -> bool {
+        self.try_contains_tol(x, y, cells).unwrap()
+    }
+
+    /// Same as `contains_tol`, but returns an error for points outside the landmask with the
+    /// `OutOfDomain::Error` policy, see `try_contains`.
+    pub fn try_contains_tol(&self, x: f64, y: f64, cells: u32) -> io::Result<bool> {
+        self.check_closed()?;
+
+        if !(y >= -90. && y <= 90.) {
+            return self.outside(x, y);
+        }
+
+        let (x, y) = self.sampling.center(x, y);
+
+        if let Some(land) = pole(y) {
+            return Ok(land);
+        }
 
         let x = modulate_longitude(x);
 
-        self.mask.contains_unchecked(x, y) && self.shapes.contains_unchecked(x, y)
+        Ok((self.mask.contains_unchecked(x, y)
+            && (self.mask.land_within_unchecked(x, y, cells) || self.shapes_contains(x, y)))
+            || self.shallow(x, y))
     }
 
-    fn contains_many(
-        &self,
-        py: Python,
-        x: PyReadonlyArrayDyn<f64>,
-        y: PyReadonlyArrayDyn<f64>,
-    ) -> Py<PyArray<bool, numpy::Ix1>> {
-        let x = x.as_array();
-        let y = y.as_array();
+    /// A context for checking many points in a row, e.g. along a track, which skips repeated
+    /// lookups of the same cell. See `QueryContext`.
+    pub fn context(&self) -> QueryContext<'_> {
+        QueryContext::new(self)
+    }
 
-        PyArray::from_iter(
-            py,
-            x.iter().zip(y.iter()).map(|(x, y)| self.contains(*x, *y)),
-        )
-        .to_owned()
+    /// Check if point (x, y) is on one of the major landmasses, ignoring all smaller islands,
+    /// e.g. for classifying ocean basins. The landmasses are those of at least
+    /// `MAJOR_LANDMASS_KM2`, see `with_major_landmasses`.
+    ///
+    /// The landmasses are rasterized into a separate mask on the first call, which takes a few
+    /// seconds, and later calls only look up the cell: like `RoaringMask::contains`, the
+    /// answer is optimistic within a cell of the coast.
+    pub fn contains_major(&self, x: f64, y: f64) -> bool {
+        self.major
+            .get_or_init(|| major_landmasses(&self.shapes, MAJOR_LANDMASS_KM2))
+            .contains(x, y)
     }
 
-    pub fn contains_many_par(
-        &self,
-        py: Python,
-        x: PyReadonlyArrayDyn<f64>,
-        y: PyReadonlyArrayDyn<f64>,
-    ) -> Py<PyArray<bool, numpy::IxDyn>> {
-        let x = x.as_array();
-        let y = y.as_array();
+    /// `contains` at every whole degree, for the 360 longitudes from -180 to 179 and the 181
+    /// latitudes from -90 to 90: the point (`lon`, `lat`) is at index
+    /// `(lat + 90) * 360 + (lon + 180)`.
+    pub fn integer_grid_table(&self) -> Vec<bool> {
+        use ndarray::parallel::prelude::*;
 
-        use ndarray::Zip;
-        let contains = Zip::from(&x)
-            .and(&y)
-            .par_map_collect(|x, y| self.contains(*x, *y));
-        PyArray::from_owned_array(py, contains).to_owned()
+        (0..181 * 360)
+            .into_par_iter()
+            .map(|i| self.contains((i % 360) as f64 - 180., (i / 360) as f64 - 90.))
+            .collect()
     }
-}
 
-/// Move longitude into -180 to 180 domain.
-fn modulate_longitude(lon: f64) -> f64 {
-    ((lon + 180.) % 360.) - 180.
-}
+    /// Check if the whole-degree point (`lon`, `lat`) is on land, with a single lookup in the
+    /// table of `integer_grid_table`. The table is made on the first call, which takes about as
+    /// long as 65 000 calls to `contains`, so this only pays off for many queries on a fixed
+    /// coarse grid.
+    ///
+    /// The result is that of `contains` at exactly those points and says nothing about the rest
+    /// of the cell around them. `lon` is wrapped to [-180, 180), while `lat` outside [-90, 90] is
+    /// handled as in `contains`.
+    pub fn contains_int(&self, lon: i32, lat: i32) -> bool {
+        if !(-90..=90).contains(&lat) {
+            return self.outside(lon as f64, lat as f64).unwrap();
+        }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+        let table = self.integer_grid.get_or_init(|| self.integer_grid_table());
+        table[(lat + 90) as usize * 360 + (lon + 180).rem_euclid(360) as usize]
+    }
 
-    #[test]
-    fn load_ms() {
-        pyo3::prepare_freethreaded_python();
-        pyo3::Python::with_gil(|py| {
-            let _ms = RoaringLandmask::new(py).unwrap();
-        })
+    /// The GSHHG level of (x, y): 0 in the ocean, and 1 to 4 for land, lake, island in lake and
+    /// pond on island, see `Gshhg::level`. Shallow water from `with_bathymetry` is level 1.
+    ///
+    /// The bundled shapes only hold level 1, so the level is 0 or 1 unless the shapes have been
+    /// replaced by ones with lakes.
+    ///
+    /// Points outside the landmask are handled like in `contains`, see `with_out_of_domain`.
+    pub fn level(&self, x: f64, y: f64) -> u8 {
+        self.try_level(x, y).unwrap()
     }
 
-    #[test]
-    fn test_np() {
-        pyo3::prepare_freethreaded_python();
-        pyo3::Python::with_gil(|py| {
-            let mask = RoaringLandmask::new(py).unwrap();
-            assert!(!mask.contains(5., 90.));
-        })
+    /// Same as `level`, but returns an error for points outside the landmask with the
+    /// `OutOfDomain::Error` policy, see `try_contains`.
+    pub fn try_level(&self, x: f64, y: f64) -> io::Result<u8> {
+        self.check_closed()?;
+
+        if !(y >= -90. && y <= 90.) {
+            return self.outside(x, y).map(u8::from);
+        }
+
+        let (x, y) = self.sampling.center(x, y);
+
+        if let Some(land) = pole(y) {
+            return Ok(land as u8);
+        }
+
+        let x = modulate_longitude(x);
+
+        let level = if self.mask.contains_unchecked(x, y) {
+            self.shapes.level_unchecked(x, y)
+        } else {
+            0
+        };
+
+        if level == 0 && self.shallow(x, y) {
+            Ok(1)
+        } else {
+            Ok(level)
+        }
     }
 
-    #[test]
-    fn test_sp() {
-        pyo3::prepare_freethreaded_python();
-        pyo3::Python::with_gil(|py| {
-            let mask = RoaringLandmask::new(py).unwrap();
-            assert!(mask.contains(5., -89.99));
-        })
+    /// Check if point (x, y) is on land or inside any of the polygons of `extra`, e.g. marine
+    /// protected areas or other zones that should be excluded along with land. The landmask
+    /// itself is not changed.
+    pub fn contains_with_extra(&self, x: f64, y: f64, extra: &Gshhg) -> bool {
+        self.contains(x, y) || extra.contains(x, y)
     }
 
-    #[test]
-    #[should_panic]
-    fn test_sp_oob() {
-        pyo3::prepare_freethreaded_python();
-        pyo3::Python::with_gil(|py| {
-            let mask = RoaringLandmask::new(py).unwrap();
-            assert!(mask.contains(5., -90.));
-        })
+    /// Classify (x, y) in a single pass: the index of the first of `regions` which contains the
+    /// point, otherwise `LAND` or `OCEAN`. The regions are checked before the landmask, so they
+    /// may also cover land.
+    pub fn classify_regions(&self, x: f64, y: f64, regions: &[Gshhg]) -> i32 {
+        self.try_classify_regions(x, y, regions).unwrap()
     }
 
-    #[test]
-    fn test_dateline_wrap() {
-        pyo3::prepare_freethreaded_python();
-        pyo3::Python::with_gil(|py| {
-            let mask = RoaringLandmask::new(py).unwrap();
+    /// Same as `classify_regions`, but returns an error for points outside the landmask with the
+    /// `OutOfDomain::Error` policy, see `try_contains`.
+    pub fn try_classify_regions(&self, x: f64, y: f64, regions: &[Gshhg]) -> io::Result<i32> {
+        self.check_closed()?;
 
-            // Close to NP
-            assert!(!mask.contains(5., 89.));
+        if !(y >= -90. && y <= 90.) {
+            return self
+                .outside(x, y)
+                .map(|land| if land { LAND } else { OCEAN });
+        }
 
-            // Close to SP
-            assert!(mask.contains(5., -89.));
+        // Not `Gshhg::contains`, whose shortcut at the poles would put the South Pole in the
+        // first region.
+        let rx = modulate_longitude(x);
+        match regions.iter().position(|r| r.contains_unchecked(rx, y)) {
+            Some(i) => Ok(i32::try_from(i).expect("too many regions")),
+            None if self.try_contains(x, y)? => Ok(LAND),
+            None => Ok(OCEAN),
+        }
+    }
 
-            // Within bounds
-            let x = (-180..180).map(f64::from).collect::<Vec<_>>();
-            let m = x.iter().map(|x| mask.contains(*x, 65.)).collect::<Vec<_>>();
+    /// Classify the points (`x[i]`, `y[i]`) in parallel, see `classify_regions`.
+    ///
+    /// Panics if `x` and `y` have different lengths.
+    pub fn classify_regions_slice(&self, x: &[f64], y: &[f64], regions: &[Gshhg]) -> Vec<i32> {
+        use ndarray::parallel::prelude::*;
 
-            // Wrapped bounds
-            let x = (180..540).map(f64::from).collect::<Vec<_>>();
-            let mm = x.iter().map(|x| mask.contains(*x, 65.)).collect::<Vec<_>>();
+        assert_eq!(x.len(), y.len());
 
-            assert_eq!(m, mm);
+        x.par_iter()
+            .zip(y.par_iter())
+            .map(|(x, y)| self.classify_regions(*x, *y, regions))
+            .collect()
+    }
+
+    /// Add the polygons of `zones` under `label`, e.g. buffers along the coastline made offline,
+    /// for `zone_of`. The zones are kept in the order they were loaded, and a label may be loaded
+    /// more than once.
+    pub fn load_zones(&mut self, zones: Gshhg, label: String) {
+        self.zones.push((label, zones));
+    }
+
+    /// The labels of the zones, in the order they were loaded, see `load_zones`.
+    pub fn zone_labels(&self) -> Vec<&str> {
+        self.zones.iter().map(|(label, _)| label.as_str()).collect()
+    }
+
+    /// The label of the first zone loaded with `load_zones` which contains (x, y), or `None` if
+    /// there is none or the point is outside the landmask. The landmask itself is not consulted,
+    /// see `classify_regions` for both in one pass.
+    pub fn zone_of(&self, x: f64, y: f64) -> Option<&str> {
+        if !(y >= -90. && y <= 90.) {
+            return None;
+        }
+
+        // Not `Gshhg::contains`, whose shortcut at the poles would put the South Pole in the
+        // first zone.
+        let x = modulate_longitude(x);
+        self.zones
+            .iter()
+            .find(|(_, zones)| zones.contains_unchecked(x, y))
+            .map(|(label, _)| label.as_str())
+    }
+
+    /// Same as `zone_of` for each of `points`, in parallel.
+    pub fn zone_of_many_blocking(&self, points: &[(f64, f64)]) -> Vec<Option<&str>> {
+        use ndarray::parallel::prelude::*;
+
+        points
+            .par_iter()
+            .map(|&(x, y)| self.zone_of(x, y))
+            .collect()
+    }
+
+    /// Same as `contains`, but with the longitude and latitude in radians.
+    ///
+    /// Latitudes beyond ±π/2 are outside the landmask like in `contains`, see
+    /// `with_out_of_domain`.
+    pub fn contains_rad(&self, lon_rad: f64, lat_rad: f64) -> bool {
+        self.contains(lon_rad.to_degrees(), lat_rad.to_degrees())
+    }
+
+    /// Same as `contains`, but counts the stage which decided the result in `stats`.
+    pub fn contains_with_stats(&self, x: f64, y: f64, stats: &QueryStats) -> bool {
+        self.try_contains_with_stats(x, y, stats).unwrap()
+    }
+
+    /// Same as `contains_with_stats`, but returns an error for points outside the landmask with
+    /// the `OutOfDomain::Error` policy, see `try_contains`.
+    pub fn try_contains_with_stats(&self, x: f64, y: f64, stats: &QueryStats) -> io::Result<bool> {
+        self.contains_counted(x, y, Some(stats), true)
+    }
+
+    /// `contains`, or `contains_approx` if not `exact`, counting the stage which decided the
+    /// result in `stats` if given. Returns the error of `outside` for points outside the
+    /// landmask.
+    fn contains_counted(
+        &self,
+        x: f64,
+        y: f64,
+        stats: Option<&QueryStats>,
+        exact: bool,
+    ) -> io::Result<bool> {
+        self.contains_staged(
+            x,
+            y,
+            stats,
+            exact,
+            |x, y| self.mask.contains_unchecked(x, y),
+            |x, y| self.shapes_contains(x, y),
+        )
+    }
+
+    /// The stages of `contains` for (x, y), with the lookups in the mask and the shapes done by
+    /// `mask` and `shapes`, e.g. through the cache of a `QueryContext`. Both get the point with
+    /// the longitude in [-180, 180), and `shapes` is only called for points where `mask` has
+    /// land, and only if `exact`. Points which pass the mask are land without `exact`.
+    ///
+    /// The stage which decided the result is counted in `stats` if given. The poles are only
+    /// counted in the total.
+    fn contains_staged(
+        &self,
+        x: f64,
+        y: f64,
+        stats: Option<&QueryStats>,
+        exact: bool,
+        mask: impl FnOnce(f64, f64) -> bool,
+        shapes: impl FnOnce(f64, f64) -> bool,
+    ) -> io::Result<bool> {
+        let count = |stage: fn(&QueryStats) -> &AtomicU64| {
+            if let Some(stats) = stats {
+                QueryStats::add(stage(stats));
+            }
+        };
+
+        self.check_closed()?;
+
+        count(|s| &s.total);
+
+        if !(y >= -90. && y <= 90.) {
+            return self.outside(x, y);
+        }
+
+        let (x, y) = self.sampling.center(x, y);
+
+        if let Some(land) = pole(y) {
+            return Ok(land);
+        }
+
+        let x = modulate_longitude(x);
+
+        let land = if mask(x, y) {
+            count(|s| &s.passed_mask);
+
+            if !exact {
+                true
+            } else if shapes(x, y) {
+                count(|s| &s.land_by_shapes);
+                true
+            } else {
+                count(|s| &s.ocean_by_shapes);
+                false
+            }
+        } else {
+            count(|s| &s.rejected_by_mask);
+            false
+        };
+
+        if !land && self.shallow(x, y) {
+            count(|s| &s.land_by_bathymetry);
+            Ok(true)
+        } else {
+            Ok(land)
+        }
+    }
+
+    /// Same as `contains`, but returns an error for points outside the landmask with the
+    /// `OutOfDomain::Error` policy, and after `close`.
+    pub fn try_contains(&self, x: f64, y: f64) -> io::Result<bool> {
+        self.contains_counted(x, y, None, true)
+    }
+
+    /// Same as `contains_approx`, but returns an error for points outside the landmask with the
+    /// `OutOfDomain::Error` policy, see `try_contains`.
+    pub fn try_contains_approx(&self, x: f64, y: f64) -> io::Result<bool> {
+        self.contains_counted(x, y, None, false)
+    }
+
+    /// Same as `contains`, but tells which stage decided the result. Useful to see how often a
+    /// workload needs the slow vector check.
+    ///
+    /// The poles are reported as decided by the stages which give the same result there: the
+    /// North Pole by the mask, and the South Pole by the mask and the shapes. Points outside the
+    /// landmask are handled like in `contains`, and are `Decision::OutsideLandmask` with
+    /// `OutOfDomain::ReturnFalse`.
+    pub fn contains_explain(&self, x: f64, y: f64) -> Decision {
+        self.try_contains_explain(x, y).unwrap()
+    }
+
+    /// Same as `contains_explain`, but returns an error for points outside the landmask with the
+    /// `OutOfDomain::Error` policy, see `try_contains`.
+    pub fn try_contains_explain(&self, x: f64, y: f64) -> io::Result<Decision> {
+        self.check_closed()?;
+
+        if !(y >= -90. && y <= 90.) {
+            return self.outside(x, y).map(|_| Decision::OutsideLandmask);
+        }
+
+        let (x, y) = self.sampling.center(x, y);
+
+        match pole(y) {
+            Some(true) => return Ok(Decision::LandByMaskAndShapes),
+            Some(false) => return Ok(Decision::OceanByMask),
+            None => (),
+        }
+
+        let x = modulate_longitude(x);
+
+        Ok(
+            if self.mask.contains_unchecked(x, y) && self.shapes_contains(x, y) {
+                Decision::LandByMaskAndShapes
+            } else if self.shallow(x, y) {
+                Decision::LandByBathymetry
+            } else if !self.mask.contains_unchecked(x, y) {
+                Decision::OceanByMask
+            } else {
+                Decision::OceanByShapesNearShore
+            },
+        )
+    }
+
+    /// Same as `contains`, but `Tri::Uncertain` if the point is within `margin` meters of the
+    /// coast, where the answer is within the accuracy of the data.
+    ///
+    /// The distance to the coast is found in the mask (see `RoaringMask::nearest_land` and
+    /// `RoaringMask::nearest_ocean`), and is approximate to the resolution of the mask.
+    pub fn contains_tri(&self, x: f64, y: f64, margin: f64) -> Tri {
+        if self.contains(x, y) {
+            if self.mask.nearest_ocean(x, y, margin).is_some() {
+                Tri::Uncertain
+            } else {
+                Tri::Land
+            }
+        } else if self.mask.nearest_land(x, y, margin).is_some() {
+            Tri::Uncertain
+        } else {
+            Tri::Ocean
+        }
+    }
+
+    /// Check if point (x, y) is on land using only the shapes, without the mask.
+    ///
+    /// `contains` only consults the shapes where the mask has land, and so relies on the mask
+    /// covering all the land in the shapes. This always runs the point-in-polygon test, which is
+    /// much slower in the ocean, but gives the most accurate answer the shapes can give.
+    pub fn contains_exact(&self, x: f64, y: f64) -> bool {
+        self.try_contains_exact(x, y).unwrap()
+    }
+
+    /// Same as `contains_exact`, but returns an error for points outside the landmask with the
+    /// `OutOfDomain::Error` policy, see `try_contains`.
+    pub fn try_contains_exact(&self, x: f64, y: f64) -> io::Result<bool> {
+        self.check_closed()?;
+
+        if !(y >= -90. && y <= 90.) {
+            return self.outside(x, y);
+        }
+
+        let (x, y) = self.sampling.center(x, y);
+
+        if let Some(land) = pole(y) {
+            return Ok(land);
+        }
+
+        Ok(self.shapes_contains(modulate_longitude(x), y))
+    }
+
+    /// Same as `contains`, but assumes that the longitude `x` is already in [-180, 180) and does
+    /// not move it into that range. Useful in hot loops over coordinates that are normalized
+    /// up front.
+    ///
+    /// Passing a longitude outside the range is a logic error: it is only checked in debug
+    /// builds, otherwise the result is unspecified. With `Sampling::Corner` the point is moved
+    /// half a cell east, and wrapped back if that crosses the antimeridian.
+    pub fn contains_raw(&self, x: f64, y: f64) -> bool {
+        debug_assert!((-180. ..180.).contains(&x));
+        self.check_closed().unwrap();
+
+        if !(y >= -90. && y <= 90.) {
+            return self.outside(x, y).unwrap();
+        }
+
+        let (x, y) = self.sampling.center(x, y);
+        let x = if x >= 180. { x - 360. } else { x };
+
+        if let Some(land) = pole(y) {
+            return land;
+        }
+
+        (self.mask.contains_unchecked(x, y) && self.shapes_contains(x, y)) || self.shallow(x, y)
+    }
+
+    /// Check which of the points (`x[i]`, `y[i]`) are on land, see `contains`.
+    ///
+    /// Panics if `x` and `y` have different lengths.
+    pub fn contains_slice(&self, x: &[f64], y: &[f64]) -> Vec<bool> {
+        self.try_contains_slice(x, y).unwrap()
+    }
+
+    /// Same as `contains_slice`, but checks the points in parallel.
+    pub fn contains_slice_par(&self, x: &[f64], y: &[f64]) -> Vec<bool> {
+        self.try_contains_slice_par(x, y).unwrap()
+    }
+
+    /// Check that the points (x, y) are within the landmask: finite, with latitudes within
+    /// [-90, 90]. Returns the indices of the points which are not, e.g. to drop them before a
+    /// long run with `contains_slice` instead of having them handled by `OutOfDomain`.
+    pub fn validate_coords(x: &[f64], y: &[f64]) -> Result<(), Vec<usize>> {
+        assert_eq!(x.len(), y.len());
+
+        let invalid = x
+            .iter()
+            .zip(y)
+            .enumerate()
+            .filter(|(_, (x, y))| !(x.is_finite() && (-90. ..=90.).contains(*y)))
+            .map(|(i, _)| i)
+            .collect::<Vec<_>>();
+
+        if invalid.is_empty() {
+            Ok(())
+        } else {
+            Err(invalid)
+        }
+    }
+
+    /// Same as `contains_slice`, but returns an error for points outside the landmask with the
+    /// `OutOfDomain::Error` policy, see `try_contains`.
+    pub fn try_contains_slice(&self, x: &[f64], y: &[f64]) -> io::Result<Vec<bool>> {
+        self.try_contains_slice_stats(x, y, None)
+    }
+
+    /// Same as `try_contains_slice`, but checks the points in parallel.
+    pub fn try_contains_slice_par(&self, x: &[f64], y: &[f64]) -> io::Result<Vec<bool>> {
+        self.try_contains_slice_par_stats(x, y, None)
+    }
+
+    /// Same as `try_contains_slice`, but counts the stage which decided each point in `stats`,
+    /// see `contains_with_stats`.
+    pub fn try_contains_slice_stats(
+        &self,
+        x: &[f64],
+        y: &[f64],
+        stats: Option<&QueryStats>,
+    ) -> io::Result<Vec<bool>> {
+        self.try_contains_slice_counted(x, y, stats, true)
+    }
+
+    /// `try_contains_slice_stats`, or the same with `contains_approx` if not `exact`.
+    fn try_contains_slice_counted(
+        &self,
+        x: &[f64],
+        y: &[f64],
+        stats: Option<&QueryStats>,
+        exact: bool,
+    ) -> io::Result<Vec<bool>> {
+        assert_eq!(x.len(), y.len());
+
+        x.iter()
+            .zip(y)
+            .map(|(x, y)| self.contains_counted(*x, *y, stats, exact))
+            .collect()
+    }
+
+    /// Same as `try_contains_slice_stats`, but checks the points in parallel.
+    pub fn try_contains_slice_par_stats(
+        &self,
+        x: &[f64],
+        y: &[f64],
+        stats: Option<&QueryStats>,
+    ) -> io::Result<Vec<bool>> {
+        self.try_contains_slice_par_counted(x, y, stats, true)
+    }
+
+    /// `try_contains_slice_par_stats`, or the same with `contains_approx` if not `exact`.
+    fn try_contains_slice_par_counted(
+        &self,
+        x: &[f64],
+        y: &[f64],
+        stats: Option<&QueryStats>,
+        exact: bool,
+    ) -> io::Result<Vec<bool>> {
+        use ndarray::parallel::prelude::*;
+
+        assert_eq!(x.len(), y.len());
+
+        x.par_iter()
+            .zip(y.par_iter())
+            .map(|(x, y)| self.contains_counted(*x, *y, stats, exact))
+            .collect()
+    }
+
+    /// Check which of `points` (longitude, latitude) are on land, in parallel.
+    ///
+    /// This blocks until all points are checked. In an async runtime call it from a blocking
+    /// thread, e.g. through `tokio::task::spawn_blocking` or `offload::contains_many` with the
+    /// `tokio` feature.
+    pub fn contains_many_blocking(&self, points: &[(f64, f64)]) -> Vec<bool> {
+        use ndarray::parallel::prelude::*;
+
+        points
+            .par_iter()
+            .map(|(x, y)| self.contains(*x, *y))
+            .collect()
+    }
+
+    /// Check if point (x, y) is on land with the answer of `contains` passed through `f`, which
+    /// gets the point and that answer and returns the final one, e.g. to turn points beyond a
+    /// time-varying ice edge into land. `f` is only called with points within the landmask.
+    ///
+    /// There is no Python counterpart: filter the result of `contains_many` there instead.
+    pub fn contains_with<F>(&self, x: f64, y: f64, f: F) -> bool
+    where
+        F: Fn(f64, f64, bool) -> bool,
+    {
+        let land = self.contains(x, y);
+
+        if y >= -90. && y <= 90. {
+            f(x, y, land)
+        } else {
+            land
+        }
+    }
+
+    /// Same as `contains_with` for each of `points`, in parallel, see `contains_many_blocking`.
+    pub fn contains_many_with<F>(&self, points: &[(f64, f64)], f: F) -> Vec<bool>
+    where
+        F: Fn(f64, f64, bool) -> bool + Sync,
+    {
+        use ndarray::parallel::prelude::*;
+
+        points
+            .par_iter()
+            .map(|&(x, y)| self.contains_with(x, y, &f))
+            .collect()
+    }
+
+    /// Check if point (`rlon`, `rlat`) on a rotated-pole grid with its north pole at (`pole_lon`,
+    /// `pole_lat`) is on land, e.g. a cell of a COSMO grid. The point is rotated to geographic
+    /// coordinates (see `rotated::to_geographic`) and checked with `contains`.
+    pub fn contains_rotated(&self, rlon: f64, rlat: f64, pole_lon: f64, pole_lat: f64) -> bool {
+        let (x, y) = rotated::to_geographic(rlon, rlat, pole_lon, pole_lat);
+        self.contains(x, y)
+    }
+
+    /// Same as `contains_rotated` for each of `points` (rotated longitude, latitude) on the grid
+    /// with its north pole at `pole` (longitude, latitude), in parallel.
+    pub fn contains_rotated_many_blocking(
+        &self,
+        points: &[(f64, f64)],
+        pole: (f64, f64),
+    ) -> Vec<bool> {
+        let points = points
+            .iter()
+            .map(|&(rlon, rlat)| rotated::to_geographic(rlon, rlat, pole.0, pole.1))
+            .collect::<Vec<_>>();
+
+        self.contains_many_blocking(&points)
+    }
+
+    /// Check which of the points (x, y) are on land and write the result into the existing file
+    /// at `path` through a memory map, one byte (`0` or `1`) per point starting at byte `offset`,
+    /// e.g. into a file made with `np.memmap(path, dtype=bool, mode="w+", shape=n)` or at an
+    /// offset within it. The file must already hold at least `offset + x.len()` bytes, it is
+    /// neither grown nor truncated, and the bytes around the result are left as they are.
+    ///
+    /// The points are checked in parallel in chunks of `TO_FILE_CHUNK`, each written straight into
+    /// the map and flushed before the next, so the results are never all held in memory. The
+    /// whole map is synced to disk when all points are written.
+    ///
+    /// Points outside the landmask are checked before anything is written, so with the
+    /// `OutOfDomain::Error` policy such a point leaves the file untouched. Only an I/O error can
+    /// leave it partially written.
+    pub fn contains_slice_to_mmap<P: AsRef<Path>>(
+        &self,
+        x: &[f64],
+        y: &[f64],
+        path: P,
+        offset: u64,
+    ) -> io::Result<()> {
+        use ndarray::parallel::prelude::*;
+
+        assert_eq!(x.len(), y.len());
+
+        if let Some(i) = y.iter().position(|y| !(*y >= -90. && *y <= 90.)) {
+            self.outside(x[i], y[i])?;
+        }
+
+        let fd = std::fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(path)?;
+        let size = fd.metadata()?.len();
+        if size < offset + x.len() as u64 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "the file holds {} bytes, but {} points at offset {} need {}",
+                    size,
+                    x.len(),
+                    offset,
+                    offset + x.len() as u64
+                ),
+            ));
+        }
+
+        if x.is_empty() {
+            return Ok(());
+        }
+
+        // Safety: the map is only written through here, callers must not truncate the file while
+        // it is being written.
+        let mut map = unsafe {
+            memmap2::MmapOptions::new()
+                .offset(offset)
+                .len(x.len())
+                .map_mut(&fd)?
+        };
+
+        for start in (0..x.len()).step_by(TO_FILE_CHUNK) {
+            let end = (start + TO_FILE_CHUNK).min(x.len());
+
+            map[start..end]
+                .par_iter_mut()
+                .zip(x[start..end].par_iter().zip(y[start..end].par_iter()))
+                .try_for_each(|(out, (x, y))| {
+                    *out = u8::from(self.try_contains(*x, *y)?);
+                    Ok::<_, io::Error>(())
+                })?;
+
+            map.flush_async_range(start, end - start)?;
+        }
+
+        map.flush()
+    }
+
+    /// Great-circle distance in meters from (x, y) to the nearest land, `0` if the point is on
+    /// land. See `with_earth` for the distance on the ellipsoid.
+    ///
+    /// The nearest land is looked up in the mask, so the distance is approximate to the
+    /// resolution of the mask (about 460 m).
+    pub fn distance_to_shore(&self, x: f64, y: f64) -> f64 {
+        self.contains_and_distance(x, y).1
+    }
+
+    /// Whether (x, y) is on land, and the distance to the shore as in `distance_to_shore`. The
+    /// point is only checked once, so this is faster than calling both.
+    pub fn contains_and_distance(&self, x: f64, y: f64) -> (bool, f64) {
+        if self.contains(x, y) {
+            (true, 0.)
+        } else if self.earth == Earth::Sphere && self.mask.has_distance_cache() {
+            (false, self.mask.nearest_land_distances(&[(x, y)])[0])
+        } else {
+            (false, self.offshore_distance(x, y))
+        }
+    }
+
+    /// Distance in meters on `earth` from (x, y) to the center of the nearest land cell in the
+    /// mask.
+    fn offshore_distance(&self, x: f64, y: f64) -> f64 {
+        self.mask
+            .nearest_land(x, y, f64::INFINITY)
+            .map(|(lx, ly, d)| self.on_earth(x, y, lx, ly, d))
+            .unwrap_or(f64::INFINITY)
+    }
+
+    /// The distance `d` in meters on the sphere from (x, y) to (lx, ly), measured on `earth`.
+    fn on_earth(&self, x: f64, y: f64, lx: f64, ly: f64, d: f64) -> f64 {
+        match self.earth {
+            Earth::Sphere => d,
+            earth => earth.distance(x, y, lx, ly),
+        }
+    }
+
+    /// Signed great-circle distance in meters from (x, y) to the shore: the distance to the
+    /// nearest land in the ocean, and minus the distance to the nearest ocean on land, e.g. for
+    /// level-set computations along the coast. Whether the point is on land is decided by
+    /// `contains`.
+    ///
+    /// Within `COASTLINE_DISTANCE` of the shore the distance is measured to the coastline of the
+    /// shapes on both sides, so it goes through zero at the coastline. Further away it is
+    /// measured to the nearest cell of the other kind in the mask, as in `distance_to_shore`,
+    /// which is approximate to the resolution of the mask (about 460 m). A distance of zero is
+    /// always returned as `0.`, never `-0.`.
+    pub fn signed_distance_to_shore(&self, x: f64, y: f64) -> f64 {
+        let (land, distance) = self.contains_and_distance(x, y);
+
+        if land {
+            self.signed_to_coastline(x, y, true, self.inland_distance(x, y))
+        } else {
+            self.signed_to_coastline(x, y, false, distance)
+        }
+    }
+
+    /// The distance in meters from (x, y) to the center of the nearest ocean cell in the mask.
+    fn inland_distance(&self, x: f64, y: f64) -> f64 {
+        self.mask
+            .nearest_ocean(x, y, f64::INFINITY)
+            .map(|(ox, oy, d)| self.on_earth(x, y, ox, oy, d))
+            .unwrap_or(f64::INFINITY)
+    }
+
+    /// The signed distance from (x, y) on `land` or not, given the distance `d` in meters to the
+    /// nearest cell of the other kind in the mask, see `signed_distance_to_shore`.
+    fn signed_to_coastline(&self, x: f64, y: f64, land: bool, d: f64) -> f64 {
+        let d = if d <= COASTLINE_DISTANCE {
+            self.coastline_distance(x, y, d).unwrap_or(d)
+        } else {
+            d
+        };
+
+        if land && d > 0. {
+            -d
+        } else {
+            d
+        }
+    }
+
+    /// The distance in meters from (x, y) to the nearest coastline of the shapes, searching up to
+    /// about `d` meters away, see `Gshhg::nearest_edge`.
+    fn coastline_distance(&self, x: f64, y: f64, d: f64) -> Option<f64> {
+        // On land the coast is closer than the nearest ocean cell, in the ocean it is within half
+        // a cell diagonal of the nearest land cell.
+        let cell = self.mask.dy() * 2f64.sqrt();
+        let radius = (d / distance::EARTH_RADIUS).to_degrees() * 1.1 + cell;
+        let (((ax, ay), (bx, by)), _) = self.shapes.nearest_edge(x, y, radius)?;
+
+        // The closest point on the edge, on the same tangent plane as in `nearest_edge`.
+        let x = modulate_longitude(x);
+        let k = y.to_radians().cos().max(1e-6);
+        let project = |px: f64, py: f64| (((px - x + 180.).rem_euclid(360.) - 180.) * k, py - y);
+        let ((ax, ay), (bx, by)) = (project(ax, ay), project(bx, by));
+
+        let (ex, ey) = (bx - ax, by - ay);
+        let len = ex * ex + ey * ey;
+        let t = if len > 0. {
+            (-(ax * ex + ay * ey) / len).clamp(0., 1.)
+        } else {
+            0.
+        };
+        let (px, py) = (x + (ax + t * ex) / k, y + ay + t * ey);
+
+        Some(self.on_earth(x, y, px, py, distance::haversine(x, y, px, py)))
+    }
+
+    /// Same as `signed_distance_to_shore` for each of `points` (longitude, latitude), blocking
+    /// the current thread. The distances to the mask in the ocean are found as in
+    /// `distance_to_shore_many_blocking`, those on land and those to the coastline point by
+    /// point in parallel.
+    pub fn signed_distance_to_shore_many_blocking(&self, points: &[(f64, f64)]) -> Vec<f64> {
+        use ndarray::parallel::prelude::*;
+
+        let (land, mut distances) = self.contains_and_distance_many_blocking(points);
+
+        distances
+            .par_iter_mut()
+            .zip(points)
+            .zip(land)
+            .for_each(|((d, &(x, y)), land)| {
+                let mask = if land { self.inland_distance(x, y) } else { *d };
+                *d = self.signed_to_coastline(x, y, land, mask);
+            });
+
+        distances
+    }
+
+    /// Same as `distance_to_shore` for each of `points` (longitude, latitude), blocking the
+    /// current thread. Much faster than checking the points one by one when many points are close
+    /// to each other, see `RoaringMask::nearest_land_distances`.
+    pub fn distance_to_shore_many_blocking(&self, points: &[(f64, f64)]) -> Vec<f64> {
+        self.contains_and_distance_many_blocking(points).1
+    }
+
+    /// Whether each of `points` (longitude, latitude) is within its own distance `radii_km` of
+    /// land, e.g. a safety margin which depends on the asset. Points on land are within any
+    /// distance, and a NaN radius is never satisfied. See `distance_to_shore_many_blocking`.
+    pub fn within_distance_many_blocking(
+        &self,
+        points: &[(f64, f64)],
+        radii_km: &[f64],
+    ) -> Vec<bool> {
+        assert_eq!(points.len(), radii_km.len());
+
+        self.distance_to_shore_many_blocking(points)
+            .into_iter()
+            .zip(radii_km)
+            .map(|(d, r)| d <= r * 1000.)
+            .collect()
+    }
+
+    /// Whether a disk of `radius_km` kilometers around (x, y) touches land, e.g. the footprint of
+    /// a sensor: the point is on land, or within `radius_km` of it as in `distance_to_shore`.
+    /// Only the disk is searched, so this is faster than `distance_to_shore` far from land.
+    pub fn circle_intersects_land(&self, x: f64, y: f64, radius_km: f64) -> bool {
+        if !(y >= -90. && y <= 90.) {
+            return self.outside(x, y).unwrap();
+        }
+
+        let r = radius_km * 1000.;
+        match self.earth {
+            Earth::Sphere => self.contains(x, y) || self.mask.nearest_land(x, y, r).is_some(),
+            _ => self.distance_to_shore(x, y) <= r,
+        }
+    }
+
+    /// Same as `circle_intersects_land` for each of `points` with the same radius, see
+    /// `within_distance_many_blocking`.
+    pub fn circle_intersects_land_many_blocking(
+        &self,
+        points: &[(f64, f64)],
+        radius_km: f64,
+    ) -> Vec<bool> {
+        self.within_distance_many_blocking(points, &vec![radius_km; points.len()])
+    }
+
+    /// Same as `contains_and_distance` for each of `points`, see
+    /// `distance_to_shore_many_blocking`. Returns whether each point is on land and its distance
+    /// to the shore.
+    pub fn contains_and_distance_many_blocking(
+        &self,
+        points: &[(f64, f64)],
+    ) -> (Vec<bool>, Vec<f64>) {
+        use ndarray::parallel::prelude::*;
+
+        let land = self.contains_many_blocking(points);
+        let ocean = points
+            .iter()
+            .zip(&land)
+            .filter(|(_, land)| !**land)
+            .map(|(p, _)| *p)
+            .collect::<Vec<_>>();
+
+        let distances = match self.earth {
+            Earth::Sphere => self.mask.nearest_land_distances(&ocean),
+            _ => ocean
+                .par_iter()
+                .map(|&(x, y)| self.offshore_distance(x, y))
+                .collect(),
+        };
+        let mut distances = distances.into_iter();
+
+        let distances = land
+            .iter()
+            .map(|land| if *land { 0. } else { distances.next().unwrap() })
+            .collect();
+
+        (land, distances)
+    }
+
+    /// The point itself if (x, y) is on land, otherwise the center of the nearest land cell in the
+    /// mask within `max_dist` meters, see `RoaringMask::nearest_land`. Returns `None` if there is
+    /// no land within `max_dist`.
+    pub fn nearest_land(&self, x: f64, y: f64, max_dist: f64) -> Option<(f64, f64)> {
+        if self.contains(x, y) {
+            Some((x, y))
+        } else {
+            self.mask
+                .nearest_land(x, y, max_dist)
+                .map(|(lon, lat, _)| (lon, lat))
+        }
+    }
+
+    /// The endpoints of the edge of the coastline in the shapes closest to (x, y), e.g. for the
+    /// local orientation of the coast. Returns `None` if there is no land.
+    ///
+    /// The search starts at the distance to the nearest land in the mask, as for
+    /// `distance_to_shore`, and is widened until an edge is found, see `Gshhg::nearest_edge`.
+    pub fn nearest_coast_segment(&self, x: f64, y: f64) -> Option<shapes::Segment> {
+        let (_, _, d) = self.mask.nearest_land(x, y, f64::INFINITY)?;
+
+        // The coast is within half a cell diagonal of the nearest land cell.
+        let cell = self.mask.dy() * 2f64.sqrt();
+        let mut radius = (d / distance::EARTH_RADIUS).to_degrees() * 1.1 + cell;
+
+        while radius < 360. {
+            if let Some((segment, _)) = self.shapes.nearest_edge(x, y, radius) {
+                return Some(segment);
+            }
+            radius *= 2.;
+        }
+
+        None
+    }
+
+    /// Same as `distance_to_shore`, but in `unit`.
+    pub fn distance_to_shore_in(&self, x: f64, y: f64, unit: Unit) -> f64 {
+        self.distance_to_shore(x, y) / unit.meters()
+    }
+
+    /// Lines at the signed distances `levels_km` from the coast within `bbox` (west, south, east,
+    /// north), e.g. for coastal zones 1, 5 and 10 km offshore. Positive levels are in the ocean
+    /// and negative levels on land. Returns a `MultiLineString` for each level, in order.
+    ///
+    /// The lines are traced with marching squares through the signed distance to the coast of
+    /// the cells of the mask (see `RoaringMask::signed_distance_window`), so they are accurate to
+    /// about a cell and do not use the shapes. `bbox` may cross the antimeridian (east < west), the
+    /// longitudes of the lines then continue past 180. The field is computed at full resolution,
+    /// so the bbox should be regional.
+    pub fn distance_contours(
+        &self,
+        levels_km: &[f64],
+        bbox: shapes::BBox,
+    ) -> io::Result<Vec<geos::Geometry>> {
+        use geos::{CoordSeq, Geometry};
+        use mask::{NX, NY, TRANSFORM};
+
+        let (west, south, mut east, north) = bbox;
+        if east < west {
+            east += 360.;
+        }
+
+        if !(west.is_finite() && east.is_finite() && west < east)
+            || !(-90. <= south && south < north && north <= 90.)
+            || !levels_km.iter().all(|l| l.is_finite())
+        {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "invalid bbox or levels",
+            ));
+        }
+
+        let margin = levels_km.iter().fold(0., |m: f64, l| m.max(l.abs())) * 1000.;
+        let (dx, dy) = (self.mask.dx(), self.mask.dy());
+
+        // The rows and columns of the bbox, and of the window around it which holds the coast
+        // within `margin`.
+        let (c0, r0) = TRANSFORM.apply(west, south);
+        let (c1, r1) = TRANSFORM.apply(east, north);
+        let (c0, c1) = (c0.floor() as i64, c1.floor() as i64);
+        let (r0, r1) = (r0.floor() as i64, r1.floor() as i64);
+        let (r0, r1) = (r0.min(NY as i64 - 1), r1.min(NY as i64 - 1));
+
+        let ns = self.mask.cell_size_m(0.).1;
+        let mr = (margin / ns).ceil() as i64 + 1;
+        let (wr0, wr1) = ((r0 - mr).max(0), (r1 + mr).min(NY as i64 - 1));
+
+        let lat = |r: i64| r as f64 * dy - 90.;
+        let ew = self.mask.cell_size_m(lat(wr0).abs().max(lat(wr1).abs())).0;
+        let mc = (margin / ew).ceil().min(NX as f64) as i64 + 1;
+        let (wc0, nx) = if c1 - c0 + 1 + 2 * mc >= NX as i64 {
+            (c0, NX as i64)
+        } else {
+            (c0 - mc, c1 - c0 + 1 + 2 * mc)
+        };
+
+        let field = self.mask.signed_distance_window(
+            wc0.rem_euclid(NX as i64) as u64,
+            wr0 as u64,
+            nx as usize,
+            (wr1 - wr0 + 1) as usize,
+        );
+
+        let (i0, i1) = (
+            (c0 - wc0) as usize,
+            ((c1 - wc0) as usize).min(nx as usize - 1),
+        );
+        let (j0, j1) = ((r0 - wr0) as usize, (r1 - wr0) as usize);
+        let field = field.slice(ndarray::s![j0..=j1, i0..=i1]);
+
+        let xs: Vec<f64> = (c0..=c0 + (i1 - i0) as i64)
+            .map(|c| c as f64 * dx - 180.)
+            .collect();
+        let ys: Vec<f64> = (r0..=r1).map(lat).collect();
+
+        let err = |_| io::Error::new(io::ErrorKind::InvalidData, "cannot create contours");
+
+        levels_km
+            .iter()
+            .map(|level| {
+                let lines = contours::marching_squares(field, &xs, &ys, level * 1000.)
+                    .into_iter()
+                    .map(|line| {
+                        let coords = line.iter().map(|(x, y)| [*x, *y]).collect::<Vec<_>>();
+                        Geometry::create_line_string(CoordSeq::new_from_vec(&coords)?)
+                    })
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(err)?;
+
+                Geometry::create_multiline_string(lines).map_err(err)
+            })
+            .collect()
+    }
+
+    /// This landmask with the mask and shapes within `bbox` (west, south, east, north) replaced
+    /// by those of `regional`, e.g. a landmask made from local high-resolution coastlines with
+    /// `from_parts`. Queries within `bbox` are answered from the regional data and the rest from
+    /// this landmask, whose settings (`with_out_of_domain`, `with_sampling`,
+    /// `with_enclosed_water`, the shallow water of `with_bathymetry` and the zones of
+    /// `load_zones`) are kept. Fails with
+    /// `InvalidInput` if `bbox` is empty or crosses the antimeridian; split it in two and
+    /// overlay twice instead.
+    ///
+    /// The masks of all landmasks share the same grid, so regional data on another grid must
+    /// first be resampled onto it, e.g. with `RoaringMask::from_array`. The shapes are cut
+    /// exactly at the edges of `bbox`, while the cells of the mask along the edges have land
+    /// from both, see `RoaringMask::overlay`: a point next to the seam is therefore decided by
+    /// the shapes of the side it is on. Coastlines that do not meet at the seam are not joined.
+    pub fn overlay(
+        &self,
+        regional: &RoaringLandmask,
+        bbox: shapes::BBox,
+    ) -> io::Result<RoaringLandmask> {
+        let (west, south, east, north) = bbox;
+        if !(west < east && south < north && west >= -180. && east <= 180.) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("invalid bbox for overlay: {:?}", bbox),
+            ));
+        }
+
+        let mask = self.mask.overlay(&regional.mask, bbox);
+        let shapes = self.shapes.overlay(&regional.shapes, bbox)?;
+
+        let mut landmask = RoaringLandmask::from_parts(mask, shapes)
+            .with_out_of_domain(self.out_of_domain)
+            .with_sampling(self.sampling)
+            .with_enclosed_water(self.enclosed_water);
+        landmask.shallow = self.shallow.clone();
+        landmask.zones = self.zones.clone();
+
+        Ok(landmask)
+    }
+
+    /// Centers (longitude, latitude) of the cells within `bbox` (west, south, east, north) which
+    /// this landmask and `other` classify differently, e.g. to compare shorelines from different
+    /// vintages. `bbox` may cross the antimeridian (east < west).
+    ///
+    /// Every landmask has the mask on the same grid, so the cells line up. The cells with land in
+    /// either mask (or shallow water, see `with_bathymetry`) are found with set operations on the
+    /// bitmaps, and only those are checked against both landmasks at their center. Land from
+    /// data on another grid must first be resampled onto the grid of the mask, e.g. with
+    /// `RoaringMask::from_array`, and changes smaller than a cell (about 460 m) may be missed.
+    /// Every land cell within `bbox` is checked, so the bbox should be regional.
+    pub fn diff(&self, other: &RoaringLandmask, bbox: shapes::BBox) -> Vec<(f64, f64)> {
+        use mask::{NX, NY, TRANSFORM};
+        use ndarray::parallel::prelude::*;
+
+        let (west, south, mut east, north) = bbox;
+        if east < west {
+            east += 360.;
+        }
+
+        let (c0, r0) = TRANSFORM.apply(west, south.max(-90.));
+        let (c1, r1) = TRANSFORM.apply(east, north.min(90.));
+        let (c0, c1) = (
+            c0.floor() as u64,
+            (c1.floor() as u64).min(c0 as u64 + NX - 1),
+        );
+        let (r0, r1) = (r0.floor() as u64, (r1.floor() as u64).min(NY - 1));
+
+        let window = mask::window(c0..c1 + 1, r0..r1 + 1);
+
+        let land = |landmask: &RoaringLandmask| {
+            let mut land = &*landmask.mask.tmap & &window;
+            if let Some(shallow) = &landmask.shallow {
+                land |= &*shallow.tmap & &window;
+            }
+            land
+        };
+
+        let candidates = (land(self) | land(other)).into_iter().collect::<Vec<_>>();
+        let (dx, dy) = (self.mask.dx(), self.mask.dy());
+
+        candidates
+            .into_par_iter()
+            .map(|i| ((i % NX) as f64 * dx - 180., (i / NX) as f64 * dy - 90.))
+            .filter(|&(x, y)| self.contains(x, y) != other.contains(x, y))
+            .collect()
+    }
+
+    /// Find where the great-circle path from `from` to `to` first reaches land, e.g. where a
+    /// drifting particle beaches. Points are (longitude, latitude).
+    ///
+    /// The path is sampled every `path::STEP` meters, and the transition from ocean to land is
+    /// refined by bisection to within a meter. Returns `from` if it is already on land, and
+    /// `None` if the whole path is in the ocean.
+    pub fn crossed_onto_land(&self, from: (f64, f64), to: (f64, f64)) -> Option<(f64, f64)> {
+        if self.contains(from.0, from.1) {
+            return Some(from);
+        }
+
+        let n = path::segments_on(from, to, path::STEP, self.earth);
+        let length = self.earth.distance(from.0, from.1, to.0, to.1);
+
+        let mut prev = 0.;
+        for i in 1..=n {
+            let f = i as f64 / n as f64;
+            let (x, y) = path::intermediate(from, to, f);
+
+            if self.contains(x, y) {
+                // Refine between the last point in the ocean and the first point on land.
+                let (mut lo, mut hi) = (prev, f);
+                while (hi - lo) * length > 1. {
+                    let mid = (lo + hi) / 2.;
+                    let (x, y) = path::intermediate(from, to, mid);
+
+                    if self.contains(x, y) {
+                        hi = mid;
+                    } else {
+                        lo = mid;
+                    }
+                }
+
+                return Some(path::intermediate(from, to, hi));
+            }
+
+            prev = f;
+        }
+
+        None
+    }
+
+    /// Find where a ray leaving `origin` (longitude, latitude) with initial `bearing_deg`
+    /// (clockwise from north) along a great circle first reaches land, e.g. for line of sight or
+    /// fetch. Returns `origin` if it is on land, and `None` if there is no land within `max_km`
+    /// kilometers.
+    ///
+    /// The ray is sampled every `path::STEP` meters and the landfall refined by bisection to
+    /// within a meter, like `crossed_onto_land`.
+    pub fn first_landfall(
+        &self,
+        origin: (f64, f64),
+        bearing_deg: f64,
+        max_km: f64,
+    ) -> Option<(f64, f64)> {
+        if self.contains(origin.0, origin.1) {
+            return Some(origin);
+        }
+
+        let length = max_km * 1000.;
+        if !(length > 0.) {
+            return None;
+        }
+
+        let at = |d: f64| path::destination(origin, bearing_deg, d);
+        let n = ((length / path::STEP).ceil() as usize).max(1);
+
+        let mut prev = 0.;
+        for i in 1..=n {
+            let d = length * i as f64 / n as f64;
+            let (x, y) = at(d);
+
+            if self.contains(x, y) {
+                // Refine between the last point in the ocean and the first point on land.
+                let (mut lo, mut hi) = (prev, d);
+                while hi - lo > 1. {
+                    let mid = (lo + hi) / 2.;
+                    let (x, y) = at(mid);
+
+                    if self.contains(x, y) {
+                        hi = mid;
+                    } else {
+                        lo = mid;
+                    }
+                }
+
+                return Some(at(hi));
+            }
+
+            prev = d;
+        }
+
+        None
+    }
+
+    /// Number of times the path through `coords` (longitude, latitude) crosses the coastline, i.e.
+    /// changes between ocean and land.
+    ///
+    /// Each leg of the path follows the great circle, and is sampled every `path::STEP` meters,
+    /// so crossings closer together than that may be missed.
+    pub fn coastline_crossings(&self, coords: &[(f64, f64)]) -> usize {
+        let mut land = match coords.first() {
+            Some((x, y)) => self.contains(*x, *y),
+            None => return 0,
+        };
+
+        let mut crossings = 0;
+        for leg in coords.windows(2) {
+            for (x, y) in path::densify(leg[0], leg[1], path::STEP).skip(1) {
+                let l = self.contains(x, y);
+                if l != land {
+                    crossings += 1;
+                    land = l;
+                }
+            }
+        }
+
+        crossings
+    }
+
+    /// The path through `coords` (longitude, latitude) as runs of land and ocean, `(on_land,
+    /// start, end)` with `start` and `end` the first and last index (inclusive) of the run in the
+    /// points of `path::densify_track(coords, step_m)`. Much more compact than a result per
+    /// point for long tracks, e.g. for drawing them.
+    ///
+    /// The legs follow the great circle, also across the antimeridian and near the poles, and
+    /// are sampled at most `step_m` meters apart, so land or water narrower than that may be
+    /// missed. Panics unless `step_m` is positive.
+    pub fn classify_track(&self, coords: &[(f64, f64)], step_m: f64) -> Vec<(bool, usize, usize)> {
+        assert!(step_m > 0.);
+
+        let mut runs: Vec<(bool, usize, usize)> = Vec::new();
+        for (i, (x, y)) in path::densify_track(coords, step_m).into_iter().enumerate() {
+            let land = self.contains(x, y);
+
+            match runs.last_mut() {
+                Some(run) if run.0 == land => run.2 = i,
+                _ => runs.push((land, i, i)),
+            }
+        }
+
+        runs
+    }
+
+    /// Same as `classify_track` for each of `tracks`, in parallel.
+    pub fn classify_tracks(
+        &self,
+        tracks: &[Vec<(f64, f64)>],
+        step_m: f64,
+    ) -> Vec<Vec<(bool, usize, usize)>> {
+        use ndarray::parallel::prelude::*;
+
+        tracks
+            .par_iter()
+            .map(|track| self.classify_track(track, step_m))
+            .collect()
+    }
+
+    /// Fraction of the length of the path through `coords` (longitude, latitude) which is over
+    /// land, e.g. the share of a track an animal or vessel spent on land.
+    ///
+    /// Each leg follows the great circle, also across the antimeridian, and is split into pieces
+    /// of at most `path::STEP` meters which are classified by their midpoint and weighted by their
+    /// length. Returns NaN if the path has no length.
+    pub fn land_time_fraction(&self, coords: &[(f64, f64)]) -> f64 {
+        let mut land = 0.;
+        let mut total = 0.;
+
+        for leg in coords.windows(2) {
+            let (from, to) = (leg[0], leg[1]);
+            let n = path::segments_on(from, to, path::STEP, self.earth);
+            let piece = self.earth.distance(from.0, from.1, to.0, to.1) / n as f64;
+
+            for i in 0..n {
+                let (x, y) = path::intermediate(from, to, (i as f64 + 0.5) / n as f64);
+                if self.contains(x, y) {
+                    land += piece;
+                }
+            }
+
+            total += piece * n as f64;
+        }
+
+        land / total
+    }
+
+    /// Fraction of the samples in `bbox` (west, south, east, north) which are on land. `bbox` is
+    /// divided into `samples` × `samples` cells of equal size in degrees, and each is sampled at
+    /// its center. The samples are not weighted by their area. `bbox` may cross the antimeridian
+    /// (east < west). Returns NaN if `samples` is zero.
+    ///
+    /// The number of samples trades accuracy for speed: land or water narrower than the spacing
+    /// of the samples, such as a thin isthmus, a strait or a small island, can fall between them
+    /// and be missed entirely. The default in Python is `COVERAGE_SAMPLES`.
+    pub fn land_fraction(&self, bbox: shapes::BBox, samples: usize) -> f64 {
+        let points = grid_samples(bbox, samples);
+        let land = points.iter().filter(|&&(x, y)| self.contains(x, y)).count();
+
+        land as f64 / points.len() as f64
+    }
+
+    /// Whether there is no land anywhere in `bbox` (west, south, east, north), e.g. to skip tiles
+    /// or chunks without land. `bbox` may cross the antimeridian (east < west).
+    ///
+    /// Only the mask is checked, see `RoaringMask::bbox_is_all_ocean`. It covers all land, so
+    /// `true` means that `contains` is `false` everywhere in the box, while `false` only means
+    /// that there is land within a cell (~460 m) of it.
+    ///
+    /// Panics if south > north.
+    pub fn bbox_is_all_ocean(&self, bbox: shapes::BBox) -> bool {
+        let (west, south, east, north) = bbox;
+        let (west, south) = self.sampling.center(west, south);
+        let (east, north) = self.sampling.center(east, north);
+        let bbox = (west, south, east, north);
+
+        self.mask.bbox_is_all_ocean(bbox)
+            && self
+                .shallow
+                .as_ref()
+                .map_or(true, |shallow| shallow.bbox_is_all_ocean(bbox))
+    }
+
+    /// A continuous land value in [0, 1] at (x, y), e.g. to weight fluxes smoothly across the
+    /// coast. The land fractions of the four cells of the mask whose centers surround the point
+    /// are found with `land_fraction` from `WEIGHT_SAMPLES` × `WEIGHT_SAMPLES` samples, and
+    /// interpolated bilinearly between the centers.
+    ///
+    /// The cells wrap around the antimeridian. The southernmost row of cells is centered on the
+    /// South Pole and only covers half a cell. North of the centers of the northernmost row,
+    /// within half a cell of the North Pole, the value is interpolated along longitude only.
+    /// Returns NaN if `y` is not within [-90, 90].
+    pub fn land_weight(&self, x: f64, y: f64) -> f64 {
+        if !(y >= -90. && y <= 90.) {
+            return f64::NAN;
+        }
+
+        let dx = 360. / mask::NX as f64;
+        let dy = 180. / mask::NY as f64;
+
+        // The centers of the cells are at multiples of the cell size from (-180, -90).
+        let fx = (modulate_longitude(x) + 180.) / dx;
+        let fy = ((y + 90.) / dy).min((mask::NY - 1) as f64);
+        let (i, j) = (fx.floor(), fy.floor());
+        let (tx, ty) = (fx - i, fy - j);
+
+        let fraction = |i: f64, j: f64| {
+            let (cx, cy) = (i * dx - 180., j * dy - 90.);
+            let bbox = (
+                cx - dx / 2.,
+                (cy - dy / 2.).max(-90.),
+                cx + dx / 2.,
+                (cy + dy / 2.).min(90.),
+            );
+            self.land_fraction(bbox, WEIGHT_SAMPLES)
+        };
+
+        let row = |j: f64| {
+            let (w, e) = (fraction(i, j), fraction(i + 1., j));
+            w + (e - w) * tx
+        };
+
+        let south = row(j);
+        if ty == 0. {
+            south
+        } else {
+            south + (row(j + 1.) - south) * ty
+        }
+    }
+
+    /// Same as `land_weight` for each of `points` (longitude, latitude), in parallel.
+    pub fn land_weight_many_blocking(&self, points: &[(f64, f64)]) -> Vec<f64> {
+        use ndarray::parallel::prelude::*;
+
+        points
+            .par_iter()
+            .map(|(x, y)| self.land_weight(*x, *y))
+            .collect()
+    }
+
+    /// Whether the polygon with exterior `ring` (longitude, latitude) is all land, all ocean or
+    /// mixed. The vertices are checked, and the points of a regular grid of `samples` × `samples`
+    /// over the bounding box of the ring which are inside it (see `land_fraction`).
+    ///
+    /// As with `land_fraction`, too few samples can miss features smaller than their spacing, so
+    /// that a polygon across a thin isthmus is classified as all ocean. The default in Python is
+    /// `COVERAGE_SAMPLES`.
+    pub fn classify_polygon(&self, ring: &[(f64, f64)], samples: usize) -> Coverage {
+        let bounds = ring.iter().fold(
+            (
+                f64::INFINITY,
+                f64::INFINITY,
+                f64::NEG_INFINITY,
+                f64::NEG_INFINITY,
+            ),
+            |(x0, y0, x1, y1), &(x, y)| (x0.min(x), y0.min(y), x1.max(x), y1.max(y)),
+        );
+
+        let inside = grid_samples(bounds, samples)
+            .into_iter()
+            .filter(|&(x, y)| shapes::ring_contains(ring, x, y));
+
+        let (mut land, mut ocean) = (false, false);
+        for (x, y) in ring.iter().copied().chain(inside) {
+            if self.contains(x, y) {
+                land = true;
+            } else {
+                ocean = true;
+            }
+
+            if land && ocean {
+                return Coverage::Mixed;
+            }
+        }
+
+        if land {
+            Coverage::Land
+        } else {
+            Coverage::Ocean
+        }
+    }
+
+    /// Where the mask has land: the extent of the tiles with land, the southernmost and
+    /// northernmost land and whether the poles are covered, see `RoaringMask::coverage_summary`.
+    /// For checking a custom or regional dataset before a large job. Only the mask is looked at,
+    /// not the shapes or shallow water.
+    pub fn coverage_summary(&self) -> Summary {
+        self.mask.coverage_summary()
+    }
+
+    /// Whether `a` and `b` (longitude, latitude) are on the same landmass, so that one can walk
+    /// between them without crossing the ocean. Returns `false` if either point is in the ocean.
+    ///
+    /// The points are looked up in the polygons of the shapes with `Gshhg::containing_polygon`,
+    /// and land split at the antimeridian counts as one landmass, see `Gshhg::landmass`.
+    pub fn same_landmass(&self, a: (f64, f64), b: (f64, f64)) -> bool {
+        let landmass = |(x, y): (f64, f64)| {
+            self.shapes
+                .containing_polygon(x, y)
+                .and_then(|id| self.shapes.landmass(id))
+        };
+
+        match (landmass(a), landmass(b)) {
+            (Some(a), Some(b)) => a == b,
+            _ => false,
+        }
+    }
+
+    /// Check the landmask against the reference points in `reference::POINTS`, which are far
+    /// from any coast. Returns the points that do not match as (longitude, latitude, expected,
+    /// actual).
+    ///
+    /// Useful to validate a landmask built from other data, or loaded from disk.
+    pub fn self_test(&self) -> Result<(), Vec<(f64, f64, bool, bool)>> {
+        let mismatches = reference::POINTS
+            .iter()
+            .map(|&(x, y, land)| (x, y, land, self.contains(x, y)))
+            .filter(|(_, _, expected, actual)| expected != actual)
+            .collect::<Vec<_>>();
+
+        if mismatches.is_empty() {
+            Ok(())
+        } else {
+            Err(mismatches)
+        }
+    }
+
+    /// Centers of cells which the mask has as land more than one cell from its shore, but which
+    /// the shapes have as ocean. Only every `step` cell along each axis is checked.
+    ///
+    /// The mask is only expected to be optimistic in a narrow band along the shore, so any cell
+    /// returned here points to an error in how the mask was built.
+    pub fn audit(&self, step: u64) -> Vec<(f64, f64)> {
+        use mask::{NX, NY};
+        use ndarray::parallel::prelude::*;
+
+        assert!(step > 0);
+
+        let rows = (0..NY).step_by(step as usize).collect::<Vec<_>>();
+
+        rows.into_par_iter()
+            .flat_map_iter(|y| {
+                (0..NX).step_by(step as usize).filter_map(move |x| {
+                    if !self.mask.is_interior(x, y) {
+                        return None;
+                    }
+
+                    let lon = x as f64 * 360. / NX as f64 - 180.;
+                    let lat = y as f64 * 180. / NY as f64 - 90.;
+
+                    if self.shapes.contains_unchecked(lon, lat) {
+                        None
+                    } else {
+                        Some((lon, lat))
+                    }
+                })
+            })
+            .collect()
+    }
+
+    /// Write the mask and shapes to a single file at `path`, see `bundle`.
+    pub fn save_bundle<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        bundle::save(self, path)
+    }
+
+    /// Load a landmask written by `save_bundle`. Fails with `InvalidData` if the file is corrupt,
+    /// was written for another version or grid, or its shapes do not match the SHA-256 in its
+    /// header. For another version or shapes the error holds a `LandmaskError`.
+    pub fn load_bundle<P: AsRef<Path>>(path: P) -> io::Result<RoaringLandmask> {
+        bundle::load(path)
+    }
+
+    /// Whether the shapes have land at (x, y) according to `enclosed_water`. Does not check for
+    /// bounds, see `Gshhg::contains_unchecked`.
+    pub(crate) fn shapes_contains(&self, x: f64, y: f64) -> bool {
+        match self.enclosed_water {
+            EnclosedWater::AsSeparate => self.shapes.contains_unchecked(x, y),
+            EnclosedWater::AsLand => self.shapes.level_unchecked(x, y) >= 1,
+            EnclosedWater::AsOcean => self.shapes.level_unchecked(x, y) == 1,
+        }
+    }
+
+    /// Check which of the points (x, y) are on land, see `contains`.
+    pub fn contains_many(
+        &self,
+        py: Python,
+        x: PyReadonlyArrayDyn<f64>,
+        y: PyReadonlyArrayDyn<f64>,
+    ) -> PyResult<Py<PyArray<bool, numpy::Ix1>>> {
+        self.contains_many_counted(py, x, y, false, None, true)
+    }
+
+    /// Same as `contains_many`, but only checks the mask, see `contains_approx`.
+    pub fn contains_many_approx(
+        &self,
+        py: Python,
+        x: PyReadonlyArrayDyn<f64>,
+        y: PyReadonlyArrayDyn<f64>,
+    ) -> PyResult<Py<PyArray<bool, numpy::Ix1>>> {
+        self.contains_many_counted(py, x, y, false, None, false)
+    }
+
+    /// Same as `contains_many`, but checks the points in parallel and keeps the shape of `x` and
+    /// `y`.
+    pub fn contains_many_par(
+        &self,
+        py: Python,
+        x: PyReadonlyArrayDyn<f64>,
+        y: PyReadonlyArrayDyn<f64>,
+    ) -> PyResult<Py<PyArray<bool, numpy::IxDyn>>> {
+        self.contains_many_par_counted(py, x, y, false, None, true)
+    }
+
+    /// Same as `contains_many_par`, but only checks the mask, see `contains_approx`.
+    pub fn contains_many_par_approx(
+        &self,
+        py: Python,
+        x: PyReadonlyArrayDyn<f64>,
+        y: PyReadonlyArrayDyn<f64>,
+    ) -> PyResult<Py<PyArray<bool, numpy::IxDyn>>> {
+        self.contains_many_par_counted(py, x, y, false, None, false)
+    }
+
+    /// `contains_many`, or `contains_many_approx` if not `exact`. With `dedupe` each unique point
+    /// is only checked once, see `deduplicated`. If `stats` is given, the stage which decided each
+    /// (unique) point is counted in it, see `QueryStats`.
+    fn contains_many_counted(
+        &self,
+        py: Python,
+        x: PyReadonlyArrayDyn<f64>,
+        y: PyReadonlyArrayDyn<f64>,
+        dedupe: bool,
+        stats: Option<&QueryStats>,
+        exact: bool,
+    ) -> PyResult<Py<PyArray<bool, numpy::Ix1>>> {
+        self.check_open()?;
+
+        let x = x.as_array();
+        let y = y.as_array();
+        check_shape(x.shape(), y.shape())?;
+
+        let (x, y) = (standard_slice(&x), standard_slice(&y));
+        let contains = if dedupe {
+            deduplicated(&x, &y, |x, y| {
+                self.try_contains_slice_counted(x, y, stats, exact)
+            })
+        } else {
+            self.try_contains_slice_counted(&x, &y, stats, exact)
+        }
+        .map_err(|e| PyValueError::new_err(e.to_string()))?;
+
+        Ok(PyArray::from_vec(py, contains).to_owned())
+    }
+
+    /// `contains_many_par`, or `contains_many_par_approx` if not `exact`, see
+    /// `contains_many_counted`.
+    fn contains_many_par_counted(
+        &self,
+        py: Python,
+        x: PyReadonlyArrayDyn<f64>,
+        y: PyReadonlyArrayDyn<f64>,
+        dedupe: bool,
+        stats: Option<&QueryStats>,
+        exact: bool,
+    ) -> PyResult<Py<PyArray<bool, numpy::IxDyn>>> {
+        self.check_open()?;
+
+        let x = x.as_array();
+        let y = y.as_array();
+        check_shape(x.shape(), y.shape())?;
+
+        let (xs, ys) = (standard_slice(&x), standard_slice(&y));
+        let contains = if dedupe {
+            deduplicated(&xs, &ys, |x, y| {
+                self.try_contains_slice_par_counted(x, y, stats, exact)
+            })
+        } else {
+            self.try_contains_slice_par_counted(&xs, &ys, stats, exact)
+        }
+        .map_err(|e| PyValueError::new_err(e.to_string()))?;
+
+        let contains = ndarray::Array::from_shape_vec(x.raw_dim(), contains).unwrap();
+        Ok(PyArray::from_owned_array(py, contains).to_owned())
+    }
+
+    fn check_open(&self) -> PyResult<()> {
+        self.check_closed()
+            .map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+
+    /// An error if the landmask has been closed, see `close`.
+    fn check_closed(&self) -> io::Result<()> {
+        if self.closed {
+            Err(io::Error::new(
+                io::ErrorKind::Other,
+                "RoaringLandmask is closed",
+            ))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+#[pymethods]
+impl RoaringLandmask {
+    /// Make a new landmask from the embedded mask and shapes. The data is decoded on the first
+    /// call (or by `preload_shared`) and shared by all landmasks made this way, so that further
+    /// landmasks take hardly any memory. It is freed when the last of them is closed or dropped,
+    /// and decoded again by the next call.
+    #[staticmethod]
+    pub fn new(_py: Python) -> io::Result<RoaringLandmask> {
+        RoaringLandmask::embedded()
+    }
+
+    /// Make a new landmask from data downloaded at runtime and cached, see `download`. Only the
+    /// full resolution (`f`) is available.
+    #[cfg(feature = "download")]
+    #[staticmethod]
+    #[pyo3(signature = (resolution = "f"))]
+    pub fn from_downloaded(resolution: &str) -> io::Result<RoaringLandmask> {
+        if resolution != "f" {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "resolution {:?} is not available, only \"f\" is",
+                    resolution
+                ),
+            ));
+        }
+
+        let mask = download::fetch("mask.tbmap.xz", MASK_SHA256)?;
+        let shapes = download::fetch(shapes::GSHHS_F, GSHHS_F_SHA256)?;
+
+        Ok(RoaringLandmask::from_parts(
+            RoaringMask::from_compressed(mask)?,
+            Gshhg::from_compressed(shapes)?,
+        ))
+    }
+
+    /// Make a landmask from the embedded mask and `shapes`, e.g. GSHHG shapes with lakes for
+    /// `enclosed_water`, see `EnclosedWater`. The embedded mask has land in the lakes, but the
+    /// shapes must not have land where it has none, see `from_parts`.
+    #[staticmethod]
+    #[pyo3(name = "from_shapes")]
+    fn py_from_shapes(shapes: Gshhg) -> io::Result<RoaringLandmask> {
+        Ok(RoaringLandmask::from_parts(shared()?.0.clone(), shapes))
+    }
+
+    /// Load the crude mask embedded in the library, see `CrudeMask`. It needs no files and little
+    /// memory, but has an error of a few kilometers near the coast.
+    #[staticmethod]
+    pub fn embedded_crude() -> io::Result<CrudeMask> {
+        CrudeMask::new()
+    }
+
+    /// The coarse mask compiled into the library, see `InstantMask`. It is available at once
+    /// without loading or decoding anything, but has an error of up to a degree near the coast.
+    #[staticmethod]
+    pub fn instant() -> InstantMask {
+        InstantMask::new()
+    }
+
+    /// Make a new landmask where the land in the mask is grown by `buffer` cells, see
+    /// `RoaringMask::dilate`.
+    ///
+    /// The mask must report land for every point that the shapes consider land, otherwise the
+    /// point is classified as ocean without consulting the shapes. A wider buffer guarantees that
+    /// the shapes decide for all points within `buffer` cells of the coast, at the cost of
+    /// consulting them more often. Growing the full resolution mask takes several seconds.
+    #[staticmethod]
+    pub fn with_buffer(py: Python, buffer: u64) -> io::Result<RoaringLandmask> {
+        let mut landmask = RoaringLandmask::new(py)?;
+        landmask.mask = landmask.mask.dilate(buffer);
+        landmask.clear_integer_grid();
+
+        Ok(landmask)
+    }
+
+    /// Make a new landmask from the full resolution shapes simplified so that no vertex moves more
+    /// than about `epsilon_m` meters (see `Gshhg::simplify`), with the mask rasterized from the
+    /// simplified shapes (see `RoaringMask::from_shapes`). This trades accuracy of the coastline
+    /// for speed and memory in finer steps than the GSHHG resolutions.
+    ///
+    /// The resulting sizes are reported by `shapes.vertices` and `mask.mask_bytes`. Rasterizing
+    /// the mask at full resolution takes a while.
+    #[staticmethod]
+    pub fn with_generalization(py: Python, epsilon_m: f64) -> io::Result<RoaringLandmask> {
+        if !(epsilon_m >= 0.) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "epsilon_m must be zero or greater",
+            ));
+        }
+
+        let landmask = RoaringLandmask::new(py)?;
+
+        py.allow_threads(|| {
+            let shapes = landmask.shapes.simplify(epsilon_m)?;
+            Ok(landmask.with_shapes(shapes))
+        })
+    }
+
+    /// Make a new landmask where water shallower than `threshold_m` meters counts as land, e.g. to
+    /// include the intertidal zone.
+    ///
+    /// `elevation` is a raster of elevation in meters (positive up) which must be aligned with
+    /// the grid of the mask at every `step` cell, see `RoaringMask::from_bathymetry`. A full
+    /// resolution raster is large, so `step` should usually be greater than one.
+    #[staticmethod]
+    #[pyo3(signature = (elevation, threshold_m, step = 1))]
+    pub fn with_bathymetry(
+        py: Python,
+        elevation: PyReadonlyArray2<f64>,
+        threshold_m: f64,
+        step: usize,
+    ) -> PyResult<RoaringLandmask> {
+        let elevation = elevation.as_array();
+
+        if step == 0 {
+            return Err(PyValueError::new_err("step must be greater than zero"));
+        }
+
+        let expected = [
+            (mask::NY as usize + step - 1) / step,
+            (mask::NX as usize + step - 1) / step,
+        ];
+        if elevation.shape() != expected {
+            return Err(PyValueError::new_err(format!(
+                "elevation must have shape {:?} for step {}, got {:?}",
+                expected,
+                step,
+                elevation.shape()
+            )));
+        }
+
+        let shallow = RoaringMask::from_bathymetry(elevation, threshold_m, step);
+
+        let mut landmask = RoaringLandmask::new(py)?;
+        landmask.shallow = Some(shallow);
+        landmask.clear_integer_grid();
+
+        Ok(landmask)
+    }
+
+    /// Make a new landmask using the `antarctica` boundary as the coast of Antarctica.
+    ///
+    /// The bundled shapes use the ice front. For `Antarctica::GroundingLine` the ice front and
+    /// grounding line polygons (GSHHG level 5 and 6) must be given as xz-compressed WKB files,
+    /// see `Gshhg::with_grounding_line`. The mask is not changed: the grounding line lies within
+    /// the ice front, so the mask still covers all land and the shapes decide for the ice
+    /// shelves.
+    #[staticmethod]
+    #[pyo3(signature = (antarctica, ice_front = None, grounding_line = None))]
+    pub fn with_antarctica(
+        py: Python,
+        antarctica: Antarctica,
+        ice_front: Option<PathBuf>,
+        grounding_line: Option<PathBuf>,
+    ) -> io::Result<RoaringLandmask> {
+        let mut landmask = RoaringLandmask::new(py)?;
+
+        if antarctica == Antarctica::GroundingLine {
+            let (ice_front, grounding_line) = match (ice_front, grounding_line) {
+                (Some(i), Some(g)) => (i, g),
+                _ => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        "the ice front and grounding line shapes are required",
+                    ))
+                }
+            };
+
+            let ice_front = Gshhg::get_geometry_from_compressed(ice_front)?;
+            let grounding_line = Gshhg::get_geometry_from_compressed(grounding_line)?;
+
+            landmask.shapes = landmask
+                .shapes
+                .with_grounding_line(&ice_front, &grounding_line)?;
+            landmask.clear_integer_grid();
+        }
+
+        Ok(landmask)
+    }
+
+    /// Make a new landmask where the estuaries in `rivermouths`, an xz-compressed WKB file with
+    /// polygons, are water if `treat_rivermouths_as_water` is set. See
+    /// `Gshhg::with_rivermouths_as_water`.
+    #[staticmethod]
+    #[pyo3(signature = (rivermouths, treat_rivermouths_as_water = true))]
+    pub fn with_rivermouths(
+        py: Python,
+        rivermouths: PathBuf,
+        treat_rivermouths_as_water: bool,
+    ) -> io::Result<RoaringLandmask> {
+        let mut landmask = RoaringLandmask::new(py)?;
+
+        if treat_rivermouths_as_water {
+            let rivermouths = Gshhg::get_geometry_from_compressed(rivermouths)?;
+            landmask.shapes = landmask.shapes.with_rivermouths_as_water(&rivermouths)?;
+            landmask.clear_integer_grid();
+        }
+
+        Ok(landmask)
+    }
+
+    /// The GSHHG release, resolution and checksums of the data embedded in the library, for
+    /// recording the provenance of results. A landmask with modified shapes, e.g. from
+    /// `with_antarctica` or `Gshhg::simplify`, still reports the data it was derived from.
+    pub fn metadata(&self) -> Metadata {
+        Metadata::embedded()
+    }
+
+    /// Free the mask and shapes. Any subsequent queries from Python raise a `ValueError`, the
+    /// `try_` methods return an error, and the other queries panic.
+    ///
+    /// The data shared by landmasks from `new` is freed with the last of them that is closed,
+    /// unless it was kept by `preload_shared`.
+    pub fn close(&mut self) -> io::Result<()> {
+        self.mask = RoaringMask::default();
+        self.shapes = Gshhg::empty()?;
+        self.shallow = None;
+        self.zones.clear();
+        self.major = OnceLock::new();
+        self.clear_integer_grid();
+        self.shared = None;
+        self.closed = true;
+
+        Ok(())
+    }
+
+    #[getter]
+    pub fn closed(&self) -> bool {
+        self.closed
+    }
+
+    /// Whether the candidates of `distance_to_shore` are cached, see `with_distance_cache`.
+    #[getter]
+    fn get_distance_cache(&self) -> bool {
+        self.mask.has_distance_cache()
+    }
+
+    #[setter]
+    fn set_distance_cache(&mut self, enabled: bool) {
+        self.mask = self.mask.with_distance_cache(enabled);
+    }
+
+    /// Whether query points are cell centers or corners, see `with_sampling`.
+    #[getter]
+    fn get_sampling(&self) -> Sampling {
+        self.sampling
+    }
+
+    #[setter]
+    fn set_sampling(&mut self, sampling: Sampling) {
+        self.sampling = sampling;
+        self.clear_integer_grid();
+    }
+
+    /// Whether lakes and ponds count as land, see `with_enclosed_water`.
+    #[getter]
+    fn get_enclosed_water(&self) -> EnclosedWater {
+        self.enclosed_water
+    }
+
+    #[setter]
+    fn set_enclosed_water(&mut self, policy: EnclosedWater) {
+        self.enclosed_water = policy;
+        self.clear_integer_grid();
+    }
+
+    fn __enter__(slf: PyRef<'_, Self>) -> PyResult<PyRef<'_, Self>> {
+        slf.check_open()?;
+        Ok(slf)
+    }
+
+    fn __exit__(
+        &mut self,
+        _exc_type: PyObject,
+        _exc_value: PyObject,
+        _traceback: PyObject,
+    ) -> io::Result<bool> {
+        self.close()?;
+        Ok(false)
+    }
+
+    #[getter]
+    pub fn dx(&self) -> f64 {
+        self.mask.dx()
+    }
+
+    #[getter]
+    pub fn dy(&self) -> f64 {
+        self.mask.dy()
+    }
+
+    /// The largest side in meters of the cells of the mask at latitude `lat`, see
+    /// `RoaringMask::cell_size_m`. Closer to the coast than this the mask cannot tell land from
+    /// ocean, and the result of `contains` depends on the shapes.
+    pub fn effective_resolution_m(&self, lat: f64) -> f64 {
+        let (ew, ns) = self.mask.cell_size_m(lat);
+        ew.max(ns)
+    }
+
+    /// Check if point (x, y) is on land. With `exact=False` only the mask is checked, which is
+    /// faster but reports land up to about 460 m out at sea, see `contains_approx`.
+    #[pyo3(name = "contains", signature = (x, y, exact = true))]
+    fn py_contains(&self, x: f64, y: f64, exact: bool) -> PyResult<bool> {
+        self.check_open()?;
+
+        if exact {
+            self.try_contains(x, y)
+        } else {
+            self.try_contains_approx(x, y)
+        }
+        .map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+
+    #[pyo3(name = "level")]
+    fn py_level(&self, x: f64, y: f64) -> PyResult<u8> {
+        self.check_open()?;
+        self.try_level(x, y)
+            .map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+
+    #[pyo3(name = "contains_with_extra")]
+    fn py_contains_with_extra(&self, x: f64, y: f64, extra: &Gshhg) -> PyResult<bool> {
+        self.check_open()?;
+        Ok(self.contains_with_extra(x, y, extra))
+    }
+
+    /// Check which of the points (`x[i]`, `y[i]`) are on land, with `x` and `y` as pyarrow
+    /// `float64` arrays and the result as a pyarrow boolean array. Nulls stay null, see
+    /// `arrow_interop`.
+    #[cfg(feature = "arrow")]
+    #[pyo3(name = "contains_many_arrow")]
+    fn py_contains_many_arrow(
+        &self,
+        py: Python,
+        x: arrow::pyarrow::PyArrowType<arrow::array::ArrayData>,
+        y: arrow::pyarrow::PyArrowType<arrow::array::ArrayData>,
+    ) -> PyResult<arrow::pyarrow::PyArrowType<arrow::array::ArrayData>> {
+        use arrow::array::Array;
+
+        self.check_open()?;
+
+        let x = arrow_interop::float64(x.0, "x")?;
+        let y = arrow_interop::float64(y.0, "y")?;
+        if x.len() != y.len() {
+            return Err(PyValueError::new_err(format!(
+                "x and y must have the same length: {} != {}",
+                x.len(),
+                y.len()
+            )));
+        }
+
+        let land = py.allow_threads(|| self.contains_many_arrow(&x, &y));
+        Ok(arrow::pyarrow::PyArrowType(land.into_data()))
+    }
+
+    #[pyo3(name = "load_zones")]
+    fn py_load_zones(&mut self, zones: Gshhg, label: String) -> PyResult<()> {
+        self.check_open()?;
+        self.load_zones(zones, label);
+        Ok(())
+    }
+
+    #[getter(zone_labels)]
+    fn py_zone_labels(&self) -> Vec<String> {
+        self.zone_labels().into_iter().map(String::from).collect()
+    }
+
+    #[pyo3(name = "zone_of")]
+    fn py_zone_of(&self, py: Python, x: f64, y: f64) -> PyResult<Option<String>> {
+        self.check_open()?;
+        Ok(py.allow_threads(|| self.zone_of(x, y).map(String::from)))
+    }
+
+    /// The label of the zone of every point (x, y) in parallel, as an object array with the
+    /// same shape holding `None` outside all zones, see `zone_of`.
+    #[pyo3(name = "zone_of_many")]
+    fn py_zone_of_many(
+        &self,
+        py: Python,
+        x: PyReadonlyArrayDyn<f64>,
+        y: PyReadonlyArrayDyn<f64>,
+    ) -> PyResult<Py<PyArray<PyObject, numpy::IxDyn>>> {
+        self.check_open()?;
+
+        let x = x.as_array();
+        let y = y.as_array();
+        check_shape(x.shape(), y.shape())?;
+
+        use ndarray::Zip;
+        let zones = py.allow_threads(|| {
+            Zip::from(&x)
+                .and(&y)
+                .par_map_collect(|x, y| self.zone_of(*x, *y))
+        });
+        let zones = zones.map(|zone| zone.into_py(py));
+        Ok(PyArray::from_owned_object_array(py, zones).to_owned())
+    }
+
+    #[pyo3(name = "classify_regions")]
+    fn py_classify_regions(&self, x: f64, y: f64, regions: Vec<Gshhg>) -> PyResult<i32> {
+        self.check_open()?;
+        self.try_classify_regions(x, y, &regions)
+            .map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+
+    #[pyo3(name = "contains_rad")]
+    fn py_contains_rad(&self, lon_rad: f64, lat_rad: f64) -> PyResult<bool> {
+        self.check_open()?;
+        Ok(self.contains_rad(lon_rad, lat_rad))
+    }
+
+    #[pyo3(name = "contains_explain")]
+    fn py_contains_explain(&self, x: f64, y: f64) -> PyResult<Decision> {
+        self.check_open()?;
+        self.try_contains_explain(x, y)
+            .map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+
+    #[pyo3(name = "contains_tri")]
+    fn py_contains_tri(&self, x: f64, y: f64, margin: f64) -> PyResult<Tri> {
+        self.check_open()?;
+        Ok(self.contains_tri(x, y, margin))
+    }
+
+    #[pyo3(name = "contains_tol")]
+    fn py_contains_tol(&self, x: f64, y: f64, cells: u32) -> PyResult<bool> {
+        self.check_open()?;
+        self.try_contains_tol(x, y, cells)
+            .map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+
+    #[pyo3(name = "contains_rotated")]
+    fn py_contains_rotated(
+        &self,
+        rlon: f64,
+        rlat: f64,
+        pole_lon: f64,
+        pole_lat: f64,
+    ) -> PyResult<bool> {
+        self.check_open()?;
+        Ok(self.contains_rotated(rlon, rlat, pole_lon, pole_lat))
+    }
+
+    #[pyo3(name = "contains_exact")]
+    fn py_contains_exact(&self, x: f64, y: f64) -> PyResult<bool> {
+        self.check_open()?;
+        self.try_contains_exact(x, y)
+            .map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+
+    #[pyo3(name = "distance_to_shore", signature = (x, y, unit = Unit::Meters))]
+    fn py_distance_to_shore(&self, x: f64, y: f64, unit: Unit) -> PyResult<f64> {
+        self.check_open()?;
+        Ok(self.distance_to_shore_in(x, y, unit))
+    }
+
+    #[pyo3(name = "signed_distance_to_shore", signature = (x, y, unit = Unit::Meters))]
+    fn py_signed_distance_to_shore(&self, x: f64, y: f64, unit: Unit) -> PyResult<f64> {
+        self.check_open()?;
+        Ok(self.signed_distance_to_shore(x, y) / unit.meters())
+    }
+
+    /// Centers of the cells within `bbox` which this landmask and `other` classify differently,
+    /// see `diff`.
+    #[pyo3(name = "diff")]
+    fn py_diff(
+        &self,
+        py: Python,
+        other: PyRef<RoaringLandmask>,
+        bbox: shapes::BBox,
+    ) -> PyResult<Vec<(f64, f64)>> {
+        self.check_open()?;
+        other.check_open()?;
+
+        let other = &*other;
+        Ok(py.allow_threads(|| self.diff(other, bbox)))
+    }
+
+    /// This landmask with the data within `bbox` replaced by that of `regional`, see `overlay`.
+    #[pyo3(name = "overlay")]
+    fn py_overlay(
+        &self,
+        py: Python,
+        regional: PyRef<RoaringLandmask>,
+        bbox: shapes::BBox,
+    ) -> PyResult<RoaringLandmask> {
+        self.check_open()?;
+        regional.check_open()?;
+
+        let regional = &*regional;
+        py.allow_threads(|| self.overlay(regional, bbox))
+            .map_err(|e| match e.kind() {
+                io::ErrorKind::InvalidInput => PyValueError::new_err(e.to_string()),
+                _ => e.into(),
+            })
+    }
+
+    #[pyo3(name = "nearest_coast_segment")]
+    fn py_nearest_coast_segment(&self, x: f64, y: f64) -> PyResult<Option<shapes::Segment>> {
+        self.check_open()?;
+        Ok(self.nearest_coast_segment(x, y))
+    }
+
+    #[pyo3(name = "contains_int")]
+    fn py_contains_int(&self, py: Python, lon: i32, lat: i32) -> PyResult<bool> {
+        self.check_open()?;
+        Ok(py.allow_threads(|| self.contains_int(lon, lat)))
+    }
+
+    #[pyo3(name = "circle_intersects_land")]
+    fn py_circle_intersects_land(
+        &self,
+        py: Python,
+        x: f64,
+        y: f64,
+        radius_km: f64,
+    ) -> PyResult<bool> {
+        self.check_open()?;
+        Ok(py.allow_threads(|| self.circle_intersects_land(x, y, radius_km)))
+    }
+
+    #[pyo3(name = "contains_major")]
+    fn py_contains_major(&self, py: Python, x: f64, y: f64) -> PyResult<bool> {
+        self.check_open()?;
+        Ok(py.allow_threads(|| self.contains_major(x, y)))
+    }
+
+    #[pyo3(name = "contains_and_distance", signature = (x, y, unit = Unit::Meters))]
+    fn py_contains_and_distance(&self, x: f64, y: f64, unit: Unit) -> PyResult<(bool, f64)> {
+        self.check_open()?;
+        let (land, distance) = self.contains_and_distance(x, y);
+        Ok((land, distance / unit.meters()))
+    }
+
+    /// Lines at the signed distances `levels_km` from the coast within `bbox` as WKB, one
+    /// `MultiLineString` for each level. See `distance_contours`.
+    #[pyo3(name = "distance_contours")]
+    fn py_distance_contours<'py>(
+        &self,
+        py: Python<'py>,
+        levels_km: Vec<f64>,
+        bbox: shapes::BBox,
+    ) -> PyResult<Vec<&'py pyo3::types::PyBytes>> {
+        use geos::Geom;
+
+        self.check_open()?;
+
+        self.distance_contours(&levels_km, bbox)
+            .map_err(|e| PyValueError::new_err(e.to_string()))?
+            .iter()
+            .map(|g| {
+                let wkb = g
+                    .to_wkb()
+                    .map_err(|_| PyValueError::new_err("cannot write geometry"))?;
+                Ok(pyo3::types::PyBytes::new(py, wkb.as_ref()))
+            })
+            .collect()
+    }
+
+    #[pyo3(name = "crossed_onto_land")]
+    fn py_crossed_onto_land(
+        &self,
+        from: (f64, f64),
+        to: (f64, f64),
+    ) -> PyResult<Option<(f64, f64)>> {
+        self.check_open()?;
+        Ok(self.crossed_onto_land(from, to))
+    }
+
+    #[pyo3(name = "first_landfall")]
+    fn py_first_landfall(
+        &self,
+        origin: (f64, f64),
+        bearing_deg: f64,
+        max_km: f64,
+    ) -> PyResult<Option<(f64, f64)>> {
+        self.check_open()?;
+        Ok(self.first_landfall(origin, bearing_deg, max_km))
+    }
+
+    #[pyo3(name = "coastline_crossings")]
+    fn py_coastline_crossings(&self, coords: Vec<(f64, f64)>) -> PyResult<usize> {
+        self.check_open()?;
+        Ok(self.coastline_crossings(&coords))
+    }
+
+    /// The runs of land and ocean along each of `tracks`, see `classify_track`. The indices
+    /// refer to the points of `densify_track(track, step_m)`.
+    #[pyo3(name = "classify_tracks", signature = (tracks, step_m = path::STEP))]
+    fn py_classify_tracks(
+        &self,
+        py: Python,
+        tracks: Vec<Vec<(f64, f64)>>,
+        step_m: f64,
+    ) -> PyResult<Vec<Vec<(bool, usize, usize)>>> {
+        self.check_open()?;
+        if !(step_m > 0.) {
+            return Err(PyValueError::new_err("step_m must be greater than zero"));
+        }
+
+        Ok(py.allow_threads(|| self.classify_tracks(&tracks, step_m)))
+    }
+
+    #[pyo3(name = "land_time_fraction")]
+    fn py_land_time_fraction(&self, coords: Vec<(f64, f64)>) -> PyResult<f64> {
+        self.check_open()?;
+        Ok(self.land_time_fraction(&coords))
+    }
+
+    #[pyo3(name = "bbox_is_all_ocean")]
+    fn py_bbox_is_all_ocean(&self, bbox: shapes::BBox) -> PyResult<bool> {
+        self.check_open()?;
+
+        let (west, south, east, north) = bbox;
+        if ![west, south, east, north].iter().all(|v| v.is_finite()) || south > north {
+            return Err(PyValueError::new_err(format!("invalid bbox: {:?}", bbox)));
+        }
+
+        Ok(self.bbox_is_all_ocean(bbox))
+    }
+
+    #[pyo3(name = "land_weight")]
+    fn py_land_weight(&self, py: Python, x: f64, y: f64) -> PyResult<f64> {
+        self.check_open()?;
+        Ok(py.allow_threads(|| self.land_weight(x, y)))
+    }
+
+    /// The land weight for every point (x, y), with the same shape as `x` and `y`. See
+    /// `land_weight`.
+    pub fn land_weight_many(
+        &self,
+        py: Python,
+        x: PyReadonlyArrayDyn<f64>,
+        y: PyReadonlyArrayDyn<f64>,
+    ) -> PyResult<Py<PyArray<f64, numpy::IxDyn>>> {
+        self.check_open()?;
+
+        let x = x.as_array();
+        let y = y.as_array();
+        check_shape(x.shape(), y.shape())?;
+
+        let points = x.iter().copied().zip(y.iter().copied()).collect::<Vec<_>>();
+        let weights = py.allow_threads(|| self.land_weight_many_blocking(&points));
+
+        let weights = ndarray::Array::from_shape_vec(x.raw_dim(), weights).unwrap();
+        Ok(PyArray::from_owned_array(py, weights).to_owned())
+    }
+
+    #[pyo3(name = "land_fraction", signature = (bbox, samples = COVERAGE_SAMPLES))]
+    fn py_land_fraction(&self, py: Python, bbox: shapes::BBox, samples: usize) -> PyResult<f64> {
+        self.check_open()?;
+        Ok(py.allow_threads(|| self.land_fraction(bbox, samples)))
+    }
+
+    #[pyo3(name = "classify_polygon", signature = (ring, samples = COVERAGE_SAMPLES))]
+    fn py_classify_polygon(&self, ring: Vec<(f64, f64)>, samples: usize) -> PyResult<Coverage> {
+        self.check_open()?;
+        Ok(self.classify_polygon(&ring, samples))
+    }
+
+    /// Where the mask has land, as a dict with the keys of `Summary`: `land_cells`, `tiles`,
+    /// `extent` and `land_lat` (`None` without land), `south_pole` and `north_pole`.
+    #[pyo3(name = "coverage_summary")]
+    fn py_coverage_summary<'py>(&self, py: Python<'py>) -> PyResult<&'py pyo3::types::PyDict> {
+        self.check_open()?;
+        let summary = py.allow_threads(|| self.coverage_summary());
+
+        let dict = pyo3::types::PyDict::new(py);
+        dict.set_item("land_cells", summary.land_cells)?;
+        dict.set_item("tiles", summary.tiles)?;
+        dict.set_item("extent", summary.extent)?;
+        dict.set_item("land_lat", summary.land_lat)?;
+        dict.set_item("south_pole", summary.south_pole)?;
+        dict.set_item("north_pole", summary.north_pole)?;
+
+        Ok(dict)
+    }
+
+    #[pyo3(name = "same_landmass")]
+    fn py_same_landmass(&self, a: (f64, f64), b: (f64, f64)) -> PyResult<bool> {
+        self.check_open()?;
+        Ok(self.same_landmass(a, b))
+    }
+
+    /// Reference points that do not match, see `self_test`. Empty if all match.
+    #[pyo3(name = "self_test")]
+    fn py_self_test(&self) -> PyResult<Vec<(f64, f64, bool, bool)>> {
+        self.check_open()?;
+        Ok(self.self_test().err().unwrap_or_default())
+    }
+
+    /// Cells where the mask and the shapes disagree away from the shore, checking every `step`
+    /// cell. See `audit`.
+    #[pyo3(name = "audit", signature = (step = 240))]
+    fn py_audit(&self, step: u64) -> PyResult<Vec<(f64, f64)>> {
+        self.check_open()?;
+
+        if step == 0 {
+            return Err(PyValueError::new_err("step must be greater than zero"));
+        }
+
+        Ok(self.audit(step))
+    }
+
+    #[pyo3(name = "save_bundle")]
+    fn py_save_bundle(&self, path: PathBuf) -> PyResult<()> {
+        self.check_open()?;
+        Ok(self.save_bundle(path)?)
+    }
+
+    #[staticmethod]
+    #[pyo3(name = "load_bundle")]
+    fn py_load_bundle(path: PathBuf) -> io::Result<RoaringLandmask> {
+        RoaringLandmask::load_bundle(path)
+    }
+
+    /// Check which of the points (x, y) are on land, see `contains_many`.
+    ///
+    /// With `exact=False` only the mask is checked, which is faster but reports land up to about
+    /// 460 m out at sea, see `contains_approx`. With `dedupe=True` each unique point is only
+    /// checked once, which is faster when the same points occur many times (e.g. fixed
+    /// stations). The results are the same either way.
+    #[pyo3(
+        name = "contains_many",
+        signature = (x, y, stats = None, exact = true, dedupe = false)
+    )]
+    fn py_contains_many(
+        &self,
+        py: Python,
+        x: PyReadonlyArrayDyn<f64>,
+        y: PyReadonlyArrayDyn<f64>,
+        stats: Option<PyRef<QueryStats>>,
+        exact: bool,
+        dedupe: bool,
+    ) -> PyResult<Py<PyArray<bool, numpy::Ix1>>> {
+        self.contains_many_counted(py, x, y, dedupe, stats.as_deref(), exact)
+    }
+
+    /// The indices of the points (x, y) in the flattened arrays which are outside the landmask,
+    /// see `validate_coords`, as an `int64` array. Empty if all are valid.
+    #[staticmethod]
+    #[pyo3(name = "validate_coords")]
+    fn py_validate_coords(
+        py: Python,
+        x: PyReadonlyArrayDyn<f64>,
+        y: PyReadonlyArrayDyn<f64>,
+    ) -> PyResult<Py<PyArray<i64, numpy::Ix1>>> {
+        let x = x.as_array();
+        let y = y.as_array();
+        check_shape(x.shape(), y.shape())?;
+
+        let (x, y) = (standard_slice(&x), standard_slice(&y));
+        let invalid = RoaringLandmask::validate_coords(&x, &y)
+            .err()
+            .unwrap_or_default();
+
+        Ok(PyArray::from_vec(py, index_array(invalid)).to_owned())
+    }
+
+    /// Same as `contains_many`, but checks the points in parallel and keeps the shape of `x` and
+    /// `y`.
+    #[pyo3(
+        name = "contains_many_par",
+        signature = (x, y, stats = None, exact = true, dedupe = false)
+    )]
+    fn py_contains_many_par(
+        &self,
+        py: Python,
+        x: PyReadonlyArrayDyn<f64>,
+        y: PyReadonlyArrayDyn<f64>,
+        stats: Option<PyRef<QueryStats>>,
+        exact: bool,
+        dedupe: bool,
+    ) -> PyResult<Py<PyArray<bool, numpy::IxDyn>>> {
+        self.contains_many_par_counted(py, x, y, dedupe, stats.as_deref(), exact)
+    }
+
+    /// Same as `contains_many`, but writes the result into the existing file `out_path` at byte
+    /// `offset` with one byte per point instead of returning it, see `contains_slice_to_mmap`.
+    /// The file can be an `np.memmap` with `dtype=bool` of at least `offset + x.size` elements,
+    /// which sees the result once this returns.
+    #[pyo3(name = "contains_many_to_mmap", signature = (x, y, out_path, offset = 0))]
+    fn py_contains_many_to_mmap(
+        &self,
+        py: Python,
+        x: PyReadonlyArrayDyn<f64>,
+        y: PyReadonlyArrayDyn<f64>,
+        out_path: PathBuf,
+        offset: u64,
+    ) -> PyResult<()> {
+        self.check_open()?;
+
+        let x = x.as_array();
+        let y = y.as_array();
+        check_shape(x.shape(), y.shape())?;
+
+        let (x, y) = (standard_slice(&x), standard_slice(&y));
+        py.allow_threads(|| self.contains_slice_to_mmap(&x, &y, out_path, offset))
+            .map_err(|e| match e.kind() {
+                io::ErrorKind::InvalidInput => PyValueError::new_err(e.to_string()),
+                _ => e.into(),
+            })
+    }
+
+    /// The GSHHG level of every point (x, y) as an `uint8` array with the same shape, see
+    /// `level`, and `contains_many` for `dedupe`.
+    #[pyo3(signature = (x, y, dedupe = false))]
+    pub fn level_many(
+        &self,
+        py: Python,
+        x: PyReadonlyArrayDyn<f64>,
+        y: PyReadonlyArrayDyn<f64>,
+        dedupe: bool,
+    ) -> PyResult<Py<PyArray<u8, numpy::IxDyn>>> {
+        use ndarray::parallel::prelude::*;
+
+        self.check_open()?;
+
+        let x = x.as_array();
+        let y = y.as_array();
+        check_shape(x.shape(), y.shape())?;
+
+        let (xs, ys) = (standard_slice(&x), standard_slice(&y));
+        let level = py
+            .allow_threads(|| {
+                let level = |x: &[f64], y: &[f64]| -> io::Result<Vec<u8>> {
+                    x.par_iter()
+                        .zip(y)
+                        .map(|(x, y)| self.try_level(*x, *y))
+                        .collect()
+                };
+
+                if dedupe {
+                    deduplicated(&xs, &ys, level)
+                } else {
+                    level(&xs, &ys)
+                }
+            })
+            .map_err(|e| PyValueError::new_err(e.to_string()))?;
+
+        let level = ndarray::Array::from_shape_vec(x.raw_dim(), level).unwrap();
+        Ok(PyArray::from_owned_array(py, level).to_owned())
+    }
+
+    /// Classify every point (x, y) into a numpy structured array with the fields `on_land`
+    /// (`bool`, see `contains`), `surface` (`uint8`, the GSHHG level, see `level`) and `dist_m`
+    /// (`float32`, see `distance_to_shore`), one record per point of the flattened `x` and `y`.
+    /// It can be passed straight to `pd.DataFrame`.
+    ///
+    /// Points outside the landmask are handled by `out_of_domain`, with a NaN `dist_m` for
+    /// `OutOfDomain::ReturnFalse`.
+    pub fn classify_many_struct<'py>(
+        &self,
+        py: Python<'py>,
+        x: PyReadonlyArrayDyn<f64>,
+        y: PyReadonlyArrayDyn<f64>,
+    ) -> PyResult<&'py PyAny> {
+        use ndarray::parallel::prelude::*;
+
+        self.check_open()?;
+
+        let x = x.as_array();
+        let y = y.as_array();
+        check_shape(x.shape(), y.shape())?;
+
+        let points = x.iter().copied().zip(y.iter().copied()).collect::<Vec<_>>();
+        let (land, surface, distances) = py
+            .allow_threads(|| -> io::Result<_> {
+                let surface = points
+                    .par_iter()
+                    .map(|(x, y)| self.try_level(*x, *y))
+                    .collect::<io::Result<Vec<_>>>()?;
+
+                // Points outside the landmask are in the ocean by `OutOfDomain::ReturnFalse`, and
+                // have no distance to the shore.
+                let inside = points
+                    .iter()
+                    .copied()
+                    .filter(|(_, y)| *y >= -90. && *y <= 90.)
+                    .collect::<Vec<_>>();
+                let (inside_land, inside_distances) =
+                    self.contains_and_distance_many_blocking(&inside);
+                let mut inside = inside_land.into_iter().zip(inside_distances);
+
+                let (land, distances): (Vec<bool>, Vec<f64>) = points
+                    .iter()
+                    .map(|(_, y)| {
+                        if *y >= -90. && *y <= 90. {
+                            inside.next().unwrap()
+                        } else {
+                            (false, f64::NAN)
+                        }
+                    })
+                    .unzip();
+
+                Ok((land, surface, distances))
+            })
+            .map_err(|e| PyValueError::new_err(e.to_string()))?;
+
+        let np = py.import("numpy")?;
+        let dtype = vec![("on_land", "?"), ("surface", "u1"), ("dist_m", "f4")];
+        let out = np.call_method1("empty", (points.len(), dtype))?;
+
+        out.set_item("on_land", PyArray::from_vec(py, land))?;
+        out.set_item("surface", PyArray::from_vec(py, surface))?;
+        out.set_item(
+            "dist_m",
+            PyArray::from_iter(py, distances.into_iter().map(|d| d as f32)),
+        )?;
+
+        Ok(out)
+    }
+
+    /// Signed distance to the shore for every point (x, y), negative on land, with the same shape
+    /// as `x` and `y`. See `signed_distance_to_shore_many_blocking`, and `contains_many` for
+    /// `dedupe`.
+    #[pyo3(signature = (x, y, unit = Unit::Meters, dedupe = false))]
+    pub fn signed_distance_to_shore_many(
+        &self,
+        py: Python,
+        x: PyReadonlyArrayDyn<f64>,
+        y: PyReadonlyArrayDyn<f64>,
+        unit: Unit,
+        dedupe: bool,
+    ) -> PyResult<Py<PyArray<f64, numpy::IxDyn>>> {
+        self.check_open()?;
+
+        let x = x.as_array();
+        let y = y.as_array();
+        check_shape(x.shape(), y.shape())?;
+
+        let (xs, ys) = (standard_slice(&x), standard_slice(&y));
+        let distances = py.allow_threads(|| {
+            let distances = |x: &[f64], y: &[f64]| -> io::Result<Vec<f64>> {
+                let points = x.iter().copied().zip(y.iter().copied()).collect::<Vec<_>>();
+                Ok(self.signed_distance_to_shore_many_blocking(&points))
+            };
+
+            if dedupe {
+                deduplicated(&xs, &ys, distances)
+            } else {
+                distances(&xs, &ys)
+            }
+        })?;
+
+        let distances = ndarray::Array::from_shape_vec(x.raw_dim(), distances)
+            .unwrap()
+            .mapv(|d| d / unit.meters());
+        Ok(PyArray::from_owned_array(py, distances).to_owned())
+    }
+
+    /// Distance to the shore for every point (x, y), with the same shape as `x` and `y`. See
+    /// `distance_to_shore_many_blocking`, and `contains_many` for `dedupe`.
+    #[pyo3(signature = (x, y, unit = Unit::Meters, dedupe = false))]
+    pub fn distance_to_shore_many(
+        &self,
+        py: Python,
+        x: PyReadonlyArrayDyn<f64>,
+        y: PyReadonlyArrayDyn<f64>,
+        unit: Unit,
+        dedupe: bool,
+    ) -> PyResult<Py<PyArray<f64, numpy::IxDyn>>> {
+        self.check_open()?;
+
+        let x = x.as_array();
+        let y = y.as_array();
+        check_shape(x.shape(), y.shape())?;
+
+        let (xs, ys) = (standard_slice(&x), standard_slice(&y));
+        let distances = py.allow_threads(|| {
+            let distances = |x: &[f64], y: &[f64]| -> io::Result<Vec<f64>> {
+                let points = x.iter().copied().zip(y.iter().copied()).collect::<Vec<_>>();
+                Ok(self.distance_to_shore_many_blocking(&points))
+            };
+
+            if dedupe {
+                deduplicated(&xs, &ys, distances)
+            } else {
+                distances(&xs, &ys)
+            }
+        })?;
+
+        let distances = ndarray::Array::from_shape_vec(x.raw_dim(), distances)
+            .unwrap()
+            .mapv(|d| d / unit.meters());
+        Ok(PyArray::from_owned_array(py, distances).to_owned())
+    }
+
+    /// Whether every point (`rlon`, `rlat`) on the rotated-pole grid with its north pole at
+    /// (`pole_lon`, `pole_lat`) is on land, with the same shape as `rlon` and `rlat`. See
+    /// `contains_rotated`.
+    pub fn contains_rotated_many(
+        &self,
+        py: Python,
+        rlon: PyReadonlyArrayDyn<f64>,
+        rlat: PyReadonlyArrayDyn<f64>,
+        pole_lon: f64,
+        pole_lat: f64,
+    ) -> PyResult<Py<PyArray<bool, numpy::IxDyn>>> {
+        self.check_open()?;
+
+        let rlon = rlon.as_array();
+        let rlat = rlat.as_array();
+        check_shape(rlon.shape(), rlat.shape())?;
+
+        let points = rlon
+            .iter()
+            .copied()
+            .zip(rlat.iter().copied())
+            .collect::<Vec<_>>();
+        let land =
+            py.allow_threads(|| self.contains_rotated_many_blocking(&points, (pole_lon, pole_lat)));
+
+        let land = ndarray::Array::from_shape_vec(rlon.raw_dim(), land).unwrap();
+        Ok(PyArray::from_owned_array(py, land).to_owned())
+    }
+
+    /// Whether every point (x, y) is within its own distance `radii_km` of land, with the same
+    /// shape as `x`, `y` and `radii_km`. See `within_distance_many_blocking`.
+    pub fn within_distance_many(
+        &self,
+        py: Python,
+        x: PyReadonlyArrayDyn<f64>,
+        y: PyReadonlyArrayDyn<f64>,
+        radii_km: PyReadonlyArrayDyn<f64>,
+    ) -> PyResult<Py<PyArray<bool, numpy::IxDyn>>> {
+        self.check_open()?;
+
+        let x = x.as_array();
+        let y = y.as_array();
+        let radii_km = radii_km.as_array();
+        check_shape(x.shape(), y.shape())?;
+        if radii_km.shape() != x.shape() {
+            return Err(PyValueError::new_err(format!(
+                "radii_km must have the same shape as x and y: {:?} != {:?}",
+                radii_km.shape(),
+                x.shape()
+            )));
+        }
+
+        let points = x.iter().copied().zip(y.iter().copied()).collect::<Vec<_>>();
+        let radii_km = standard_slice(&radii_km);
+        let within = py.allow_threads(|| self.within_distance_many_blocking(&points, &radii_km));
+
+        let within = ndarray::Array::from_shape_vec(x.raw_dim(), within).unwrap();
+        Ok(PyArray::from_owned_array(py, within).to_owned())
+    }
+
+    /// Whether a disk of `radius_km` around every point (x, y) touches land, with the same shape
+    /// as `x` and `y`. See `circle_intersects_land`.
+    pub fn circle_intersects_land_many(
+        &self,
+        py: Python,
+        x: PyReadonlyArrayDyn<f64>,
+        y: PyReadonlyArrayDyn<f64>,
+        radius_km: f64,
+    ) -> PyResult<Py<PyArray<bool, numpy::IxDyn>>> {
+        self.check_open()?;
+
+        let x = x.as_array();
+        let y = y.as_array();
+        check_shape(x.shape(), y.shape())?;
+
+        let points = x.iter().copied().zip(y.iter().copied()).collect::<Vec<_>>();
+        let touches =
+            py.allow_threads(|| self.circle_intersects_land_many_blocking(&points, radius_km));
+
+        let touches = ndarray::Array::from_shape_vec(x.raw_dim(), touches).unwrap();
+        Ok(PyArray::from_owned_array(py, touches).to_owned())
+    }
+
+    /// Whether every point (x, y) is on land and its distance to the shore, as two arrays with
+    /// the same shape as `x` and `y`. See `contains_and_distance_many_blocking`.
+    #[pyo3(signature = (x, y, unit = Unit::Meters))]
+    pub fn contains_and_distance_many(
+        &self,
+        py: Python,
+        x: PyReadonlyArrayDyn<f64>,
+        y: PyReadonlyArrayDyn<f64>,
+        unit: Unit,
+    ) -> PyResult<(
+        Py<PyArray<bool, numpy::IxDyn>>,
+        Py<PyArray<f64, numpy::IxDyn>>,
+    )> {
+        self.check_open()?;
+
+        let x = x.as_array();
+        let y = y.as_array();
+        check_shape(x.shape(), y.shape())?;
+
+        let points = x.iter().copied().zip(y.iter().copied()).collect::<Vec<_>>();
+        let (land, distances) =
+            py.allow_threads(|| self.contains_and_distance_many_blocking(&points));
+
+        let land = ndarray::Array::from_shape_vec(x.raw_dim(), land).unwrap();
+        let distances = ndarray::Array::from_shape_vec(x.raw_dim(), distances)
+            .unwrap()
+            .mapv(|d| d / unit.meters());
+
+        Ok((
+            PyArray::from_owned_array(py, land).to_owned(),
+            PyArray::from_owned_array(py, distances).to_owned(),
+        ))
+    }
+
+    /// Same as `contains_many_par`, but points inside the polygons of `extra` also count as land,
+    /// see `contains_with_extra`.
+    pub fn contains_many_with_extra(
+        &self,
+        py: Python,
+        x: PyReadonlyArrayDyn<f64>,
+        y: PyReadonlyArrayDyn<f64>,
+        extra: &Gshhg,
+    ) -> PyResult<Py<PyArray<bool, numpy::IxDyn>>> {
+        self.check_open()?;
+
+        let x = x.as_array();
+        let y = y.as_array();
+        check_shape(x.shape(), y.shape())?;
+
+        use ndarray::Zip;
+        let contains = Zip::from(&x)
+            .and(&y)
+            .par_map_collect(|x, y| self.contains_with_extra(*x, *y, extra));
+        Ok(PyArray::from_owned_array(py, contains).to_owned())
+    }
+
+    /// Classify every point (x, y) in parallel as an `int32` array with the same shape: the
+    /// index of the first of `regions` containing it, otherwise `LAND` or `OCEAN`. See
+    /// `classify_regions`.
+    pub fn classify_regions_many(
+        &self,
+        py: Python,
+        x: PyReadonlyArrayDyn<f64>,
+        y: PyReadonlyArrayDyn<f64>,
+        regions: Vec<Gshhg>,
+    ) -> PyResult<Py<PyArray<i32, numpy::IxDyn>>> {
+        self.check_open()?;
+
+        let x = x.as_array();
+        let y = y.as_array();
+        check_shape(x.shape(), y.shape())?;
+
+        use ndarray::Zip;
+        let classes = Zip::from(&x)
+            .and(&y)
+            .par_map_collect(|x, y| self.try_classify_regions(*x, *y, &regions));
+        let classes = classes
+            .into_iter()
+            .collect::<io::Result<Vec<_>>>()
+            .map_err(|e| PyValueError::new_err(e.to_string()))?;
+        let classes = ndarray::Array::from_shape_vec(x.raw_dim(), classes).unwrap();
+        Ok(PyArray::from_owned_array(py, classes).to_owned())
+    }
+
+    /// Same as `contains_many_par`, but with the longitudes and latitudes in radians, see
+    /// `contains_rad`.
+    pub fn contains_many_rad(
+        &self,
+        py: Python,
+        lon_rad: PyReadonlyArrayDyn<f64>,
+        lat_rad: PyReadonlyArrayDyn<f64>,
+    ) -> PyResult<Py<PyArray<bool, numpy::IxDyn>>> {
+        self.check_open()?;
+
+        let x = lon_rad.as_array();
+        let y = lat_rad.as_array();
+        check_shape(x.shape(), y.shape())?;
+
+        use ndarray::Zip;
+        let contains = Zip::from(&x)
+            .and(&y)
+            .par_map_collect(|x, y| self.contains_rad(*x, *y));
+        Ok(PyArray::from_owned_array(py, contains).to_owned())
+    }
+
+    /// Iterate lazily over whether each point (x, y) is on land, without allocating an output
+    /// array. The points are checked as the iterator is advanced.
+    pub fn contains_iter(
+        slf: PyRef<'_, Self>,
+        x: &PyArrayDyn<f64>,
+        y: &PyArrayDyn<f64>,
+    ) -> PyResult<ContainsIter> {
+        slf.check_open()?;
+        check_shape(x.shape(), y.shape())?;
+
+        let py = slf.py();
+        Ok(ContainsIter::new(py, slf.into(), x, y))
+    }
+
+    /// Move every point (x, y) onto the nearest land within `max_dist` meters, see
+    /// `nearest_land`. Returns the longitudes and latitudes with the same shape as `x` and `y`,
+    /// NaN where there is no land within `max_dist`.
+    #[pyo3(signature = (x, y, max_dist = f64::INFINITY))]
+    pub fn nearest_land_many(
+        &self,
+        py: Python,
+        x: PyReadonlyArrayDyn<f64>,
+        y: PyReadonlyArrayDyn<f64>,
+        max_dist: f64,
+    ) -> PyResult<(Py<PyArrayDyn<f64>>, Py<PyArrayDyn<f64>>)> {
+        self.check_open()?;
+
+        let x = x.as_array();
+        let y = y.as_array();
+        check_shape(x.shape(), y.shape())?;
+
+        use ndarray::Zip;
+        let nearest = Zip::from(&x).and(&y).par_map_collect(|x, y| {
+            self.nearest_land(*x, *y, max_dist)
+                .unwrap_or((f64::NAN, f64::NAN))
+        });
+
+        Ok((
+            PyArray::from_owned_array(py, nearest.map(|p| p.0)).to_owned(),
+            PyArray::from_owned_array(py, nearest.map(|p| p.1)).to_owned(),
+        ))
+    }
+
+    /// Sample `contains` on a regular grid from (`xmin`, `ymin`) to (`xmax`, `ymax`) with
+    /// `step_deg` degrees between the points, e.g. for a quick overview plot. The maximum is
+    /// included if it falls on the grid.
+    ///
+    /// Returns a 2D array of (latitude, longitude) together with the longitudes and latitudes of
+    /// the grid.
+    pub fn contains_grid_coarse(
+        &self,
+        py: Python,
+        xmin: f64,
+        xmax: f64,
+        ymin: f64,
+        ymax: f64,
+        step_deg: f64,
+    ) -> PyResult<(
+        Py<PyArray<bool, numpy::Ix2>>,
+        Py<PyArray<f64, numpy::Ix1>>,
+        Py<PyArray<f64, numpy::Ix1>>,
+    )> {
+        self.check_open()?;
+
+        if !(step_deg > 0.) {
+            return Err(PyValueError::new_err("step_deg must be greater than zero"));
+        }
+        if !(xmin <= xmax && ymin <= ymax) {
+            return Err(PyValueError::new_err(
+                "the minimum must not exceed the maximum",
+            ));
+        }
+        if ymin < -90. || ymax > 90. {
+            return Err(PyValueError::new_err("latitude must be within [-90, 90]"));
+        }
+
+        // Allow for rounding, so that the maximum is included when it is a multiple of the step.
+        let n = |min: f64, max: f64| ((max - min) / step_deg + 1e-9).floor() as usize + 1;
+        let lons =
+            ndarray::Array::from_iter((0..n(xmin, xmax)).map(|i| xmin + i as f64 * step_deg));
+        let lats = ndarray::Array::from_iter(
+            (0..n(ymin, ymax)).map(|j| (ymin + j as f64 * step_deg).min(ymax)),
+        );
+
+        let mut grid = ndarray::Array2::from_elem((lats.len(), lons.len()), false);
+        py.allow_threads(|| {
+            ndarray::Zip::indexed(&mut grid)
+                .par_for_each(|(j, i), land| *land = self.contains(lons[i], lats[j]))
+        });
+
+        Ok((
+            PyArray::from_owned_array(py, grid).to_owned(),
+            PyArray::from_owned_array(py, lons).to_owned(),
+            PyArray::from_owned_array(py, lats).to_owned(),
+        ))
+    }
+
+    /// Classify the grid (x, y) once, and keep the result in a `GridMask` that can be reused for
+    /// every query against the same grid.
+    pub fn precompute_grid(
+        &self,
+        x: PyReadonlyArrayDyn<f64>,
+        y: PyReadonlyArrayDyn<f64>,
+    ) -> PyResult<GridMask> {
+        self.check_open()?;
+
+        let x = x.as_array();
+        let y = y.as_array();
+        check_shape(x.shape(), y.shape())?;
+
+        Ok(GridMask::new(self, x, y))
+    }
+}
+
+/// Compute `f` only for the unique points of (`x`, `y`), and scatter the results back to all the
+/// points. Points are the same if their coordinates have the same bits, so the results are
+/// identical to computing `f` for every point.
+fn deduplicated<T: Clone, E>(
+    x: &[f64],
+    y: &[f64],
+    f: impl FnOnce(&[f64], &[f64]) -> Result<Vec<T>, E>,
+) -> Result<Vec<T>, E> {
+    let mut unique = std::collections::HashMap::new();
+    let (mut ux, mut uy) = (Vec::new(), Vec::new());
+
+    let index = x
+        .iter()
+        .zip(y)
+        .map(|(x, y)| {
+            *unique.entry((x.to_bits(), y.to_bits())).or_insert_with(|| {
+                ux.push(*x);
+                uy.push(*y);
+                ux.len() - 1
+            })
+        })
+        .collect::<Vec<_>>();
+
+    let results = f(&ux, &uy)?;
+
+    Ok(index.into_iter().map(|i| results[i].clone()).collect())
+}
+
+/// Indices into the flattened input as `int64`, the dtype numpy uses for indexing, so that they
+/// are not truncated for inputs of more than 2^31 points on any platform.
+pub(crate) fn index_array(indices: Vec<usize>) -> Vec<i64> {
+    indices
+        .into_iter()
+        .map(|i| i64::try_from(i).expect("index does not fit in int64"))
+        .collect()
+}
+
+/// The elements of `a` in logical order, borrowed if the array is in standard layout.
+fn standard_slice<'a>(a: &'a ndarray::ArrayViewD<'_, f64>) -> Cow<'a, [f64]> {
+    a.as_slice()
+        .map(Cow::Borrowed)
+        .unwrap_or_else(|| Cow::Owned(a.iter().copied().collect()))
+}
+
+/// Check that the `x` and `y` coordinate arrays have the same shape.
+pub(crate) fn check_shape(x: &[usize], y: &[usize]) -> PyResult<()> {
+    if x != y {
+        Err(PyValueError::new_err(format!(
+            "x and y must have the same shape: {:?} != {:?}",
+            x, y
+        )))
+    } else {
+        Ok(())
+    }
+}
+
+/// Landmasses of at least this many square kilometers are major, see
+/// `RoaringLandmask::contains_major`. The continents and Greenland are above it, while New Guinea
+/// and Borneo are below.
+pub const MAJOR_LANDMASS_KM2: f64 = 1_000_000.;
+
+/// A mask of the landmasses of `shapes` with at least `min_area_km2` square kilometers, counting
+/// the parts split at the antimeridian together.
+fn major_landmasses(shapes: &Gshhg, min_area_km2: f64) -> RoaringMask {
+    let ids = (0..shapes.polygons()).map(PolygonId).collect::<Vec<_>>();
+
+    let mut areas = std::collections::HashMap::new();
+    for &id in &ids {
+        *areas.entry(shapes.landmass(id).unwrap()).or_insert(0.) +=
+            shapes.polygon_area_m2(id).unwrap() / 1e6;
+    }
+
+    let major = ids
+        .into_iter()
+        .filter(|&id| areas[&shapes.landmass(id).unwrap()] >= min_area_km2);
+
+    RoaringMask::from_polygons(shapes, major, mask::CellRule::AnyOverlap)
+}
+
+/// Number of points checked at a time by `RoaringLandmask::contains_slice_to_mmap`.
+pub const TO_FILE_CHUNK: usize = 1 << 20;
+
+/// Land or ocean at exactly the poles, where all longitudes collapse to a single point: the North
+/// Pole is in the ocean and the South Pole is on the Antarctic continent.
+fn pole(y: f64) -> Option<bool> {
+    if y == 90. {
+        Some(false)
+    } else if y == -90. {
+        Some(true)
+    } else {
+        None
+    }
+}
+
+/// The centers of `n` × `n` cells of equal size in `bbox` (west, south, east, north). If east <
+/// west the box crosses the antimeridian, and the longitudes of the samples east of it are
+/// beyond 180.
+fn grid_samples((west, south, east, north): shapes::BBox, n: usize) -> Vec<(f64, f64)> {
+    let east = if east < west { east + 360. } else { east };
+    let dx = (east - west) / n as f64;
+    let dy = (north - south) / n as f64;
+
+    (0..n)
+        .flat_map(|j| {
+            (0..n).map(move |i| (west + (i as f64 + 0.5) * dx, south + (j as f64 + 0.5) * dy))
+        })
+        .collect()
+}
+
+/// Move longitude into the [-180, 180) domain, so that 180 and -180 are the same.
+fn modulate_longitude(lon: f64) -> f64 {
+    (lon + 180.).rem_euclid(360.) - 180.
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn load_ms() {
+        pyo3::prepare_freethreaded_python();
+        pyo3::Python::with_gil(|py| {
+            let _ms = RoaringLandmask::new(py).unwrap();
+        })
+    }
+
+    #[test]
+    fn test_np() {
+        pyo3::prepare_freethreaded_python();
+        pyo3::Python::with_gil(|py| {
+            let mask = RoaringLandmask::new(py).unwrap();
+            assert!(!mask.contains(5., 90.));
+        })
+    }
+
+    #[test]
+    fn test_sp() {
+        pyo3::prepare_freethreaded_python();
+        pyo3::Python::with_gil(|py| {
+            let mask = RoaringLandmask::new(py).unwrap();
+            assert!(mask.contains(5., -89.99));
+        })
+    }
+
+    #[test]
+    fn test_sp_exact() {
+        pyo3::prepare_freethreaded_python();
+        pyo3::Python::with_gil(|py| {
+            let mask = RoaringLandmask::new(py).unwrap();
+            assert!(mask.contains(5., -90.));
+        })
+    }
+
+    #[test]
+    fn test_poles_any_longitude() {
+        pyo3::prepare_freethreaded_python();
+        pyo3::Python::with_gil(|py| {
+            let mask = RoaringLandmask::new(py).unwrap();
+
+            for x in (-360..=540).step_by(15).map(f64::from) {
+                assert!(!mask.contains(x, 90.));
+                assert!(mask.contains(x, -90.));
+            }
+        })
+    }
+
+    #[test]
+    fn contains_explain() {
+        pyo3::prepare_freethreaded_python();
+        pyo3::Python::with_gil(|py| {
+            let mask = RoaringLandmask::new(py).unwrap();
+
+            assert_eq!(
+                mask.contains_explain(15., 65.6),
+                Decision::LandByMaskAndShapes
+            );
+            assert_eq!(mask.contains_explain(5., 65.6), Decision::OceanByMask);
+            assert!(!mask.contains_explain(5., 65.6).shapes_consulted());
+
+            // Find a point near the shore where the shapes overrule the mask.
+            let near_shore = (0..10_000)
+                .map(|i| 5. + i as f64 * 0.001)
+                .map(|x| mask.contains_explain(x, 65.6))
+                .any(|d| d == Decision::OceanByShapesNearShore);
+            assert!(near_shore);
+
+            for x in (-180..180).step_by(10).map(f64::from) {
+                for y in (-90..=90).step_by(10).map(f64::from) {
+                    assert_eq!(mask.contains_explain(x, y).land(), mask.contains(x, y));
+                }
+            }
+
+            // The explanation is for the cell that `contains` looks up.
+            let mask = mask.with_sampling(Sampling::Corner);
+            for x in (0..2400).map(|i| 5. + i as f64 * 0.00416) {
+                assert_eq!(
+                    mask.contains_explain(x, 65.6).land(),
+                    mask.contains(x, 65.6)
+                );
+            }
+        })
+    }
+
+    #[test]
+    fn contains_approx() {
+        pyo3::prepare_freethreaded_python();
+        pyo3::Python::with_gil(|py| {
+            let mask = RoaringLandmask::new(py).unwrap();
+
+            assert!(mask.contains_approx(15., 65.6));
+            assert!(!mask.contains_approx(5., 65.6));
+            assert!(mask.contains_approx(5., -90.));
+
+            // The mask over-reports land near the shore, but never misses any.
+            let (x, y): (Vec<f64>, Vec<f64>) =
+                (0..10_000).map(|i| (5. + i as f64 * 0.001, 65.6)).unzip();
+            let exact = mask.try_contains_slice(&x, &y).unwrap();
+            let approx = mask
+                .try_contains_slice_counted(&x, &y, None, false)
+                .unwrap();
+
+            assert!(exact.iter().zip(&approx).all(|(e, a)| !e || *a));
+            assert!(exact.iter().zip(&approx).any(|(e, a)| !e && *a));
+
+            let stats = QueryStats::new();
+            let par = mask
+                .try_contains_slice_par_counted(&x, &y, Some(&stats), false)
+                .unwrap();
+            assert_eq!(par, approx);
+            assert_eq!(stats.land_by_shapes() + stats.ocean_by_shapes(), 0);
+
+            let (x, y) = (PyArray::from_vec(py, x), PyArray::from_vec(py, y));
+            let many = mask
+                .contains_many_approx(py, x.to_dyn().readonly(), y.to_dyn().readonly())
+                .unwrap();
+            assert_eq!(many.as_ref(py).to_vec().unwrap(), approx);
+            let many = mask
+                .contains_many_par_approx(py, x.to_dyn().readonly(), y.to_dyn().readonly())
+                .unwrap();
+            assert_eq!(many.as_ref(py).to_vec().unwrap(), approx);
+        })
+    }
+
+    #[test]
+    fn contains_tri() {
+        pyo3::prepare_freethreaded_python();
+        pyo3::Python::with_gil(|py| {
+            let mask = RoaringLandmask::new(py).unwrap();
+
+            assert_eq!(mask.contains_tri(15., 65.6, 1000.), Tri::Land);
+            assert_eq!(mask.contains_tri(5., 65.6, 1000.), Tri::Ocean);
+
+            // Within 1000 km of the Norwegian coast.
+            assert_eq!(mask.contains_tri(15., 65.6, 1e6), Tri::Uncertain);
+            assert_eq!(mask.contains_tri(5., 65.6, 1e6), Tri::Uncertain);
+        })
+    }
+
+    #[test]
+    fn contains_exact() {
+        pyo3::prepare_freethreaded_python();
+        pyo3::Python::with_gil(|py| {
+            let mask = RoaringLandmask::new(py).unwrap();
+
+            assert!(mask.contains_exact(15., 65.6));
+            assert!(!mask.contains_exact(5., 65.6));
+            assert!(!mask.contains_exact(5., 90.));
+            assert!(mask.contains_exact(5., -90.));
+        })
+    }
+
+    #[test]
+    fn contains_tol() {
+        pyo3::prepare_freethreaded_python();
+        pyo3::Python::with_gil(|py| {
+            let mask = RoaringLandmask::new(py).unwrap();
+
+            // Along the coast of Norway, the mask only decides points it has as ocean, or with
+            // land all around.
+            for x in (0..1000).map(|i| 5. + 10. * i as f64 / 1000.) {
+                let y = 65.6;
+                assert_eq!(mask.contains_tol(x, y, 0), mask.mask.contains(x, y));
+
+                for cells in [1, 4] {
+                    let tol = mask.contains_tol(x, y, cells);
+                    if mask.contains(x, y) {
+                        assert!(tol, "{x}, {y}");
+                    }
+                    if !mask.mask.contains(x, y) {
+                        assert!(!tol, "{x}, {y}");
+                    }
+                }
+            }
+
+            assert!(mask.contains_tol(20., 50., 4));
+            assert!(!mask.contains_tol(5., 65.6, 4));
+            assert!(!mask.contains_tol(5., 90., 4));
+            assert!(mask.contains_tol(5., -90., 4));
+        })
+    }
+
+    #[test]
+    fn contains_rotated() {
+        pyo3::prepare_freethreaded_python();
+        pyo3::Python::with_gil(|py| {
+            let mask = RoaringLandmask::new(py).unwrap();
+
+            // The COSMO-DE grid, with the origin at (10, 50) in Germany.
+            let pole = (-170., 40.);
+            assert!(mask.contains_rotated(0., 0., pole.0, pole.1));
+
+            let points = (-50..=55)
+                .flat_map(|i| (-50..=65).map(move |j| (i as f64 / 10., j as f64 / 10.)))
+                .collect::<Vec<_>>();
+            let land = mask.contains_rotated_many_blocking(&points, pole);
+
+            for (&(rlon, rlat), &land) in points.iter().zip(&land) {
+                let (x, y) = rotated::to_geographic(rlon, rlat, pole.0, pole.1);
+                assert_eq!(land, mask.contains(x, y));
+            }
+
+            // Mostly land, but with the North Sea and the Baltic.
+            let n = land.iter().filter(|&&l| l).count();
+            assert!(n > points.len() / 2 && n < points.len());
+        })
+    }
+
+    #[test]
+    fn enclosed_water() {
+        use geos::Geometry;
+
+        // Land with a large lake, an island in the lake and a pond on the island. The mask is
+        // made from the land without the lake, so it also has land in the lake.
+        let g = Geometry::new_from_wkt(
+            "MULTIPOLYGON(((0 0, 10 0, 10 10, 0 10, 0 0), (2 2, 8 2, 8 8, 2 8, 2 2)), ((3 3, 7 3, 7 7, 3 7, 3 3), (4 4, 6 4, 6 6, 4 6, 4 4)))",
+        )
+        .unwrap();
+        let outline = Geometry::new_from_wkt("POLYGON((0 0, 10 0, 10 10, 0 10, 0 0))").unwrap();
+        let mask = RoaringMask::from_shapes(&Gshhg::from_geom(outline).unwrap());
+        let shapes = Gshhg::from_geom(g).unwrap();
+
+        // In the ocean, on land, in the lake, on the island and in the pond.
+        let points = [(20., 20.), (1., 1.), (2.5, 2.5), (3.5, 3.5), (5., 5.)];
+        for (&(x, y), level) in points.iter().zip(0..) {
+            assert_eq!(shapes.level(x, y), level);
+        }
+
+        for (policy, expected) in [
+            (EnclosedWater::AsSeparate, [false, true, false, true, false]),
+            (EnclosedWater::AsLand, [false, true, true, true, true]),
+            (EnclosedWater::AsOcean, [false, true, false, false, false]),
+        ] {
+            let landmask = RoaringLandmask::from_parts(mask.clone(), shapes.clone())
+                .with_enclosed_water(policy);
+
+            for (&(x, y), expected) in points.iter().zip(expected) {
+                assert_eq!(landmask.contains(x, y), expected, "{policy:?}: {x}, {y}");
+                assert_eq!(
+                    landmask.contains_exact(x, y),
+                    expected,
+                    "{policy:?}: {x}, {y}"
+                );
+                assert_eq!(landmask.context().contains(x, y), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn contains_raw() {
+        pyo3::prepare_freethreaded_python();
+        pyo3::Python::with_gil(|py| {
+            let mask = RoaringLandmask::new(py).unwrap();
+
+            for x in (-180..180).step_by(5).map(f64::from) {
+                for y in (-90..=90).step_by(5).map(f64::from) {
+                    assert_eq!(mask.contains(x, y), mask.contains_raw(x, y));
+                }
+            }
+        })
+    }
+
+    #[test]
+    fn contains_many_blocking() {
+        fn is_send_sync<T: Send + Sync>() {}
+        is_send_sync::<RoaringLandmask>();
+
+        pyo3::prepare_freethreaded_python();
+        pyo3::Python::with_gil(|py| {
+            let mask = RoaringLandmask::new(py).unwrap();
+
+            assert_eq!(
+                mask.contains_many_blocking(&[(15., 65.6), (5., 65.6), (10., 60.)]),
+                vec![true, false, true]
+            );
+        })
+    }
+
+    #[test]
+    fn test_dateline_wrap() {
+        pyo3::prepare_freethreaded_python();
+        pyo3::Python::with_gil(|py| {
+            let mask = RoaringLandmask::new(py).unwrap();
+
+            // Close to NP
+            assert!(!mask.contains(5., 89.));
+
+            // Close to SP
+            assert!(mask.contains(5., -89.));
+
+            // Within bounds
+            let x = (-180..180).map(f64::from).collect::<Vec<_>>();
+            let m = x.iter().map(|x| mask.contains(*x, 65.)).collect::<Vec<_>>();
+
+            // Wrapped bounds
+            let x = (180..540).map(f64::from).collect::<Vec<_>>();
+            let mm = x.iter().map(|x| mask.contains(*x, 65.)).collect::<Vec<_>>();
+
+            assert_eq!(m, mm);
+        })
+    }
+
+    #[test]
+    fn test_modulate_longitude() {
+        assert_eq!(modulate_longitude(180.), -180.);
+        assert_eq!(modulate_longitude(-180.), -180.);
+        assert_eq!(modulate_longitude(540.), -180.);
+        assert_eq!(modulate_longitude(-190.), 170.);
+        assert_eq!(modulate_longitude(370.), 10.);
+        assert_eq!(modulate_longitude(15.), 15.);
+    }
+
+    #[test]
+    fn test_antimeridian() {
+        pyo3::prepare_freethreaded_python();
+        pyo3::Python::with_gil(|py| {
+            let mask = RoaringLandmask::new(py).unwrap();
+
+            for y in (-90..=90).map(f64::from) {
+                assert_eq!(mask.contains(180., y), mask.contains(-180., y));
+            }
+
+            // Taveuni, Fiji, is crossed by the antimeridian.
+            assert!(mask.contains(180., -16.9));
+            assert!(mask.contains(-180., -16.9));
+        })
+    }
+
+    #[cfg(feature = "download")]
+    #[test]
+    fn test_from_downloaded_resolution() {
+        let err = RoaringLandmask::from_downloaded("c").err().unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn test_distance_to_shore_many() {
+        pyo3::prepare_freethreaded_python();
+        pyo3::Python::with_gil(|py| {
+            let mask = RoaringLandmask::new(py).unwrap();
+
+            let points = (0..100)
+                .map(|i| (4. + i as f64 * 0.1, 65.6))
+                .collect::<Vec<_>>();
+            let d = mask.distance_to_shore_many_blocking(&points);
+
+            for ((x, y), d) in points.iter().zip(d) {
+                assert!((d - mask.distance_to_shore(*x, *y)).abs() < 1e-6);
+            }
+        })
+    }
+
+    #[test]
+    fn test_contains_slice() {
+        pyo3::prepare_freethreaded_python();
+        pyo3::Python::with_gil(|py| {
+            let mask = RoaringLandmask::new(py).unwrap();
+
+            let x = [15., 5., 10., -180.];
+            let y = [65.6, 65.6, 60., -90.];
+            let expected = vec![true, false, true, true];
+
+            assert_eq!(mask.contains_slice(&x, &y), expected);
+            assert_eq!(mask.contains_slice_par(&x, &y), expected);
+
+            let mask = mask.with_out_of_domain(OutOfDomain::Error);
+            assert!(mask.try_contains_slice_par(&[0.], &[95.]).is_err());
+        })
+    }
+
+    #[test]
+    fn test_shared() {
+        pyo3::prepare_freethreaded_python();
+        pyo3::Python::with_gil(|py| {
+            preload_shared(py).unwrap();
+
+            let a = RoaringLandmask::new(py).unwrap();
+            let mut b = RoaringLandmask::new(py).unwrap();
+
+            assert!(std::sync::Arc::ptr_eq(&a.mask.tmap, &b.mask.tmap));
+            assert!(std::sync::Arc::ptr_eq(&a.shapes.inner, &b.shapes.inner));
+
+            b.close().unwrap();
+            assert!(a.contains(15., 65.6));
+        })
+    }
+
+    #[test]
+    fn shared_released() {
+        use geos::Geometry;
+
+        let g = Geometry::new_from_wkt("POLYGON((0 0, 10 0, 10 10, 0 10, 0 0))").unwrap();
+        let shapes = Gshhg::from_geom(g).unwrap();
+        let load = || Ok((RoaringMask::from_shapes(&shapes), shapes.clone()));
+
+        let slot = Mutex::new(Weak::new());
+        let a = shared_in(&slot, load).unwrap();
+        let b = shared_in(&slot, || panic!("loaded twice")).unwrap();
+        assert!(Arc::ptr_eq(&a, &b));
+
+        // The data is freed when the last landmask holding it is closed.
+        let landmask = |shared: Arc<SharedData>| {
+            let mut landmask = RoaringLandmask::from_parts(shared.0.clone(), shared.1.clone());
+            landmask.shared = Some(shared);
+            landmask
+        };
+        let (mut a, mut b) = (landmask(a), landmask(b));
+        let tmap = Arc::downgrade(&a.mask.tmap);
+
+        a.close().unwrap();
+        assert!(b.contains(5., 5.));
+        assert!(tmap.upgrade().is_some());
+
+        b.close().unwrap();
+        assert!(tmap.upgrade().is_none());
+        assert!(slot.lock().unwrap().upgrade().is_none());
+
+        let mut loaded = false;
+        shared_in(&slot, || {
+            loaded = true;
+            load()
+        })
+        .unwrap();
+        assert!(loaded);
+    }
+
+    #[test]
+    fn test_query_stats() {
+        pyo3::prepare_freethreaded_python();
+        pyo3::Python::with_gil(|py| {
+            let mask = RoaringLandmask::new(py).unwrap();
+
+            let x = [15., 5., 10., -180., 5.];
+            let y = [65.6, 65.6, 60., -90., 65.6];
+
+            let stats = QueryStats::new();
+            let contains = mask.try_contains_slice_stats(&x, &y, Some(&stats)).unwrap();
+            assert_eq!(contains, mask.contains_slice(&x, &y));
+
+            assert_eq!(stats.total(), 5);
+            assert_eq!(stats.rejected_by_mask(), 2);
+            assert_eq!(stats.passed_mask(), 2);
+            assert_eq!(stats.land_by_shapes(), 2);
+            assert_eq!(stats.ocean_by_shapes(), 0);
+
+            mask.try_contains_slice_par_stats(&x, &y, Some(&stats))
+                .unwrap();
+            assert_eq!(stats.total(), 10);
+            assert_eq!(stats.rejected_by_mask(), 4);
+
+            stats.reset();
+            assert_eq!(stats.total(), 0);
+        })
+    }
+
+    #[test]
+    fn test_deduplicated() {
+        let x = [1., 2., 1., 1., 3.];
+        let y = [0., 0., 0., 1., 0.];
+
+        let mut unique = 0;
+        let sums = deduplicated(&x, &y, |x, y| {
+            unique = x.len();
+            Ok::<_, ()>(x.iter().zip(y).map(|(x, y)| x + 10. * y).collect())
+        })
+        .unwrap();
+
+        assert_eq!(unique, 4);
+        assert_eq!(sums, vec![1., 2., 1., 11., 3.]);
+    }
+
+    #[test]
+    fn test_contains_grid_coarse() {
+        pyo3::prepare_freethreaded_python();
+        pyo3::Python::with_gil(|py| {
+            let mask = RoaringLandmask::new(py).unwrap();
+
+            let (grid, lons, lats) = mask
+                .contains_grid_coarse(py, -180., 180., -90., 90., 10.)
+                .unwrap();
+            let grid = grid.as_ref(py).readonly();
+            let grid = grid.as_array();
+            assert_eq!(grid.shape(), &[19, 37]);
+            assert_eq!(lons.as_ref(py).len(), 37);
+            assert_eq!(lats.as_ref(py).readonly().as_array()[18], 90.);
+
+            // 10E, 60N and the poles.
+            assert!(grid[[15, 19]]);
+            assert!(grid[[0, 0]]);
+            assert!(!grid[[18, 0]]);
+
+            assert!(mask.contains_grid_coarse(py, 0., 10., 0., 10., 0.).is_err());
+            assert!(mask
+                .contains_grid_coarse(py, 0., 10., 0., 100., 1.)
+                .is_err());
+        })
+    }
+
+    #[test]
+    fn test_effective_resolution_m() {
+        let mask = RoaringLandmask::from_parts(RoaringMask::default(), Gshhg::empty().unwrap());
+
+        assert!((mask.effective_resolution_m(0.) - 463.3).abs() < 0.1);
+        assert_eq!(
+            mask.effective_resolution_m(60.),
+            mask.effective_resolution_m(0.)
+        );
+        assert_eq!(
+            mask.effective_resolution_m(-80.),
+            mask.mask.cell_size_m(-80.).1
+        );
+    }
+
+    #[test]
+    fn test_classify_regions() {
+        pyo3::prepare_freethreaded_python();
+        pyo3::Python::with_gil(|py| {
+            let mask = RoaringLandmask::new(py).unwrap();
+
+            // Two overlapping boxes in the Norwegian Sea, the second reaching onto Norway.
+            let regions = [
+                "POLYGON((4 65, 6 65, 6 66, 4 66, 4 65))",
+                "POLYGON((5 65, 16 65, 16 66, 5 66, 5 65))",
+            ]
+            .map(|wkt| Gshhg::from_geom(geos::Geometry::new_from_wkt(wkt).unwrap()).unwrap());
+
+            let x = [5., 5.5, 15., 10., 0.];
+            let y = [65.6, 65.6, 65.6, 60., 65.6];
+            let expected = vec![0, 0, 1, LAND, OCEAN];
+
+            let classes = x
+                .iter()
+                .zip(&y)
+                .map(|(x, y)| mask.classify_regions(*x, *y, &regions))
+                .collect::<Vec<_>>();
+            assert_eq!(classes, expected);
+            assert_eq!(mask.classify_regions_slice(&x, &y, &regions), expected);
+
+            assert_eq!(mask.classify_regions(15., 65.6, &[]), LAND);
+
+            // The regions do not cover the poles, or points across the antimeridian.
+            assert_eq!(mask.classify_regions(0., -90., &regions), LAND);
+            assert_eq!(mask.classify_regions(0., 90., &regions), OCEAN);
+            assert_eq!(mask.classify_regions(365., 65.6, &regions), 0);
+        })
+    }
+
+    #[test]
+    fn test_level() {
+        pyo3::prepare_freethreaded_python();
+        pyo3::Python::with_gil(|py| {
+            let mask = RoaringLandmask::new(py).unwrap();
+
+            assert_eq!(mask.level(15., 65.6), 1);
+            assert_eq!(mask.level(5., 65.6), 0);
+            assert_eq!(mask.level(0., 90.), 0);
+
+            let mask = mask.with_sampling(Sampling::Corner);
+            for x in (0..2400).map(|i| 5. + i as f64 * 0.00416) {
+                assert_eq!(mask.level(x, 65.6), mask.contains(x, 65.6) as u8);
+            }
+        })
+    }
+
+    #[test]
+    fn test_contains_with_extra() {
+        pyo3::prepare_freethreaded_python();
+        pyo3::Python::with_gil(|py| {
+            let mask = RoaringLandmask::new(py).unwrap();
+            let zone =
+                geos::Geometry::new_from_wkt("POLYGON((4 65, 6 65, 6 66, 4 66, 4 65))").unwrap();
+            let extra = Gshhg::from_geom(zone).unwrap();
+
+            assert!(mask.contains_with_extra(15., 65.6, &extra));
+            assert!(mask.contains_with_extra(5., 65.6, &extra));
+            assert!(!mask.contains(5., 65.6));
+            assert!(!mask.contains_with_extra(3., 65.6, &extra));
+        })
+    }
+
+    #[test]
+    fn test_contains_rad() {
+        pyo3::prepare_freethreaded_python();
+        pyo3::Python::with_gil(|py| {
+            let mask = RoaringLandmask::new(py).unwrap();
+
+            assert!(mask.contains_rad(15f64.to_radians(), 65.6f64.to_radians()));
+            assert!(!mask.contains_rad(5f64.to_radians(), 65.6f64.to_radians()));
+
+            assert!(!mask.contains_rad(0., std::f64::consts::FRAC_PI_2));
+            assert!(mask.contains_rad(0., -std::f64::consts::FRAC_PI_2));
+            assert!(mask.contains_rad(0., 90f64.to_radians()) == mask.contains(0., 90.));
+            assert!(mask.contains_rad(0., -90f64.to_radians()) == mask.contains(0., -90.));
+
+            // Beyond the South Pole is outside the landmask, not on land.
+            let mask = mask.with_out_of_domain(OutOfDomain::ReturnFalse);
+            assert!(!mask.contains_rad(0., -1.6));
+            assert!(!mask.contains_rad(0., 1.6));
+        })
+    }
+
+    #[test]
+    fn test_close() {
+        pyo3::prepare_freethreaded_python();
+        pyo3::Python::with_gil(|py| {
+            let mut mask = RoaringLandmask::new(py).unwrap();
+            assert!(mask.contains(15., 65.6));
+
+            mask.close().unwrap();
+            assert!(mask.closed());
+            assert!(mask.py_contains(15., 65.6).is_err());
+
+            // The Rust queries report that the landmask is closed rather than answer.
+            let err = mask.try_contains(15., 65.6).unwrap_err();
+            assert_eq!(err.to_string(), "RoaringLandmask is closed");
+            assert!(mask.try_contains_slice(&[15.], &[65.6]).is_err());
+            assert!(mask.try_level(15., 65.6).is_err());
+            assert!(mask.try_contains_explain(15., 65.6).is_err());
+            assert!(mask.try_contains_exact(15., 65.6).is_err());
+
+            let panicked =
+                std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| mask.contains(15., 65.6)));
+            assert!(panicked.is_err());
+        })
+    }
+
+    #[test]
+    fn distance_to_shore() {
+        pyo3::prepare_freethreaded_python();
+        pyo3::Python::with_gil(|py| {
+            let mask = RoaringLandmask::new(py).unwrap();
+
+            assert_eq!(mask.distance_to_shore(15., 65.6), 0.);
+
+            let d = mask.distance_to_shore(5., 65.6);
+            assert!(d > 10_000. && d < 1_000_000.);
+
+            assert_eq!(
+                mask.distance_to_shore_in(5., 65.6, Unit::Kilometers),
+                d / 1000.
+            );
+            assert_eq!(
+                mask.distance_to_shore_in(5., 65.6, Unit::NauticalMiles),
+                d / 1852.
+            );
+        })
+    }
+
+    #[test]
+    fn contains_with() {
+        pyo3::prepare_freethreaded_python();
+        pyo3::Python::with_gil(|py| {
+            let mask = RoaringLandmask::new(py)
+                .unwrap()
+                .with_out_of_domain(OutOfDomain::ReturnFalse);
+
+            // Sea ice north of 65 N counts as land.
+            let ice = |_x: f64, y: f64, land: bool| land || y > 65.;
+
+            assert!(mask.contains_with(5., 65.6, ice));
+            assert!(!mask.contains_with(5., 64., ice));
+            assert!(mask.contains_with(15., 64., ice));
+            assert!(!mask.contains_with(5., 95., ice));
+
+            let points = [(5., 65.6), (5., 64.), (15., 64.)];
+            assert_eq!(
+                mask.contains_many_with(&points, ice),
+                vec![true, false, true]
+            );
+            assert_eq!(
+                mask.contains_many_with(&points, |_, _, land| land),
+                mask.contains_many_blocking(&points)
+            );
+        })
+    }
+
+    #[test]
+    fn with_distance_cache() {
+        pyo3::prepare_freethreaded_python();
+        pyo3::Python::with_gil(|py| {
+            let mask = RoaringLandmask::new(py).unwrap();
+            let points = (0..100)
+                .map(|i| (4. + i as f64 * 0.01, 59. + i as f64 * 0.003))
+                .collect::<Vec<_>>();
+            let expected = mask.distance_to_shore_many_blocking(&points);
+
+            let mask = mask.with_distance_cache(true);
+            assert_eq!(mask.distance_to_shore_many_blocking(&points), expected);
+            for (&(x, y), d) in points.iter().zip(&expected) {
+                assert_eq!(mask.distance_to_shore(x, y), *d);
+            }
+        })
+    }
+
+    #[test]
+    fn signed_distance_to_shore() {
+        pyo3::prepare_freethreaded_python();
+        pyo3::Python::with_gil(|py| {
+            let mask = RoaringLandmask::new(py).unwrap();
+
+            let ocean = mask.signed_distance_to_shore(5., 65.6);
+            assert_eq!(ocean, mask.distance_to_shore(5., 65.6));
+            assert!(ocean > 10_000.);
+
+            // Inland Sweden is far from the coast.
+            let land = mask.signed_distance_to_shore(15., 65.6);
+            assert!(land < -10_000. && land > -1_000_000., "{land}");
+
+            let points = [(5., 65.6), (15., 65.6), (15., 65.6), (-123.4, -48.9)];
+            let many = mask.signed_distance_to_shore_many_blocking(&points);
+            assert_eq!(&many[..3], &[ocean, land, land]);
+            assert!(many[3] > 2_000_000.);
+
+            // Across an edge of the coastline near Bergen, about 20 m to either side.
+            let ((a, b), _) = mask.shapes.nearest_edge(5.32, 60.39, 0.1).unwrap();
+            let (mx, my) = ((a.0 + b.0) / 2., (a.1 + b.1) / 2.);
+            let k = my.to_radians().cos();
+            let (ex, ey) = ((b.0 - a.0) * k, b.1 - a.1);
+            let n = ex.hypot(ey) / 0.0002;
+            let (nx, ny) = (-ey / n / k, ex / n);
+
+            let sides = [(mx - nx, my - ny), (mx + nx, my + ny)];
+            assert_ne!(
+                mask.contains(sides[0].0, sides[0].1),
+                mask.contains(sides[1].0, sides[1].1)
+            );
+
+            for (x, y) in sides {
+                let d = mask.signed_distance_to_shore(x, y);
+                // No further than the edge, another may be closer.
+                assert!(d != 0. && d.abs() < 23., "{d}");
+                assert_eq!(d < 0., mask.contains(x, y));
+            }
+            assert_eq!(
+                mask.signed_distance_to_shore_many_blocking(&sides),
+                sides.map(|(x, y)| mask.signed_distance_to_shore(x, y))
+            );
+        })
+    }
+
+    #[test]
+    fn diff() {
+        use geos::Geometry;
+        use mask::NX;
+        use roaring::RoaringTreemap;
+
+        let landmask = |east: f64, cells: u64| {
+            let mut tmap = RoaringTreemap::new();
+            for y in 34560..34800 {
+                tmap.insert_range(y * NX + 45600..y * NX + 45600 + cells);
+            }
+
+            let g = Geometry::new_from_wkt(&format!(
+                "POLYGON((10 54, {east} 54, {east} 55, 10 55, 10 54))"
+            ))
+            .unwrap();
+
+            RoaringLandmask::from_parts(RoaringMask::from(tmap), Gshhg::from_geom(g).unwrap())
+        };
+
+        // The coast moved half a degree east.
+        let (a, b) = (landmask(11., 240), landmask(11.5, 360));
+
+        let diff = a.diff(&b, (9., 53., 12., 56.));
+        assert!(!diff.is_empty());
+        for (x, y) in &diff {
+            assert!((11. ..11.5).contains(x) && (54. ..55.).contains(y));
+        }
+        assert_eq!(b.diff(&a, (9., 53., 12., 56.)).len(), diff.len());
+
+        assert!(a.diff(&a, (9., 53., 12., 56.)).is_empty());
+        assert!(a.diff(&b, (179., 53., -179., 56.)).is_empty());
+        assert!(a.diff(&b, (11.6, 53., 12., 56.)).is_empty());
+    }
+
+    #[test]
+    fn overlay() {
+        use geos::Geometry;
+
+        let landmask = |wkt: &str| {
+            let shapes = Gshhg::from_geom(Geometry::new_from_wkt(wkt).unwrap()).unwrap();
+            let mask = RoaringMask::from_shapes(&shapes);
+            RoaringLandmask::from_parts(mask, shapes)
+        };
+
+        // The coast at 11 E in the base and at 11.5 E in the region, and an island outside the
+        // region.
+        let base = landmask(
+            "MULTIPOLYGON(((10 54, 11 54, 11 55, 10 55, 10 54)), ((20 54, 21 54, 21 55, 20 54)))",
+        )
+        .with_out_of_domain(OutOfDomain::ReturnFalse);
+        let region = landmask("POLYGON((10 54, 11.5 54, 11.5 55, 10 55, 10 54))");
+
+        let mut base = base;
+        base.load_zones(
+            Gshhg::from_geom(
+                Geometry::new_from_wkt("POLYGON((4 65, 6 65, 6 66, 4 66, 4 65))").unwrap(),
+            )
+            .unwrap(),
+            "zone".into(),
+        );
+
+        let merged = base.overlay(&region, (10.5, 53., 12., 56.)).unwrap();
+        assert!(merged.contains(10.2, 54.5));
+        assert!(merged.contains(11.2, 54.5));
+        assert!(!merged.contains(11.7, 54.5));
+        assert!(merged.contains(20.8, 54.2));
+        assert_eq!(merged.out_of_domain, OutOfDomain::ReturnFalse);
+        assert_eq!(merged.zone_of(5., 65.6), Some("zone"));
+        assert_eq!(merged.shapes.polygons(), 2);
+
+        // Along the seam the side of the point decides.
+        let merged = region.overlay(&base, (10.5, 53., 12., 56.)).unwrap();
+        assert!(merged.contains(10.9, 54.5));
+        assert!(!merged.contains(11.2, 54.5));
+        assert!(!merged.contains(20.8, 54.2));
+
+        assert!(base.overlay(&region, (12., 53., 10.5, 56.)).is_err());
+        assert!(base.overlay(&region, (170., 53., 190., 56.)).is_err());
+    }
+
+    #[test]
+    fn with_generalization() {
+        use geos::Geometry;
+
+        // The mask rasterized from the shapes covers their land.
+        let g = Geometry::new_from_wkt("POLYGON((10 54, 11 54, 10.5 55, 10 54))").unwrap();
+        let shapes = Gshhg::from_geom(g).unwrap();
+        let landmask = RoaringLandmask::from_parts(RoaringMask::from_shapes(&shapes), shapes);
+
+        for (x, y) in [(10.5, 54.01), (10.5, 54.99), (10.01, 54.01), (10.7, 54.5)] {
+            assert!(landmask.contains(x, y), "{x}, {y}");
+        }
+        assert!(!landmask.contains(10.1, 54.9));
+        assert!(!landmask.mask.contains(10.1, 54.9));
+
+        // Replacing the shapes rasterizes the mask again.
+        let empty = RoaringLandmask::from_parts(
+            RoaringMask::from(roaring::RoaringTreemap::new()),
+            Gshhg::empty().unwrap(),
+        );
+        assert!(!empty.contains(10.5, 54.5));
+
+        let replaced = empty.with_shapes(landmask.shapes.clone());
+        assert_eq!(replaced.mask.tmap, landmask.mask.tmap);
+        for (x, y) in [(10.5, 54.01), (10.5, 54.99), (10.01, 54.01), (10.7, 54.5)] {
+            assert!(replaced.contains(x, y), "{x}, {y}");
+        }
+
+        pyo3::prepare_freethreaded_python();
+        pyo3::Python::with_gil(|py| {
+            assert!(RoaringLandmask::with_generalization(py, -1.).is_err());
+        })
+    }
+
+    #[test]
+    fn coverage_sampling() {
+        use geos::Geometry;
+
+        // An isthmus about 2 km wide, north to south through the middle of the area.
+        let g =
+            Geometry::new_from_wkt("POLYGON((10.02 50, 10.05 50, 10.05 60, 10.02 60, 10.02 50))")
+                .unwrap();
+        let shapes = Gshhg::from_geom(g).unwrap();
+        let landmask = RoaringLandmask::from_parts(RoaringMask::from_shapes(&shapes), shapes);
+
+        let bbox = (9., 54., 11., 56.);
+        let ring = [(9., 54.), (11., 54.), (11., 56.), (9., 56.), (9., 54.)];
+
+        // With 4 samples along each side they are half a degree apart, and all miss the isthmus.
+        assert_eq!(landmask.land_fraction(bbox, 4), 0.);
+        assert_eq!(landmask.classify_polygon(&ring, 4), Coverage::Ocean);
+
+        let f = landmask.land_fraction(bbox, 64);
+        assert!(f > 0. && f < 0.05, "{f}");
+        assert_eq!(landmask.classify_polygon(&ring, 64), Coverage::Mixed);
+
+        assert_eq!(
+            landmask.classify_polygon(
+                &[(10.03, 55.), (10.04, 55.), (10.03, 55.1), (10.03, 55.)],
+                4
+            ),
+            Coverage::Land
+        );
+
+        // Across the antimeridian.
+        assert_eq!(landmask.land_fraction((179., -1., -179., 1.), 8), 0.);
+        assert!(landmask.land_fraction(bbox, 0).is_nan());
+    }
+
+    #[test]
+    fn bbox_is_all_ocean() {
+        pyo3::prepare_freethreaded_python();
+        pyo3::Python::with_gil(|py| {
+            let mask = RoaringLandmask::new(py).unwrap();
+
+            // The South Pacific, and the Norwegian Sea with the coast of Norway.
+            assert!(mask.bbox_is_all_ocean((-130., -50., -120., -40.)));
+            assert!(!mask.bbox_is_all_ocean((0., 60., 10., 70.)));
+            assert!(mask.bbox_is_all_ocean((0., 62., 2., 64.)));
+
+            // The North Pacific across the antimeridian, and with the Aleutians.
+            assert!(mask.bbox_is_all_ocean((175., 40., -175., 45.)));
+            assert!(!mask.bbox_is_all_ocean((175., 40., -175., 55.)));
+
+            // A box with no land in it is never land.
+            let (x, y) = grid_samples((-130., -50., -120., -40.), 50)
+                .into_iter()
+                .unzip::<_, _, Vec<_>, Vec<_>>();
+            assert!(!mask.try_contains_slice(&x, &y).unwrap().contains(&true));
+        })
+    }
+
+    #[test]
+    fn land_weight() {
+        use geos::Geometry;
+
+        let g = Geometry::new_from_wkt("POLYGON((10 50, 11 50, 11 60, 10 60, 10 50))").unwrap();
+        let shapes = Gshhg::from_geom(g).unwrap();
+        let landmask = RoaringLandmask::from_parts(RoaringMask::from_shapes(&shapes), shapes);
+
+        assert_eq!(landmask.land_weight(9.5, 55.), 0.);
+        assert_eq!(landmask.land_weight(10.5, 55.), 1.);
+
+        // The coast runs through the middle of the cell centered on it.
+        let w = landmask.land_weight(10., 55.);
+        assert!((w - 0.5).abs() < 1e-6, "{w}");
+
+        // Across the coast the weight rises smoothly over about a cell.
+        let weights = (0..=100)
+            .map(|i| landmask.land_weight(9.99 + i as f64 * 0.0002, 55.3))
+            .collect::<Vec<_>>();
+        assert_eq!(weights[0], 0.);
+        assert_eq!(weights[100], 1.);
+        assert!(weights.windows(2).all(|w| w[0] <= w[1]));
+        assert!(weights.iter().filter(|w| **w > 0. && **w < 1.).count() > 5);
+
+        assert_eq!(landmask.land_weight(370.5, 55.), 1.);
+        assert_eq!(landmask.land_weight(10.5, 90.), 0.);
+        assert!(landmask.land_weight(10.5, 91.).is_nan());
+
+        let points = [(9.5, 55.), (10., 55.), (10.5, 55.)];
+        assert_eq!(
+            landmask.land_weight_many_blocking(&points),
+            points
+                .iter()
+                .map(|(x, y)| landmask.land_weight(*x, *y))
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn nearest_coast_segment() {
+        pyo3::prepare_freethreaded_python();
+        pyo3::Python::with_gil(|py| {
+            let mask = RoaringLandmask::new(py).unwrap();
+
+            // From the Norwegian Sea the segment is about as far as the shore.
+            let (a, b) = mask.nearest_coast_segment(5., 65.6).unwrap();
+            let d = distance::haversine(5., 65.6, a.0, a.1)
+                .min(distance::haversine(5., 65.6, b.0, b.1));
+            let shore = mask.distance_to_shore(5., 65.6);
+            assert!(a.0 > 5. && b.0 > 5.);
+            assert!(d >= shore - 1000. && d < shore + 50_000., "{d} {shore}");
+
+            // From inland Norway.
+            let (a, _) = mask.nearest_coast_segment(15., 65.6).unwrap();
+            assert!(a.0 > 5. && a.0 < 20.);
+        })
+    }
+
+    #[test]
+    fn contains_major() {
+        use geos::Geometry;
+
+        // A large square, and a small island next to it.
+        let g = Geometry::new_from_wkt(
+            "MULTIPOLYGON(((0 0, 20 0, 20 20, 0 20, 0 0)), ((21 0, 22 0, 22 1, 21 0)))",
+        )
+        .unwrap();
+        let shapes = Gshhg::from_geom(g).unwrap();
+        let mask = RoaringMask::from_shapes(&shapes);
+        let landmask = RoaringLandmask::from_parts(mask, shapes);
+
+        assert!(landmask.contains(21.9, 0.5));
+        assert!(landmask.contains_major(10., 10.));
+        assert!(!landmask.contains_major(21.9, 0.5));
+        assert!(!landmask.contains_major(30., 10.));
+
+        let landmask = landmask.with_major_landmasses(1000.);
+        assert!(landmask.contains_major(21.9, 0.5));
+    }
+
+    #[test]
+    fn contains_int() {
+        pyo3::prepare_freethreaded_python();
+        pyo3::Python::with_gil(|py| {
+            let mask = RoaringLandmask::new(py).unwrap();
+            let table = mask.integer_grid_table();
+            assert_eq!(table.len(), 360 * 181);
+
+            for lat in -90..=90 {
+                for lon in (-180..180).step_by(7) {
+                    let land = mask.contains(lon as f64, lat as f64);
+                    assert_eq!(mask.contains_int(lon, lat), land, "{lon}, {lat}");
+                    assert_eq!(
+                        table[(lat + 90) as usize * 360 + (lon + 180) as usize],
+                        land
+                    );
+                }
+            }
+
+            assert!(mask.contains_int(15, 65));
+            assert!(!mask.contains_int(5, 65));
+            assert_eq!(mask.contains_int(180, 0), mask.contains_int(-180, 0));
+            assert_eq!(mask.contains_int(375, 65), mask.contains_int(15, 65));
+            assert!(mask.contains_int(0, -90));
+            assert!(!mask.contains_int(0, 90));
+
+            // Changing the settings from Python drops the table.
+            let mut mask = mask;
+            assert!(mask.integer_grid.get().is_some());
+            mask.set_sampling(Sampling::Corner);
+            assert!(mask.integer_grid.get().is_none());
+            assert!(mask.contains_int(15, 65));
+            mask.set_enclosed_water(EnclosedWater::AsOcean);
+            assert!(mask.integer_grid.get().is_none());
+        })
+    }
+
+    #[test]
+    fn within_distance_many() {
+        pyo3::prepare_freethreaded_python();
+        pyo3::Python::with_gil(|py| {
+            let mask = RoaringLandmask::new(py).unwrap();
+
+            let d = mask.distance_to_shore(5., 65.6) / 1000.;
+            let points = [(15., 65.6), (5., 65.6), (5., 65.6), (5., 65.6)];
+            let radii = [0., d - 1., d + 1., f64::NAN];
+
+            assert_eq!(
+                mask.within_distance_many_blocking(&points, &radii),
+                vec![true, false, true, false]
+            );
+        })
+    }
+
+    #[test]
+    fn circle_intersects_land() {
+        pyo3::prepare_freethreaded_python();
+        pyo3::Python::with_gil(|py| {
+            let mask = RoaringLandmask::new(py).unwrap();
+
+            let d = mask.distance_to_shore(5., 65.6) / 1000.;
+            assert!(mask.circle_intersects_land(15., 65.6, 0.));
+            assert!(!mask.circle_intersects_land(5., 65.6, d - 1.));
+            assert!(mask.circle_intersects_land(5., 65.6, d + 1.));
+
+            // Point Nemo is more than 2600 km from the nearest land.
+            assert!(!mask.circle_intersects_land(-123.4, -48.9, 2000.));
+
+            let points = [(15., 65.6), (5., 65.6), (-123.4, -48.9)];
+            assert_eq!(
+                mask.circle_intersects_land_many_blocking(&points, d + 1.),
+                vec![true, true, false]
+            );
+        })
+    }
+
+    #[test]
+    fn contains_and_distance() {
+        pyo3::prepare_freethreaded_python();
+        pyo3::Python::with_gil(|py| {
+            let mask = RoaringLandmask::new(py).unwrap();
+
+            let points = [(15., 65.6), (5., 65.6), (-123.4, -48.9)];
+            let (land, distances) = mask.contains_and_distance_many_blocking(&points);
+
+            for (i, &(x, y)) in points.iter().enumerate() {
+                let (l, d) = mask.contains_and_distance(x, y);
+                assert_eq!(l, land[i]);
+                assert_eq!(l, mask.contains(x, y));
+                assert_eq!(d, mask.distance_to_shore(x, y));
+                assert!((d - distances[i]).abs() < 1e-6);
+            }
+
+            assert_eq!(land, vec![true, false, false]);
+        })
+    }
+
+    #[test]
+    fn zones() {
+        use geos::Geometry;
+
+        pyo3::prepare_freethreaded_python();
+        pyo3::Python::with_gil(|py| {
+            let mut mask = RoaringLandmask::new(py).unwrap();
+            let zone = |wkt: &str| Gshhg::from_geom(Geometry::new_from_wkt(wkt).unwrap()).unwrap();
+
+            // A narrow buffer along the coast inside a wider one.
+            mask.load_zones(
+                zone("POLYGON((4 65, 6 65, 6 66, 4 66, 4 65))"),
+                "1 km".into(),
+            );
+            mask.load_zones(
+                zone("POLYGON((0 64, 8 64, 8 67, 0 67, 0 64))"),
+                "12 nm".into(),
+            );
+            assert_eq!(mask.zone_labels(), vec!["1 km", "12 nm"]);
+
+            assert_eq!(mask.zone_of(5., 65.6), Some("1 km"));
+            assert_eq!(mask.zone_of(1., 65.6), Some("12 nm"));
+            assert_eq!(mask.zone_of(-10., 65.6), None);
+            assert_eq!(mask.zone_of(5., 95.), None);
+            assert_eq!(mask.zone_of(365., 65.6), Some("1 km"));
+            assert_eq!(mask.zone_of(5., -90.), None);
+
+            assert_eq!(
+                mask.zone_of_many_blocking(&[(5., 65.6), (1., 65.6), (-10., 65.6)]),
+                vec![Some("1 km"), Some("12 nm"), None]
+            );
+
+            mask.close().unwrap();
+            assert!(mask.zone_labels().is_empty());
+        })
+    }
+
+    #[test]
+    fn with_earth() {
+        pyo3::prepare_freethreaded_python();
+        pyo3::Python::with_gil(|py| {
+            let sphere = RoaringLandmask::new(py).unwrap();
+            let wgs84 = RoaringLandmask::new(py).unwrap().with_earth(Earth::Wgs84);
+
+            // At high latitudes the sphere is too short by a few tenths of a percent.
+            let (d, e) = (
+                sphere.distance_to_shore(5., 65.6),
+                wgs84.distance_to_shore(5., 65.6),
+            );
+            assert!(d / e > 0.994 && d / e < 0.999, "{d} {e}");
+
+            let (x, y, _) = sphere.mask.nearest_land(5., 65.6, f64::INFINITY).unwrap();
+            assert_eq!(e, distance::vincenty(5., 65.6, x, y));
+
+            let points = [(15., 65.6), (5., 65.6), (-123.4, -48.9)];
+            let many = wgs84.distance_to_shore_many_blocking(&points);
+            assert_eq!(many[0], 0.);
+            assert_eq!(many[1], e);
+            assert!(wgs84.signed_distance_to_shore(15., 65.6) < 0.);
+
+            let f = wgs84.land_time_fraction(&[(5., 65.6), (15., 65.6)]);
+            assert!((f - sphere.land_time_fraction(&[(5., 65.6), (15., 65.6)])).abs() < 0.01);
+        })
+    }
+
+    #[test]
+    fn nearest_land_many() {
+        pyo3::prepare_freethreaded_python();
+        pyo3::Python::with_gil(|py| {
+            let mask = RoaringLandmask::new(py).unwrap();
+
+            let x = PyArray::from_vec(py, vec![15., 5., 5.]).to_dyn();
+            let y = PyArray::from_vec(py, vec![65.6, 65.6, 65.6]).to_dyn();
+
+            let (nx, ny) = mask
+                .nearest_land_many(py, x.readonly(), y.readonly(), 1_000_000.)
+                .unwrap();
+            let nx = nx.as_ref(py).to_vec().unwrap();
+            let ny = ny.as_ref(py).to_vec().unwrap();
+
+            assert_eq!((nx[0], ny[0]), (15., 65.6));
+            assert!(nx[1] > 5. && nx[1] < 15.);
+            assert!(mask.mask.contains(nx[1], ny[1]));
+
+            let (nx, ny) = mask
+                .nearest_land_many(py, x.readonly(), y.readonly(), 1000.)
+                .unwrap();
+            assert!(nx.as_ref(py).to_vec().unwrap()[1].is_nan());
+            assert!(ny.as_ref(py).to_vec().unwrap()[2].is_nan());
+        })
+    }
+
+    #[test]
+    fn crossed_onto_land() {
+        pyo3::prepare_freethreaded_python();
+        pyo3::Python::with_gil(|py| {
+            let mask = RoaringLandmask::new(py).unwrap();
+
+            // From the Norwegian Sea onto Norway.
+            let (x, y) = mask.crossed_onto_land((5., 65.6), (15., 65.6)).unwrap();
+            assert!(x > 5. && x < 15.);
+            assert!(mask.contains(x, y));
+
+            // Already on land.
+            assert_eq!(
+                mask.crossed_onto_land((15., 65.6), (5., 65.6)),
+                Some((15., 65.6))
+            );
+
+            // Across the dateline in the Pacific.
+            assert_eq!(mask.crossed_onto_land((179., 0.5), (-179., 0.5)), None);
+        })
+    }
+
+    #[test]
+    fn first_landfall() {
+        pyo3::prepare_freethreaded_python();
+        pyo3::Python::with_gil(|py| {
+            let mask = RoaringLandmask::new(py).unwrap();
+
+            // East from the Norwegian Sea onto Norway.
+            let (x, y) = mask.first_landfall((5., 65.6), 90., 1000.).unwrap();
+            assert!(x > 5. && x < 15.);
+            assert!(mask.contains(x, y));
+
+            // Already on land.
+            assert_eq!(mask.first_landfall((15., 65.6), 0., 10.), Some((15., 65.6)));
+
+            // Not far enough, and across the dateline in the Pacific.
+            assert_eq!(mask.first_landfall((5., 65.6), 90., 10.), None);
+            assert_eq!(mask.first_landfall((179., 0.5), 90., 200.), None);
+        })
+    }
+
+    #[test]
+    fn distance_contours() {
+        use geos::Geom;
+        use mask::NX;
+        use roaring::RoaringTreemap;
+
+        // An island of one by one degree on the equator.
+        let mut tmap = RoaringTreemap::new();
+        for y in 21600..21840 {
+            tmap.insert_range(y * NX + 43200..y * NX + 43440);
+        }
+        let mask = RoaringLandmask::from_parts(RoaringMask::from(tmap), Gshhg::empty().unwrap());
+
+        let contours = mask
+            .distance_contours(&[10., -10., 200.], (-1., -1., 2., 2.))
+            .unwrap();
+        assert_eq!(contours.len(), 3);
+        assert_eq!(contours[2].get_num_geometries().unwrap(), 0);
+
+        for (contour, level) in contours.iter().zip([10., -10.]) {
+            assert_eq!(contour.get_num_geometries().unwrap(), 1);
+
+            let cs = contour.get_geometry_n(0).unwrap().get_coord_seq().unwrap();
+            assert!(cs.size().unwrap() > 100);
+
+            for i in 0..cs.size().unwrap() {
+                let (x, y) = (cs.get_x(i).unwrap(), cs.get_y(i).unwrap());
+
+                // Signed distance to the island in km.
+                let d = if level > 0. {
+                    let dx = (-x).max(x - 1.).max(0.);
+                    let dy = (-y).max(y - 1.).max(0.);
+                    dx.hypot(dy) * 111.2
+                } else {
+                    -x.min(1. - x).min(y).min(1. - y) * 111.2
+                };
+                assert!((d - level).abs() < 1., "{} at ({}, {})", d, x, y);
+            }
+        }
+
+        assert!(mask.distance_contours(&[1.], (0., 1., 1., 0.)).is_err());
+    }
+
+    #[test]
+    fn land_time_fraction() {
+        pyo3::prepare_freethreaded_python();
+        pyo3::Python::with_gil(|py| {
+            let mask = RoaringLandmask::new(py).unwrap();
+
+            // From the Norwegian Sea onto Norway.
+            let f = mask.land_time_fraction(&[(5., 65.6), (15., 65.6)]);
+            assert!(f > 0. && f < 1.);
+
+            // Back and forth along the same path.
+            let g = mask.land_time_fraction(&[(5., 65.6), (15., 65.6), (5., 65.6)]);
+            assert!((f - g).abs() < 1e-6);
+
+            // Across the dateline in the Pacific, and over Central Europe.
+            assert_eq!(mask.land_time_fraction(&[(179., 0.5), (-179., 0.5)]), 0.);
+            assert_eq!(mask.land_time_fraction(&[(20., 50.), (22., 50.)]), 1.);
+
+            assert!(mask.land_time_fraction(&[(5., 65.6)]).is_nan());
+        })
+    }
+
+    #[test]
+    fn classify_tracks() {
+        pyo3::prepare_freethreaded_python();
+        pyo3::Python::with_gil(|py| {
+            let mask = RoaringLandmask::new(py).unwrap();
+
+            // From the Norwegian Sea onto Norway and back out, and across the dateline in the
+            // Pacific.
+            let tracks = vec![
+                vec![(5., 65.6), (15., 65.6), (5., 65.6)],
+                vec![(179., 0.5), (-179., 0.5)],
+                vec![],
+            ];
+            let runs = mask.classify_tracks(&tracks, 1000.);
+            assert_eq!(runs.len(), 3);
+
+            let points = path::densify_track(&tracks[0], 1000.);
+            let first = &runs[0];
+            assert_eq!(first[0].1, 0);
+            assert_eq!(first.last().unwrap().2, points.len() - 1);
+            assert!(!first[0].0 && !first.last().unwrap().0);
+            assert!(first.iter().any(|r| r.0));
+
+            for w in first.windows(2) {
+                assert_ne!(w[0].0, w[1].0);
+                assert_eq!(w[0].2 + 1, w[1].1);
+            }
+            for &(land, a, b) in first {
+                for &(x, y) in &points[a..=b] {
+                    assert_eq!(mask.contains(x, y), land);
+                }
+            }
+
+            let n = path::densify_track(&tracks[1], 1000.).len();
+            assert_eq!(runs[1], vec![(false, 0, n - 1)]);
+            assert!(runs[2].is_empty());
+
+            // Over the North Pole.
+            let runs = mask.classify_track(&[(0., 89.9), (180., 89.9)], 1000.);
+            assert_eq!(runs.len(), 1);
+            assert!(!runs[0].0);
+        })
+    }
+
+    #[test]
+    fn contains_slice_to_mmap() {
+        pyo3::prepare_freethreaded_python();
+        pyo3::Python::with_gil(|py| {
+            let mask = RoaringLandmask::new(py).unwrap();
+            let path = std::env::temp_dir().join(format!(
+                "roaring-landmask-test-{}.contains",
+                std::process::id()
+            ));
+            std::fs::write(&path, [9; 8]).unwrap();
+
+            // The result goes at the offset, and the bytes around it are kept.
+            let x = [15., 5., 20.];
+            let y = [65.6, 65.6, 50.];
+            mask.contains_slice_to_mmap(&x, &y, &path, 3).unwrap();
+            assert_eq!(std::fs::read(&path).unwrap(), vec![9, 9, 9, 1, 0, 1, 9, 9]);
+
+            // A file which is too short is not grown.
+            let err = mask.contains_slice_to_mmap(&x, &y, &path, 6).err().unwrap();
+            assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+            assert_eq!(std::fs::metadata(&path).unwrap().len(), 8);
+
+            // A point outside the landmask leaves the file untouched.
+            let mask = mask.with_out_of_domain(OutOfDomain::Error);
+            let err = mask
+                .contains_slice_to_mmap(&[5., 0.], &[65.6, 100.], &path, 3)
+                .err()
+                .unwrap();
+            assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+            assert_eq!(std::fs::read(&path).unwrap(), vec![9, 9, 9, 1, 0, 1, 9, 9]);
+
+            std::fs::remove_file(&path).unwrap();
+        })
+    }
+
+    #[test]
+    fn parallel_same_as_serial() {
+        pyo3::prepare_freethreaded_python();
+        pyo3::Python::with_gil(|py| {
+            let mask = RoaringLandmask::new(py)
+                .unwrap()
+                .with_out_of_domain(OutOfDomain::ReturnFalse);
+
+            // Random points over the globe from a xorshift generator, half of them near the
+            // Norwegian coast, and the edge cases.
+            let mut state = 0x2545_f491_4f6c_dd1du64;
+            let mut random = || {
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+                (state >> 11) as f64 / (1u64 << 53) as f64
+            };
+
+            let (mut x, mut y) = (Vec::new(), Vec::new());
+            for i in 0..200_000 {
+                if i % 2 == 0 {
+                    x.push(-180. + 360. * random());
+                    y.push(-90. + 180. * random());
+                } else {
+                    x.push(4. + 2. * random());
+                    y.push(60. + 2. * random());
+                }
+            }
+            for (ex, ey) in [
+                (-180., 0.),
+                (180., 0.),
+                (180., -90.),
+                (0., 90.),
+                (f64::NAN, 0.),
+                (0., f64::NAN),
+                (0., 91.),
+                (540., 65.6),
+            ] {
+                x.push(ex);
+                y.push(ey);
+            }
+
+            let serial = mask.try_contains_slice(&x, &y).unwrap();
+            let parallel = mask.try_contains_slice_par(&x, &y).unwrap();
+            assert_eq!(serial, parallel);
+
+            let deduped = deduplicated(&x, &y, |x, y| mask.try_contains_slice_par(x, y)).unwrap();
+            assert_eq!(serial, deduped);
+
+            let stats = QueryStats::new();
+            let counted = mask
+                .try_contains_slice_par_stats(&x, &y, Some(&stats))
+                .unwrap();
+            assert_eq!(serial, counted);
+        })
+    }
+
+    #[test]
+    #[cfg(target_pointer_width = "64")]
+    fn index_array() {
+        let large = (1usize << 33) + 7;
+        assert_eq!(
+            super::index_array(vec![0, 1, large]),
+            vec![0, 1, large as i64]
+        );
+    }
+
+    #[test]
+    fn validate_coords() {
+        let x = [0., f64::NAN, 200., 10., f64::INFINITY, 0.];
+        let y = [0., 0., 45., 91., 0., -90.];
+        assert_eq!(RoaringLandmask::validate_coords(&x, &y), Err(vec![1, 3, 4]));
+
+        assert_eq!(RoaringLandmask::validate_coords(&x[..1], &y[..1]), Ok(()));
+        assert_eq!(RoaringLandmask::validate_coords(&[], &[]), Ok(()));
+    }
+
+    #[test]
+    fn same_landmass() {
+        pyo3::prepare_freethreaded_python();
+        pyo3::Python::with_gil(|py| {
+            let mask = RoaringLandmask::new(py).unwrap();
+
+            // Madrid and north of Oslo, but not London or New York.
+            let madrid = (-3.7, 40.4);
+            assert!(mask.same_landmass(madrid, (11.0, 60.0)));
+            assert!(!mask.same_landmass(madrid, (-0.1, 51.5)));
+            assert!(!mask.same_landmass(madrid, (-74., 40.7)));
+
+            // Chukotka across the antimeridian.
+            assert!(mask.same_landmass(madrid, (-177., 66.2)));
+
+            // The Norwegian Sea.
+            assert!(!mask.same_landmass((5., 65.6), (5., 65.6)));
+        })
+    }
+
+    #[test]
+    fn coastline_crossings() {
+        pyo3::prepare_freethreaded_python();
+        pyo3::Python::with_gil(|py| {
+            let mask = RoaringLandmask::new(py).unwrap();
+
+            assert_eq!(mask.coastline_crossings(&[]), 0);
+            assert_eq!(mask.coastline_crossings(&[(5., 65.6)]), 0);
+
+            // From the Norwegian Sea onto Norway, and back.
+            let n = mask.coastline_crossings(&[(5., 65.6), (15., 65.6)]);
+            assert_eq!(n % 2, 1);
+
+            let n = mask.coastline_crossings(&[(5., 65.6), (15., 65.6), (5., 65.6)]);
+            assert!(n >= 2 && n % 2 == 0);
+
+            // Across the dateline in the Pacific.
+            assert_eq!(
+                mask.coastline_crossings(&[(179., 0.5), (-179., 0.5), (-179., 1.5)]),
+                0
+            );
+        })
+    }
+
+    #[test]
+    fn buffer_corrects_mask() {
+        use mask::{NX, TRANSFORM};
+        use roaring::RoaringTreemap;
+
+        pyo3::prepare_freethreaded_python();
+        pyo3::Python::with_gil(|py| {
+            // A mask with a block of land around a point on land, with a hole at the point.
+            let (cx, cy) = TRANSFORM.apply(15., 65.6);
+            let (cx, cy) = (cx as u64, cy as u64);
+
+            let mut tmap = RoaringTreemap::new();
+            for row in cy - 10..=cy + 10 {
+                tmap.insert_range(row * NX + cx - 10..=row * NX + cx + 10);
+            }
+            tmap.remove(cy * NX + cx);
+
+            let mut landmask =
+                RoaringLandmask::from_parts(RoaringMask::from(tmap), Gshhg::new(py).unwrap());
+
+            // The shapes are never consulted for the point.
+            assert!(!landmask.contains(15., 65.6));
+            assert!(landmask.contains_exact(15., 65.6));
+
+            landmask.mask = landmask.mask.dilate(1);
+            assert!(landmask.contains(15., 65.6));
+        })
+    }
+
+    #[test]
+    fn with_bathymetry() {
+        pyo3::prepare_freethreaded_python();
+        pyo3::Python::with_gil(|py| {
+            // A shallow bank in the Norwegian Sea between 5E, 65N and 6E, 66N.
+            let mut elevation = ndarray::Array2::from_elem((180, 360), -1000.);
+            elevation[[155, 185]] = -10.;
+            let elevation = PyArray::from_owned_array(py, elevation);
+
+            let landmask =
+                RoaringLandmask::with_bathymetry(py, elevation.readonly(), 20., 240).unwrap();
+            assert!(landmask.contains(5.5, 65.6));
+            assert_eq!(
+                landmask.contains_explain(5.5, 65.6),
+                Decision::LandByBathymetry
+            );
+            assert!(!landmask.contains(4.5, 65.6));
+            assert!(landmask.contains(15., 65.6));
+
+            let landmask =
+                RoaringLandmask::with_bathymetry(py, elevation.readonly(), 5., 240).unwrap();
+            assert!(!landmask.contains(5.5, 65.6));
+
+            assert!(RoaringLandmask::with_bathymetry(py, elevation.readonly(), 5., 24).is_err());
+        })
+    }
+
+    #[test]
+    fn self_test() {
+        pyo3::prepare_freethreaded_python();
+        pyo3::Python::with_gil(|py| {
+            let mut mask = RoaringLandmask::new(py).unwrap();
+            assert_eq!(mask.self_test(), Ok(()));
+
+            mask.mask = RoaringMask::default();
+            let mismatches = mask.self_test().unwrap_err();
+            assert!(mismatches.contains(&(15., 65.6, true, false)));
+            assert!(mismatches.iter().all(|(_, _, expected, _)| *expected));
+        })
+    }
+
+    #[test]
+    fn audit() {
+        use mask::NX;
+        use roaring::RoaringTreemap;
+
+        pyo3::prepare_freethreaded_python();
+        pyo3::Python::with_gil(|py| {
+            // Blocks of land in the Norwegian Sea (5E, 65N) and in Sweden (15E, 65N).
+            let mut tmap = RoaringTreemap::new();
+            for (cx, cy) in [(44400, 37200), (46800, 37200)] {
+                for row in cy - 1..=cy + 1 {
+                    tmap.insert_range(row * NX + cx - 1..=row * NX + cx + 1);
+                }
+            }
+
+            let landmask =
+                RoaringLandmask::from_parts(RoaringMask::from(tmap), Gshhg::new(py).unwrap());
+
+            assert_eq!(landmask.audit(240), vec![(5., 65.)]);
+        })
+    }
+
+    #[test]
+    fn precompute_grid() {
+        pyo3::prepare_freethreaded_python();
+        pyo3::Python::with_gil(|py| {
+            let mask = RoaringLandmask::new(py).unwrap();
+
+            let x = PyArray::from_vec(py, vec![15., 5., 10.]).to_dyn();
+            let y = PyArray::from_vec(py, vec![65.6, 65.6, 60.]).to_dyn();
+
+            let grid = mask.precompute_grid(x.readonly(), y.readonly()).unwrap();
+            assert_eq!(grid.land().as_slice().unwrap(), &[true, false, true]);
+
+            let many = mask
+                .contains_many_par(py, x.readonly(), y.readonly())
+                .unwrap();
+            assert_eq!(
+                grid.contains(py).as_ref(py).readonly().as_array(),
+                many.as_ref(py).readonly().as_array()
+            );
+        })
+    }
+
+    #[test]
+    fn test_mismatched_lengths() {
+        pyo3::prepare_freethreaded_python();
+        pyo3::Python::with_gil(|py| {
+            let mask = RoaringLandmask::new(py).unwrap();
+
+            let x = PyArray::from_vec(py, vec![15., 5.]);
+            let y = PyArray::from_vec(py, vec![65.6]);
+
+            assert!(mask
+                .contains_many(py, x.to_dyn().readonly(), y.to_dyn().readonly())
+                .is_err());
+            assert!(mask
+                .contains_many_par(py, x.to_dyn().readonly(), y.to_dyn().readonly())
+                .is_err());
+        })
+    }
+
+    #[test]
+    fn test_sampling() {
+        pyo3::prepare_freethreaded_python();
+        pyo3::Python::with_gil(|py| {
+            let centers = RoaringLandmask::new(py).unwrap();
+            let dx = centers.mask.dx();
+
+            // Cell centers along the coast of Norway, and the corners of the same cells.
+            let row = (65.6f64 / dx).round() * dx;
+            let points = (0..2400)
+                .map(|i| 5. + i as f64 * dx)
+                .map(|x| (x, row))
+                .collect::<Vec<_>>();
+            let corners = points
+                .iter()
+                .map(|(x, y)| (x - dx / 2., y - dx / 2.))
+                .collect::<Vec<_>>();
+
+            let land = centers.contains_many_blocking(&points);
+            assert!(land.iter().any(|l| *l) && land.iter().any(|l| !*l));
+
+            let corner = centers.with_sampling(Sampling::Corner);
+            assert_eq!(corner.contains_many_blocking(&corners), land);
+
+            // The other lookups of a single cell sample the same cell as `contains`.
+            for (&(x, y), &land) in corners.iter().zip(&land) {
+                assert_eq!(corner.contains_explain(x, y).land(), land);
+                assert_eq!(corner.level(x, y), land as u8);
+                assert_eq!(corner.contains_raw(x, y), land);
+            }
+            assert_eq!(
+                corner.contains_raw(180. - dx / 4., 0.),
+                corner.contains(-180., 0.)
+            );
+        })
+    }
+
+    #[test]
+    fn test_out_of_domain() {
+        pyo3::prepare_freethreaded_python();
+        pyo3::Python::with_gil(|py| {
+            let mask = RoaringLandmask::new(py)
+                .unwrap()
+                .with_out_of_domain(OutOfDomain::ReturnFalse);
+            assert!(!mask.contains(15., 95.));
+            assert!(!mask.contains(15., f64::NAN));
+            assert!(mask.contains(15., 65.6));
+
+            // The other single-point queries follow the same policy.
+            assert_eq!(mask.level(15., 95.), 0);
+            assert_eq!(mask.contains_explain(15., 95.), Decision::OutsideLandmask);
+            assert!(!mask.contains_explain(15., 95.).shapes_consulted());
+            assert!(!mask.contains_exact(15., 95.));
+            assert!(!mask.contains_raw(15., 95.));
+            assert!(!mask.contains_tol(15., 95., 4));
+            assert_eq!(mask.classify_regions(15., 95., &[]), OCEAN);
+
+            let mask = mask.with_out_of_domain(OutOfDomain::Error);
+            let err = mask.try_contains(15., -95.).err().unwrap();
+            assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+            assert!(mask.try_contains(15., 65.6).unwrap());
+
+            assert!(mask.try_level(15., -95.).is_err());
+            assert!(mask.try_contains_explain(15., -95.).is_err());
+            assert!(mask.try_contains_exact(15., -95.).is_err());
+            assert!(mask.try_contains_tol(15., -95., 4).is_err());
+            assert!(mask.try_classify_regions(15., -95., &[]).is_err());
+            assert!(mask
+                .try_contains_with_stats(15., -95., &QueryStats::default())
+                .is_err());
+            assert_eq!(mask.try_level(15., 65.6).unwrap(), 1);
+
+            let mask = mask.with_out_of_domain(OutOfDomain::Panic);
+            let panicked = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                mask.try_contains(15., 95.)
+            }));
+            assert!(panicked.is_err());
         })
     }
 
@@ -322,7 +5476,7 @@ mod tests {
                     let x = x.to_dyn().readonly();
                     let y = y.to_dyn().readonly();
 
-                    let onland = mask.contains_many(py, x, y);
+                    let onland = mask.contains_many(py, x, y).unwrap();
                     assert!(onland.as_ref(py).len() == len);
                 })
             })
@@ -368,7 +5522,7 @@ mod tests {
                     let x = x.to_dyn().readonly();
                     let y = y.to_dyn().readonly();
 
-                    let onland = mask.contains_many_par(py, x, y);
+                    let onland = mask.contains_many_par(py, x, y).unwrap();
                     assert!(onland.as_ref(py).len() == len);
                 })
             })
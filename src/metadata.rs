@@ -0,0 +1,112 @@
+//! Provenance of the embedded data.
+
+use pyo3::prelude::*;
+
+/// Release of GSHHG that the mask and shapes were generated from, defined in `build.rs` together
+/// with the checksums of the data.
+pub use crate::GSHHG_VERSION;
+
+/// Resolution of the GSHHG shapes, `f` is the full resolution.
+pub const GSHHG_RESOLUTION: &str = "f";
+
+/// Which shoreline data a landmask was built from, see `RoaringLandmask::metadata`.
+#[pyclass]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Metadata {
+    /// GSHHG release, e.g. `2.3.7`.
+    #[pyo3(get)]
+    pub gshhg_version: String,
+
+    /// GSHHG resolution of the shapes (`c`, `l`, `i`, `h` or `f`).
+    #[pyo3(get)]
+    pub resolution: String,
+
+    /// Date (UTC) the data was embedded in the library, as `YYYY-MM-DD`. Respects
+    /// `SOURCE_DATE_EPOCH`.
+    #[pyo3(get)]
+    pub build_date: String,
+
+    /// SHA-256 of the compressed mask.
+    #[pyo3(get)]
+    pub mask_sha256: String,
+
+    /// SHA-256 of the compressed shapes.
+    #[pyo3(get)]
+    pub shapes_sha256: String,
+
+    /// Version of this library.
+    #[pyo3(get)]
+    pub version: String,
+}
+
+impl Metadata {
+    /// The metadata of the data embedded in the library.
+    pub fn embedded() -> Metadata {
+        let build_time: i64 = env!("ROARING_LANDMASK_BUILD_TIME").parse().unwrap();
+
+        Metadata {
+            gshhg_version: GSHHG_VERSION.to_string(),
+            resolution: GSHHG_RESOLUTION.to_string(),
+            build_date: date(build_time),
+            mask_sha256: crate::MASK_SHA256.to_string(),
+            shapes_sha256: crate::GSHHS_F_SHA256.to_string(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+        }
+    }
+}
+
+#[pymethods]
+impl Metadata {
+    fn __repr__(&self) -> String {
+        format!("{:?}", self)
+    }
+}
+
+/// The date of `t` seconds since the epoch, as `YYYY-MM-DD`.
+fn date(t: i64) -> String {
+    // Howard Hinnant's `civil_from_days`.
+    let z = t.div_euclid(86400) + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let d = doy - (153 * mp + 2) / 5 + 1;
+    let m = if mp < 10 { mp + 3 } else { mp - 9 };
+    let y = yoe + era * 400 + if m <= 2 { 1 } else { 0 };
+
+    format!("{:04}-{:02}-{:02}", y, m, d)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dates() {
+        assert_eq!(date(0), "1970-01-01");
+        assert_eq!(date(951_782_400), "2000-02-29");
+        assert_eq!(date(1_700_000_000), "2023-11-14");
+    }
+
+    #[test]
+    fn embedded() {
+        let m = Metadata::embedded();
+        assert_eq!(m.gshhg_version, GSHHG_VERSION);
+        assert_eq!(m.mask_sha256.len(), 64);
+        assert_eq!(m.build_date.len(), 10);
+    }
+
+    #[test]
+    fn version_of_data() {
+        // The release must be changed together with the data it describes.
+        assert_eq!(
+            (GSHHG_VERSION, crate::GSHHS_F_SHA256, crate::MASK_SHA256),
+            (
+                "2.3.7",
+                "05bdf3089407b9829a7a5be7ee43f1e4205f2bbc641e4778af77e4814be216da",
+                "5ea0e772ffc6ca8ad10c5de02be50670cbaedcff20b3541df6b78d3e1fdf48a1"
+            )
+        );
+    }
+}
@@ -0,0 +1,249 @@
+//! The nearest of a set of points, e.g. ports or stations.
+//!
+//! The points are kept in a k-d tree of their positions on the unit sphere. The straight-line
+//! distance through the sphere grows with the great-circle distance, so the nearest point in the
+//! tree is also the nearest along the surface, without special cases at the antimeridian or the
+//! poles. The distance is then the great-circle distance from `distance::haversine`.
+//!
+//! ```no_run
+//! # pyo3::prepare_freethreaded_python();
+//! # pyo3::Python::with_gil(|py| {
+//! use roaring_landmask::PointSet;
+//!
+//! let ports = PointSet::new(vec![(10.75, 59.9), (5.32, 60.39)]).unwrap();
+//!
+//! let (i, d) = ports.nearest(5., 60.);
+//! assert_eq!(i, 1);
+//! assert!(d < 50_000.);
+//! # });
+//! ```
+
+use ndarray::parallel::prelude::*;
+use numpy::{PyArray, PyReadonlyArrayDyn};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use std::io;
+
+use crate::distance::{self, Unit};
+
+/// A set of points (longitude, latitude) to find the nearest of, see the module documentation.
+#[pyclass]
+#[derive(Clone, Debug)]
+pub struct PointSet {
+    points: Vec<(f64, f64)>,
+
+    /// The points on the unit sphere and their index in `points`, as a k-d tree: the median
+    /// along the axis of the level is in the middle of each slice, with the smaller values
+    /// before it and the larger after it. The axes are x, y and z in turn.
+    tree: Vec<([f64; 3], usize)>,
+}
+
+/// The position of (x, y) in degrees on the unit sphere.
+fn unit(x: f64, y: f64) -> [f64; 3] {
+    let (x, y) = (x.to_radians(), y.to_radians());
+    [y.cos() * x.cos(), y.cos() * x.sin(), y.sin()]
+}
+
+fn build(nodes: &mut [([f64; 3], usize)], axis: usize) {
+    if nodes.len() <= 1 {
+        return;
+    }
+
+    let mid = nodes.len() / 2;
+    nodes.select_nth_unstable_by(mid, |a, b| a.0[axis].total_cmp(&b.0[axis]));
+
+    let (before, after) = nodes.split_at_mut(mid);
+    build(before, (axis + 1) % 3);
+    build(&mut after[1..], (axis + 1) % 3);
+}
+
+/// Update `best` (index and squared distance through the sphere) with the nearest node to `q`.
+/// Of equally near points the one with the lowest index is kept.
+fn search(nodes: &[([f64; 3], usize)], axis: usize, q: [f64; 3], best: &mut (usize, f64)) {
+    if nodes.is_empty() {
+        return;
+    }
+
+    let mid = nodes.len() / 2;
+    let (p, i) = nodes[mid];
+
+    let d2 = (0..3).map(|k| (q[k] - p[k]).powi(2)).sum::<f64>();
+    if d2 < best.1 || (d2 == best.1 && i < best.0) {
+        *best = (i, d2);
+    }
+
+    let diff = q[axis] - p[axis];
+    let (near, far) = if diff < 0. {
+        (&nodes[..mid], &nodes[mid + 1..])
+    } else {
+        (&nodes[mid + 1..], &nodes[..mid])
+    };
+
+    search(near, (axis + 1) % 3, q, best);
+    if diff * diff <= best.1 {
+        search(far, (axis + 1) % 3, q, best);
+    }
+}
+
+impl PointSet {
+    /// Index `points` (longitude, latitude). Fails with `InvalidInput` if there are no points,
+    /// or if a latitude is beyond ±90 or a coordinate is not finite.
+    pub fn new(points: Vec<(f64, f64)>) -> io::Result<PointSet> {
+        if points.is_empty() {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "no points"));
+        }
+
+        if let Some(i) = points
+            .iter()
+            .position(|&(x, y)| !(x.is_finite() && (-90. ..=90.).contains(&y)))
+        {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("invalid point {i}: {:?}", points[i]),
+            ));
+        }
+
+        let mut tree = points
+            .iter()
+            .enumerate()
+            .map(|(i, &(x, y))| (unit(x, y), i))
+            .collect::<Vec<_>>();
+        build(&mut tree, 0);
+
+        Ok(PointSet { points, tree })
+    }
+
+    /// The points, in the order they were given.
+    pub fn points(&self) -> &[(f64, f64)] {
+        &self.points
+    }
+
+    /// The index of the point nearest to (x, y), and the great-circle distance to it in meters.
+    /// Of equally near points the first is returned.
+    pub fn nearest(&self, x: f64, y: f64) -> (usize, f64) {
+        let mut best = (0, f64::INFINITY);
+        search(&self.tree, 0, unit(x, y), &mut best);
+
+        let (px, py) = self.points[best.0];
+        (best.0, distance::haversine(x, y, px, py))
+    }
+
+    /// Same as `nearest` for each of `points`, in parallel.
+    pub fn nearest_many(&self, points: &[(f64, f64)]) -> Vec<(usize, f64)> {
+        points
+            .par_iter()
+            .map(|&(x, y)| self.nearest(x, y))
+            .collect()
+    }
+}
+
+#[pymethods]
+impl PointSet {
+    #[new]
+    fn py_new(points: Vec<(f64, f64)>) -> PyResult<PointSet> {
+        PointSet::new(points).map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+
+    fn __len__(&self) -> usize {
+        self.points.len()
+    }
+
+    #[pyo3(name = "nearest", signature = (x, y, unit = Unit::Meters))]
+    fn py_nearest(&self, x: f64, y: f64, unit: Unit) -> (usize, f64) {
+        let (i, d) = self.nearest(x, y);
+        (i, d / unit.meters())
+    }
+
+    /// The index of the nearest point to every point (x, y) and the distance to it, as two
+    /// arrays (int64 and float) with the same shape as `x` and `y`. See `nearest`.
+    #[pyo3(name = "nearest_many", signature = (x, y, unit = Unit::Meters))]
+    fn py_nearest_many(
+        &self,
+        py: Python,
+        x: PyReadonlyArrayDyn<f64>,
+        y: PyReadonlyArrayDyn<f64>,
+        unit: Unit,
+    ) -> PyResult<(
+        Py<PyArray<i64, numpy::IxDyn>>,
+        Py<PyArray<f64, numpy::IxDyn>>,
+    )> {
+        let x = x.as_array();
+        let y = y.as_array();
+        crate::check_shape(x.shape(), y.shape())?;
+
+        let queries = x.iter().copied().zip(y.iter().copied()).collect::<Vec<_>>();
+        let (indices, distances): (Vec<usize>, Vec<f64>) = py
+            .allow_threads(|| self.nearest_many(&queries))
+            .into_iter()
+            .unzip();
+
+        let indices =
+            ndarray::Array::from_shape_vec(x.raw_dim(), crate::index_array(indices)).unwrap();
+        let distances = ndarray::Array::from_shape_vec(x.raw_dim(), distances)
+            .unwrap()
+            .mapv(|d| d / unit.meters());
+
+        Ok((
+            PyArray::from_owned_array(py, indices).to_owned(),
+            PyArray::from_owned_array(py, distances).to_owned(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn same_as_brute_force() {
+        let mut state = 0x2545_f491_4f6c_dd1du64;
+        let mut random = || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            (state >> 11) as f64 / (1u64 << 53) as f64
+        };
+
+        let points = (0..1000)
+            .map(|_| (random() * 360. - 180., random() * 180. - 90.))
+            .collect::<Vec<_>>();
+        let set = PointSet::new(points.clone()).unwrap();
+
+        let queries = (0..1000)
+            .map(|_| (random() * 360. - 180., random() * 180. - 90.))
+            .chain([(180., 0.), (-180., 0.), (0., 90.), (0., -90.)])
+            .collect::<Vec<_>>();
+
+        for (&(x, y), (i, d)) in queries.iter().zip(set.nearest_many(&queries)) {
+            let brute = points
+                .iter()
+                .map(|&(px, py)| distance::haversine(x, y, px, py))
+                .fold(f64::INFINITY, f64::min);
+
+            assert!((d - brute).abs() < 1e-6, "{x}, {y}: {d} != {brute}");
+            assert_eq!(d, distance::haversine(x, y, points[i].0, points[i].1));
+        }
+    }
+
+    #[test]
+    fn antimeridian() {
+        let set = PointSet::new(vec![(170., 0.), (-179.5, 0.5), (0., 0.)]).unwrap();
+
+        let (i, d) = set.nearest(179.5, 0.);
+        assert_eq!(i, 1);
+        assert!(d < 150_000.);
+
+        assert_eq!(set.nearest(-10., 0.).0, 2);
+
+        // The same point twice, the first wins.
+        let set = PointSet::new(vec![(-1., 0.), (1., 0.), (3., 0.), (1., 0.)]).unwrap();
+        assert_eq!(set.nearest(1.5, 0.).0, 1);
+    }
+
+    #[test]
+    fn invalid() {
+        assert!(PointSet::new(Vec::new()).is_err());
+        assert!(PointSet::new(vec![(0., 91.)]).is_err());
+        assert!(PointSet::new(vec![(f64::NAN, 0.)]).is_err());
+    }
+}
@@ -0,0 +1,56 @@
+//! Run batch queries from an async runtime without blocking it.
+//!
+//! Checking many points is CPU bound and can take a while, so running it directly on an async
+//! executor stalls every other task on that worker. With the `tokio` feature the queries are
+//! moved to the blocking thread pool of the runtime:
+//!
+//! ```no_run
+//! # use std::sync::Arc;
+//! # use roaring_landmask::{offload, RoaringLandmask};
+//! # async fn handler(landmask: Arc<RoaringLandmask>) {
+//! let points = vec![(15., 65.6), (5., 65.6)];
+//! let land = offload::contains_many(landmask, points).await.unwrap();
+//! assert_eq!(land, vec![true, false]);
+//! # }
+//! ```
+//!
+//! Without the feature the same pattern works with `RoaringLandmask::contains_many_blocking`
+//! inside `tokio::task::spawn_blocking`, the landmask is `Send` and `Sync` so it can be shared
+//! through an `Arc`.
+
+use std::sync::Arc;
+use tokio::task::{self, JoinError};
+
+use crate::RoaringLandmask;
+
+/// Check which of `points` (longitude, latitude) are on land on the blocking thread pool of the
+/// current tokio runtime. See `RoaringLandmask::contains_many_blocking`.
+///
+/// Panics if called outside a tokio runtime. Returns an error if the query panicked.
+pub async fn contains_many(
+    landmask: Arc<RoaringLandmask>,
+    points: Vec<(f64, f64)>,
+) -> Result<Vec<bool>, JoinError> {
+    task::spawn_blocking(move || landmask.contains_many_blocking(&points)).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn contains_many_on_runtime() {
+        pyo3::prepare_freethreaded_python();
+        let landmask = pyo3::Python::with_gil(|py| RoaringLandmask::new(py).unwrap());
+        let landmask = Arc::new(landmask);
+
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+
+        let land = rt
+            .block_on(contains_many(landmask, vec![(15., 65.6), (5., 65.6)]))
+            .unwrap();
+        assert_eq!(land, vec![true, false]);
+    }
+}
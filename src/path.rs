@@ -0,0 +1,142 @@
+//! Great-circle paths between points.
+
+use crate::distance::{haversine, Earth};
+
+/// Default spacing in meters between points sampled along a path, about half the resolution of
+/// the mask.
+pub const STEP: f64 = 200.;
+
+/// The point at fraction `f` along the great circle from `from` to `to`, points are given as
+/// (longitude, latitude) in degrees.
+pub fn intermediate(from: (f64, f64), to: (f64, f64), f: f64) -> (f64, f64) {
+    let (l0, p0) = (from.0.to_radians(), from.1.to_radians());
+    let (l1, p1) = (to.0.to_radians(), to.1.to_radians());
+
+    let d = haversine(from.0, from.1, to.0, to.1) / crate::distance::EARTH_RADIUS;
+    if d.sin().abs() < 1e-12 {
+        // Same point (or antipodal, where the path is not defined).
+        return from;
+    }
+
+    let a = ((1. - f) * d).sin() / d.sin();
+    let b = (f * d).sin() / d.sin();
+
+    let x = a * p0.cos() * l0.cos() + b * p1.cos() * l1.cos();
+    let y = a * p0.cos() * l0.sin() + b * p1.cos() * l1.sin();
+    let z = a * p0.sin() + b * p1.sin();
+
+    (
+        y.atan2(x).to_degrees(),
+        z.atan2((x * x + y * y).sqrt()).to_degrees(),
+    )
+}
+
+/// The point `distance` meters from `from` along the great circle leaving it with initial
+/// `bearing` (degrees clockwise from north).
+pub fn destination(from: (f64, f64), bearing: f64, distance: f64) -> (f64, f64) {
+    let (l0, p0) = (from.0.to_radians(), from.1.to_radians());
+    let b = bearing.to_radians();
+    let d = distance / crate::distance::EARTH_RADIUS;
+
+    let p1 = (p0.sin() * d.cos() + p0.cos() * d.sin() * b.cos()).asin();
+    let l1 = l0 + (b.sin() * d.sin() * p0.cos()).atan2(d.cos() - p0.sin() * p1.sin());
+
+    (crate::modulate_longitude(l1.to_degrees()), p1.to_degrees())
+}
+
+/// Number of segments needed to split the path from `from` to `to` into pieces no longer than
+/// `step` meters.
+pub fn segments(from: (f64, f64), to: (f64, f64), step: f64) -> usize {
+    segments_on(from, to, step, Earth::Sphere)
+}
+
+/// Same as `segments`, with the length of the path measured on `earth`. The points are still
+/// placed along the great circle, which departs slightly from the geodesic of the ellipsoid.
+pub fn segments_on(from: (f64, f64), to: (f64, f64), step: f64, earth: Earth) -> usize {
+    let d = earth.distance(from.0, from.1, to.0, to.1);
+    ((d / step).ceil() as usize).max(1)
+}
+
+/// Points along the great circle from `from` to `to`, including both ends, spaced at most `step`
+/// meters apart.
+pub fn densify(from: (f64, f64), to: (f64, f64), step: f64) -> impl Iterator<Item = (f64, f64)> {
+    let n = segments(from, to, step);
+    (0..=n).map(move |i| intermediate(from, to, i as f64 / n as f64))
+}
+
+/// The points along the path through `coords`, with each leg densified along the great circle
+/// (see `densify`) and the points where the legs meet only once.
+pub fn densify_track(coords: &[(f64, f64)], step: f64) -> Vec<(f64, f64)> {
+    let mut points = coords.first().copied().into_iter().collect::<Vec<_>>();
+
+    for leg in coords.windows(2) {
+        points.extend(densify(leg[0], leg[1], step).skip(1));
+    }
+
+    points
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ends() {
+        let p = intermediate((5., 60.), (10., 61.), 0.);
+        assert!((p.0 - 5.).abs() < 1e-9 && (p.1 - 60.).abs() < 1e-9);
+
+        let p = intermediate((5., 60.), (10., 61.), 1.);
+        assert!((p.0 - 10.).abs() < 1e-9 && (p.1 - 61.).abs() < 1e-9);
+    }
+
+    #[test]
+    fn antimeridian() {
+        let p = intermediate((179., 0.), (-179., 0.), 0.5);
+        assert!((p.0.abs() - 180.).abs() < 1e-9);
+        assert!(p.1.abs() < 1e-9);
+
+        let points = densify((179., 10.), (-179., 10.), 10_000.).collect::<Vec<_>>();
+        assert!(points.iter().all(|(x, _)| x.abs() >= 179. - 1e-9));
+    }
+
+    #[test]
+    fn densify_track() {
+        let track = [(0., 0.), (1., 0.), (1., 1.)];
+        let points = super::densify_track(&track, 50_000.);
+
+        assert_eq!(points.len(), 1 + 3 + 3);
+        assert_eq!(points[0], (0., 0.));
+        assert!((points[3].0 - 1.).abs() < 1e-9 && points[3].1.abs() < 1e-9);
+        assert!((points[6].0 - 1.).abs() < 1e-9 && (points[6].1 - 1.).abs() < 1e-9);
+
+        assert!(super::densify_track(&[], 1000.).is_empty());
+        assert_eq!(super::densify_track(&[(5., 5.)], 1000.), vec![(5., 5.)]);
+    }
+
+    #[test]
+    fn destination_degree() {
+        let d = haversine(0., 0., 1., 0.);
+
+        let p = destination((0., 0.), 90., d);
+        assert!((p.0 - 1.).abs() < 1e-9 && p.1.abs() < 1e-9);
+
+        let p = destination((0., 0.), 0., d);
+        assert!(p.0.abs() < 1e-9 && (p.1 - 1.).abs() < 1e-9);
+
+        let p = destination((179.5, 0.), 90., d);
+        assert!((p.0 + 179.5).abs() < 1e-9);
+
+        let p = destination((5., 60.), 37., 123_456.);
+        assert!((haversine(5., 60., p.0, p.1) - 123_456.).abs() < 1e-6);
+    }
+
+    #[test]
+    fn spacing() {
+        let points = densify((0., 0.), (1., 0.), 1000.).collect::<Vec<_>>();
+        assert_eq!(points.len(), 113);
+
+        for w in points.windows(2) {
+            assert!(haversine(w[0].0, w[0].1, w[1].0, w[1].1) <= 1000.);
+        }
+    }
+}
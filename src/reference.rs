@@ -0,0 +1,36 @@
+//! Reference points with known land or ocean, used by `RoaringLandmask::self_test`.
+//!
+//! The points are well away from any coast, so that they do not depend on the resolution of the
+//! mask or the shapes.
+
+/// Longitude, latitude and whether the point is on land.
+pub const POINTS: &[(f64, f64, bool)] = &[
+    // Land
+    (15., 65.6, true),   // Sweden
+    (2.35, 48.86, true), // Paris
+    (-100., 40., true),  // Kansas
+    (-60., -10., true),  // Amazonas
+    (20., 0., true),     // Congo
+    (135., -25., true),  // Central Australia
+    (100., 60., true),   // Siberia
+    (78., 22., true),    // India
+    (105., 35., true),   // China
+    (-45., 72., true),   // Greenland
+    (-65., -32., true),  // Argentina
+    (25., 15., true),    // Sudan
+    (0., -80., true),    // Antarctica
+    (0., -90., true),    // South Pole
+    // Ocean
+    (5., 65.6, false),      // Norwegian Sea
+    (-30., 30., false),     // North Atlantic
+    (-20., -40., false),    // South Atlantic
+    (-140., 0., false),     // Equatorial Pacific
+    (160., 30., false),     // North-west Pacific
+    (-150., 50., false),    // Gulf of Alaska
+    (-123.4, -48.9, false), // Point Nemo
+    (80., -30., false),     // Indian Ocean
+    (0., 0., false),        // Gulf of Guinea
+    (150., -60., false),    // Southern Ocean
+    (0., 85., false),       // Arctic Ocean
+    (0., 90., false),       // North Pole
+];
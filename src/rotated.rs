@@ -0,0 +1,100 @@
+//! Rotated-pole coordinates, as used by the grids of some atmospheric models (e.g. COSMO and
+//! ICON-LAM) and described by the CF conventions as `rotated_latitude_longitude`.
+//!
+//! The rotated grid has its north pole at (`pole_lon`, `pole_lat`) in geographic coordinates,
+//! the `grid_north_pole_longitude` and `grid_north_pole_latitude` of CF. The origin of the
+//! rotated grid is then at longitude `pole_lon + 180` on the equator of the rotated pole, e.g.
+//! at (10, 50) for the pole at (-170, 40) of COSMO-DE. A rotation about the rotated pole
+//! (`north_pole_grid_longitude`) is not supported.
+
+/// The geographic (longitude, latitude) of the point (`rlon`, `rlat`) on the grid with its north
+/// pole at (`pole_lon`, `pole_lat`). All in degrees, the longitude is in [-180, 180].
+pub fn to_geographic(rlon: f64, rlat: f64, pole_lon: f64, pole_lat: f64) -> (f64, f64) {
+    let (rlon, rlat) = (rlon.to_radians(), rlat.to_radians());
+    let t = (90. - pole_lat).to_radians();
+
+    let (x, y, z) = (rlat.cos() * rlon.cos(), rlat.cos() * rlon.sin(), rlat.sin());
+
+    // Tilt the pole of the grid down to `pole_lat`, then turn it to `pole_lon`.
+    let (x, z) = (t.cos() * x - t.sin() * z, t.sin() * x + t.cos() * z);
+
+    let lon = y.atan2(x).to_degrees() + pole_lon + 180.;
+    let lat = z.clamp(-1., 1.).asin().to_degrees();
+
+    (wrap(lon), lat)
+}
+
+/// The rotated (longitude, latitude) of the geographic point (`lon`, `lat`) on the grid with its
+/// north pole at (`pole_lon`, `pole_lat`), the inverse of `to_geographic`.
+pub fn to_rotated(lon: f64, lat: f64, pole_lon: f64, pole_lat: f64) -> (f64, f64) {
+    let (lon, lat) = ((lon - pole_lon - 180.).to_radians(), lat.to_radians());
+    let t = (90. - pole_lat).to_radians();
+
+    let (x, y, z) = (lat.cos() * lon.cos(), lat.cos() * lon.sin(), lat.sin());
+    let (x, z) = (t.cos() * x + t.sin() * z, -t.sin() * x + t.cos() * z);
+
+    (
+        wrap(y.atan2(x).to_degrees()),
+        z.clamp(-1., 1.).asin().to_degrees(),
+    )
+}
+
+/// `lon` moved into [-180, 180].
+fn wrap(lon: f64) -> f64 {
+    let lon = (lon + 180.).rem_euclid(360.) - 180.;
+    if lon == -180. {
+        180.
+    } else {
+        lon
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: (f64, f64), b: (f64, f64)) -> bool {
+        (a.0 - b.0).abs() < 1e-9 && (a.1 - b.1).abs() < 1e-9
+    }
+
+    #[test]
+    fn cosmo_de() {
+        // COSMO-DE: the pole at (-170, 40), the domain from about (-5, -5) to (5.5, 6.5) in
+        // rotated coordinates, over Germany.
+        let (plon, plat) = (-170., 40.);
+
+        assert!(close(to_geographic(0., 0., plon, plat), (10., 50.)));
+        assert!(close(to_geographic(0., 90., plon, plat), (-170., 40.)));
+        assert!(close(to_geographic(0., -40., plon, plat), (10., 10.)));
+
+        // The south-west corner of the domain is in France, the north-east corner over the Baltic.
+        let (x, y) = to_geographic(-5., -5., plon, plat);
+        assert!(
+            (x - 2.98).abs() < 0.01 && (y - 44.77).abs() < 0.01,
+            "{x}, {y}"
+        );
+        let (x, y) = to_geographic(5.5, 6.5, plon, plat);
+        assert!(
+            (x - 19.84).abs() < 0.01 && (y - 56.14).abs() < 0.01,
+            "{x}, {y}"
+        );
+
+        // Rotated east is geographic east at the origin.
+        assert!(to_geographic(1., 0., plon, plat).0 > 10.);
+    }
+
+    #[test]
+    fn roundtrip() {
+        for (plon, plat) in [(-170., 40.), (-162., 39.25), (0., 90.), (37.5, -12.)] {
+            for rlon in (-17..=17).map(|i| i as f64 * 10.) {
+                for rlat in (-8..=8).map(|i| i as f64 * 10.) {
+                    let (lon, lat) = to_geographic(rlon, rlat, plon, plat);
+                    assert!(close(to_rotated(lon, lat, plon, plat), (rlon, rlat)));
+                }
+            }
+        }
+
+        // With the pole at the North Pole the grid is geographic, shifted by 180 degrees.
+        assert!(close(to_geographic(20., 30., 0., 90.), (-160., 30.)));
+    }
+}
@@ -1,19 +1,326 @@
 use pyo3::{prelude::*, types::PyBytes};
 use std::borrow::Borrow;
+use std::convert::TryInto;
+use std::fmt;
 use std::fs::File;
 use std::io::{self, prelude::*};
 use std::path::Path;
+use std::sync::{Arc, OnceLock};
 
-use geos::{CoordSeq, Geom, Geometry, PreparedGeometry};
+use geos::{CoordSeq, GResult, Geom, Geometry, PreparedGeometry};
 use numpy::{PyArray, PyReadonlyArrayDyn};
 
+use crate::distance::{haversine, Earth};
+
 pub static GSHHS_F: &str = "gshhs_f_-180.000000E-90.000000N180.000000E90.000000N.wkb.xz";
 
+static INDEX_MAGIC: &[u8; 4] = b"RLMI";
+const INDEX_VERSION: u32 = 1;
+
+/// Magic, version, CRC of the entries and number of polygons and vertices.
+const INDEX_HEADER: usize = 4 + 4 + 4 + 8 + 8;
+
+/// Identifies a polygon in the shapes by its index in the GSHHG multipolygon.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct PolygonId(pub usize);
+
+/// Which boundary of Antarctica counts as the coast.
+///
+/// GSHHG has two boundaries for Antarctica, distinguished by the level of the polygons: level 5
+/// is the ice front (the seaward edge of the ice shelves) and level 6 is the grounding line
+/// (where the ice starts to float). The bundled shapes and mask use the ice front, so floating
+/// ice shelves are land.
 #[pyclass]
-pub struct Gshhg {
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Antarctica {
+    #[default]
+    IceFront,
+    GroundingLine,
+}
+
+/// A ring of (longitude, latitude) vertices.
+pub type Ring = Vec<(f64, f64)>;
+
+/// An edge of a ring, from one (longitude, latitude) vertex to the next.
+pub type Segment = ((f64, f64), (f64, f64));
+
+/// A bounding box (min longitude, min latitude, max longitude, max latitude).
+pub type BBox = (f64, f64, f64, f64);
+
+/// A problem with the rings of a polygon which makes `contains` unreliable, see
+/// `Gshhg::validate`. Rings are numbered as in `Gshhg::polygon_rings`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum GeometryIssue {
+    /// The last vertex of the ring is not the same as the first.
+    UnclosedRing { polygon: PolygonId, ring: usize },
+
+    /// The ring has fewer than three distinct vertices, and does not enclose anything.
+    TooFewPoints {
+        polygon: PolygonId,
+        ring: usize,
+        points: usize,
+    },
+
+    /// Edges of the ring cross or overlap each other, or the edges of another ring of the same
+    /// polygon, at `at`.
+    SelfIntersection {
+        polygon: PolygonId,
+        rings: (usize, usize),
+        at: (f64, f64),
+    },
+}
+
+impl fmt::Display for GeometryIssue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            GeometryIssue::UnclosedRing { polygon, ring } => {
+                write!(
+                    f,
+                    "polygon {}, ring {}: ring is not closed",
+                    polygon.0, ring
+                )
+            }
+            GeometryIssue::TooFewPoints {
+                polygon,
+                ring,
+                points,
+            } => write!(
+                f,
+                "polygon {}, ring {}: ring has {} points, needs at least 3 distinct",
+                polygon.0, ring, points
+            ),
+            GeometryIssue::SelfIntersection {
+                polygon,
+                rings: (a, b),
+                at: (x, y),
+            } if a == b => write!(
+                f,
+                "polygon {}, ring {}: ring intersects itself at ({}, {})",
+                polygon.0, a, x, y
+            ),
+            GeometryIssue::SelfIntersection {
+                polygon,
+                rings: (a, b),
+                at: (x, y),
+            } => write!(
+                f,
+                "polygon {}, rings {} and {}: rings intersect at ({}, {})",
+                polygon.0, a, b, x, y
+            ),
+        }
+    }
+}
+
+/// The point-in-polygon test used by `Gshhg::contains`, see `Gshhg::with_predicate`.
+pub trait PointInPolygon: Send + Sync {
+    /// Whether (x, y) is inside the polygons of `shapes`. The longitude is within [-180, 180] and
+    /// the latitude within (-90, 90), the poles are handled by `Gshhg`.
+    fn contains(&self, shapes: &Gshhg, x: f64, y: f64) -> bool;
+}
+
+/// The default test, using the prepared GEOS geometry.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Prepared;
+
+impl PointInPolygon for Prepared {
+    fn contains(&self, shapes: &Gshhg, x: f64, y: f64) -> bool {
+        let point = CoordSeq::new_from_vec(&[&[x, y]]).unwrap();
+        let point = Geometry::create_point(point).unwrap();
+        shapes.inner.prepared().unwrap().contains(&point).unwrap()
+    }
+}
+
+/// Even-odd ray casting over the rings of every polygon, see `Gshhg::polygon_rings`.
+///
+/// The polygons are scanned linearly, and only the vertices of those whose bounding box contains
+/// the point are checked. It does not use the spatial index of GEOS, so it is the default when
+/// the `index` feature is off, and a reference for other implementations.
+///
+/// It agrees with `Prepared` everywhere except exactly on the boundary of a polygon: GEOS counts
+/// such points as outside, while ray casting depends on the direction of the edge. Points on the
+/// antimeridian, where GSHHG splits the polygons, are moved off the split edges first (see
+/// `off_antimeridian`), so that both tests treat them as inside the polygon next to the line.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct RayCasting;
+
+impl PointInPolygon for RayCasting {
+    fn contains(&self, shapes: &Gshhg, x: f64, y: f64) -> bool {
+        shapes.rings_around(off_antimeridian(x), y) % 2 == 1
+    }
+}
+
+/// Longitude of a point on the antimeridian moved a tiny distance (about 0.1 mm) into the
+/// western hemisphere.
+///
+/// GSHHG splits the polygons which cross the antimeridian along x = ±180, so points on that line
+/// are on the boundary of the polygons on both sides. A point on the boundary is not inside
+/// according to GEOS, and depends on the direction of the edges with ray casting, so land on the
+/// antimeridian would read as ocean. Moved off the line, the point is inside the polygon which
+/// covers the western side.
+fn off_antimeridian(x: f64) -> f64 {
+    const NUDGE: f64 = 1e-9;
+
+    if x <= -180. {
+        -180. + NUDGE
+    } else if x >= 180. {
+        180. - NUDGE
+    } else {
+        x
+    }
+}
+
+/// Even-odd ray casting for a single ring.
+pub(crate) fn ring_contains(ring: &[(f64, f64)], x: f64, y: f64) -> bool {
+    let mut inside = false;
+
+    for w in ring.windows(2) {
+        let ((x0, y0), (x1, y1)) = (w[0], w[1]);
+
+        if (y0 > y) != (y1 > y) && x < x0 + (y - y0) * (x1 - x0) / (y1 - y0) {
+            inside = !inside;
+        }
+    }
+
+    inside
+}
+
+/// The issues with `polygons`, each given as its exterior ring followed by any holes, see
+/// `Gshhg::validate`.
+///
+/// Rings are checked for intersections with a sweep over the edges sorted by longitude, so only
+/// edges whose bounding boxes overlap are compared. Repeated vertices are skipped, and rings of
+/// the same polygon may touch at a shared vertex, but not cross.
+pub fn ring_issues(polygons: &[Vec<Ring>]) -> Vec<GeometryIssue> {
+    let mut issues = Vec::new();
+
+    for (polygon, rings) in polygons.iter().enumerate() {
+        let polygon = PolygonId(polygon);
+
+        // (westernmost longitude, ring, edge, number of edges in the ring, edge)
+        let mut edges = Vec::new();
+
+        for (ring, vertices) in rings.iter().enumerate() {
+            let mut path = vertices.clone();
+            path.dedup();
+
+            let closed = path.len() > 1 && path.first() == path.last();
+            if !vertices.is_empty() && vertices.first() != vertices.last() {
+                issues.push(GeometryIssue::UnclosedRing { polygon, ring });
+            }
+
+            let distinct = path.len() - usize::from(closed);
+            if distinct < 3 {
+                issues.push(GeometryIssue::TooFewPoints {
+                    polygon,
+                    ring,
+                    points: vertices.len(),
+                });
+                continue;
+            }
+
+            // An unclosed ring is checked as if it were closed.
+            if !closed {
+                path.push(path[0]);
+            }
+
+            let n = path.len() - 1;
+            edges.extend(
+                path.windows(2)
+                    .enumerate()
+                    .map(|(i, w)| (w[0].0.min(w[1].0), ring, i, n, (w[0], w[1]))),
+            );
+        }
+
+        edges.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+        for (i, &(_, ra, ia, n, (a0, a1))) in edges.iter().enumerate() {
+            let east = a0.0.max(a1.0);
+
+            for &(west, rb, ib, _, (b0, b1)) in &edges[i + 1..] {
+                if west > east {
+                    break;
+                }
+
+                // Consecutive edges of a ring share a vertex.
+                if ra == rb && (ia.abs_diff(ib) == 1 || ia.abs_diff(ib) == n - 1) {
+                    continue;
+                }
+
+                if let Some(at) = segment_intersection((a0, a1), (b0, b1)) {
+                    let shared = (at == a0 || at == a1) && (at == b0 || at == b1);
+                    if ra != rb && shared {
+                        continue;
+                    }
+
+                    issues.push(GeometryIssue::SelfIntersection {
+                        polygon,
+                        rings: (ra.min(rb), ra.max(rb)),
+                        at,
+                    });
+                }
+            }
+        }
+    }
+
+    issues
+}
+
+/// A point where the segments `a` and `b` meet, or `None` if they are disjoint. Where the
+/// segments overlap, or only touch, the point is an end of one of them.
+fn segment_intersection(a: Segment, b: Segment) -> Option<(f64, f64)> {
+    fn orient(p: (f64, f64), q: (f64, f64), r: (f64, f64)) -> f64 {
+        (q.0 - p.0) * (r.1 - p.1) - (q.1 - p.1) * (r.0 - p.0)
+    }
+
+    fn within(p: (f64, f64), (q, r): Segment) -> bool {
+        p.0 >= q.0.min(r.0) && p.0 <= q.0.max(r.0) && p.1 >= q.1.min(r.1) && p.1 <= q.1.max(r.1)
+    }
+
+    let ((a0, a1), (b0, b1)) = (a, b);
+    let (d0, d1) = (orient(b0, b1, a0), orient(b0, b1, a1));
+    let (d2, d3) = (orient(a0, a1, b0), orient(a0, a1, b1));
+
+    if d0 * d1 < 0. && d2 * d3 < 0. {
+        let t = d0 / (d0 - d1);
+        return Some((a0.0 + t * (a1.0 - a0.0), a0.1 + t * (a1.1 - a0.1)));
+    }
+
+    [(d0, a0, b), (d1, a1, b), (d2, b0, a), (d3, b1, a)]
+        .iter()
+        .find(|(d, p, s)| *d == 0. && within(*p, *s))
+        .map(|(_, p, _)| *p)
+}
+
+/// Whether the edge from `a` to `b` runs along the antimeridian or the South Pole, where GSHHG
+/// closes the polygons that cross them. Such edges are not coastline.
+fn closing_edge((x0, y0): (f64, f64), (x1, y1): (f64, f64)) -> bool {
+    (x0 == x1 && x0.abs() == 180.) || (y0 == -90. && y1 == -90.)
+}
+
+/// The geometry and the data derived from it, shared between clones of `Gshhg`.
+pub(crate) struct Inner {
     // prepped requires `geom` above to be around, and is valid as long as geom is alive.
     geom: Geometry,
-    prepped: PreparedGeometry,
+
+    /// The prepared geometry for `Prepared`, built up front with the `index` feature and on
+    /// first use otherwise.
+    prepped: OnceLock<PreparedGeometry>,
+
+    /// The vertices of each polygon, extracted on first use.
+    rings: OnceLock<Vec<Vec<Ring>>>,
+
+    /// The bounding box of each polygon, computed on first use.
+    bounds: OnceLock<Vec<BBox>>,
+
+    /// The landmass of each polygon, see `Gshhg::landmass`, computed on first use.
+    landmasses: OnceLock<Vec<PolygonId>>,
+}
+
+#[pyclass]
+#[derive(Clone)]
+pub struct Gshhg {
+    pub(crate) inner: Arc<Inner>,
+    predicate: Arc<dyn PointInPolygon>,
 }
 
 // impl Drop for Gshhg {
@@ -34,34 +341,132 @@ fn warmup_prepped(prepped: &PreparedGeometry) {
     prepped.contains(&point).unwrap();
 }
 
-impl Clone for Gshhg {
-    fn clone(&self) -> Self {
-        let geom = Clone::clone(&self.geom);
-        let prepped = geom.to_prepared_geom().unwrap();
+impl Inner {
+    /// The prepared geometry, built and warmed up on first use.
+    fn prepared(&self) -> io::Result<&PreparedGeometry> {
+        if let Some(prepped) = self.prepped.get() {
+            return Ok(prepped);
+        }
+
+        let prepped = self
+            .geom
+            .to_prepared_geom()
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "cannot prepare geomtry"))?;
         warmup_prepped(&prepped);
 
-        Gshhg {
-            geom,
-            prepped,
-        }
+        Ok(self.prepped.get_or_init(|| prepped))
+    }
+}
+
+/// `Prepared` with the `index` feature, `RayCasting` without it.
+fn default_predicate() -> Arc<dyn PointInPolygon> {
+    if cfg!(feature = "index") {
+        Arc::new(Prepared)
+    } else {
+        Arc::new(RayCasting)
     }
 }
 
+fn ring_coords(ring: &impl Geom) -> GResult<Ring> {
+    let cs = ring.get_coord_seq()?;
+    (0..cs.size()?)
+        .map(|i| Ok((cs.get_x(i)?, cs.get_y(i)?)))
+        .collect()
+}
+
+/// A multipolygon of `polygons`, each given as the exterior ring followed by the interior rings.
+fn multipolygon<P: AsRef<[Ring]>>(polygons: &[P]) -> GResult<Geometry> {
+    let linear_ring = |ring: &Ring| {
+        let coords = ring.iter().map(|(x, y)| [*x, *y]).collect::<Vec<_>>();
+        Geometry::create_linear_ring(CoordSeq::new_from_vec(&coords)?)
+    };
+
+    let polygons = polygons
+        .iter()
+        .map(|rings| {
+            let rings = rings.as_ref();
+            let exterior = linear_ring(&rings[0])?;
+            let interiors = rings[1..].iter().map(linear_ring).collect::<GResult<_>>()?;
+            Geometry::create_polygon(exterior, interiors)
+        })
+        .collect::<GResult<Vec<_>>>()?;
+
+    Geometry::create_multipolygon(polygons)
+}
+
+/// The exterior ring followed by the interior rings of every polygon in `geom`.
+fn extract_rings(geom: &Geometry) -> GResult<Vec<Vec<Ring>>> {
+    (0..geom.get_num_geometries()?)
+        .map(|n| {
+            let polygon = geom.get_geometry_n(n)?;
+
+            let mut rings = vec![ring_coords(&polygon.get_exterior_ring()?)?];
+            for i in 0..polygon.get_num_interior_rings()? {
+                let ring = polygon.get_interior_ring_n(i.try_into().unwrap())?;
+                rings.push(ring_coords(&ring)?);
+            }
+
+            Ok(rings)
+        })
+        .collect()
+}
+
 impl Gshhg {
     pub fn from_geom(geom: Geometry) -> io::Result<Gshhg> {
         // let bxd = Box::new(geom);
         // let gptr = Box::into_raw(bxd);
         // let prepped = unsafe { (&*gptr).to_prepared_geom() }
         //     .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "cannot prepare geomtry"))?;
-        let prepped = geom.to_prepared_geom().map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "cannot prepare geomtry"))?;
-        warmup_prepped(&prepped);
+        let inner = Inner {
+            geom,
+            prepped: OnceLock::new(),
+            rings: OnceLock::new(),
+            bounds: OnceLock::new(),
+            landmasses: OnceLock::new(),
+        };
+
+        #[cfg(feature = "index")]
+        inner.prepared()?;
 
         Ok(Gshhg {
-            geom,
-            prepped,
+            inner: Arc::new(inner),
+            predicate: default_predicate(),
         })
     }
 
+    /// The embedded GSHHG shapes (full resolution), without needing Python.
+    pub fn embedded() -> io::Result<Gshhg> {
+        let g = geos::Geometry::new_from_wkb(&Gshhg::embedded_wkb()?).unwrap();
+        Gshhg::from_geom(g)
+    }
+
+    fn embedded_wkb() -> io::Result<Vec<u8>> {
+        use crate::GsshgData;
+
+        let buf = GsshgData::get(&GSHHS_F).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                "cannot find shapes, built without the embed feature?",
+            )
+        })?;
+        let buf: &[u8] = buf.data.borrow();
+        let mut fd = xz2::read::XzDecoder::new(buf);
+
+        let mut buf = Vec::new();
+        fd.read_to_end(&mut buf)?;
+
+        Ok(buf)
+    }
+
+    /// Shapes without any land.
+    pub fn empty() -> io::Result<Gshhg> {
+        let g = Geometry::create_empty_polygon().map_err(|_| {
+            io::Error::new(io::ErrorKind::InvalidData, "cannot create empty geometry")
+        })?;
+
+        Gshhg::from_geom(g)
+    }
+
     pub fn from_compressed<P: AsRef<Path>>(path: P) -> io::Result<Gshhg> {
         let g = Gshhg::get_geometry_from_compressed(path)?;
 
@@ -77,32 +482,858 @@ impl Gshhg {
 
         Ok(geos::Geometry::new_from_wkb(&buf).unwrap())
     }
+
+    /// Make shapes from `polygons`, each given as its exterior ring followed by any holes, e.g.
+    /// read from a custom shapefile.
+    ///
+    /// Rings which are not closed are an error, unless `close_rings` is set, in which case the
+    /// first vertex is repeated at the end. Rings with too few points are always an error. Other
+    /// issues are not fixed, check them with `validate`.
+    pub fn from_rings(mut polygons: Vec<Vec<Ring>>, close_rings: bool) -> io::Result<Gshhg> {
+        if close_rings {
+            for ring in polygons.iter_mut().flatten() {
+                if ring.len() > 1 && ring.first() != ring.last() {
+                    ring.push(ring[0]);
+                }
+            }
+        }
+
+        if let Some(id) = polygons.iter().position(Vec::is_empty) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("polygon {} has no rings", id),
+            ));
+        }
+
+        if let Some(issue) = ring_issues(&polygons)
+            .into_iter()
+            .find(|issue| !matches!(issue, GeometryIssue::SelfIntersection { .. }))
+        {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                issue.to_string(),
+            ));
+        }
+
+        let geom = multipolygon(&polygons)
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "cannot create polygons"))?;
+
+        Gshhg::from_geom(geom)
+    }
+
+    /// Check the rings of the polygons for self-intersections, e.g. after loading a custom
+    /// shapefile. Points near an issue may be wrongly classified by `contains`, which assumes
+    /// valid polygons. An empty list means no issues were found.
+    ///
+    /// Rings read by GEOS are always closed and have at least four points, so only
+    /// `GeometryIssue::SelfIntersection` is reported here, see `ring_issues` to check rings
+    /// before loading them.
+    pub fn validate(&self) -> Vec<GeometryIssue> {
+        let polygons = (0..self.polygons())
+            .map(|id| self.polygon_rings(PolygonId(id)).unwrap().to_vec())
+            .collect::<Vec<_>>();
+
+        ring_issues(&polygons)
+    }
+
+    /// Use `predicate` as the point-in-polygon test instead of the default: `Prepared`, or
+    /// `RayCasting` when the `index` feature is off.
+    pub fn with_predicate<P: PointInPolygon + 'static>(self, predicate: P) -> Gshhg {
+        Gshhg {
+            predicate: Arc::new(predicate),
+            ..self
+        }
+    }
+
+    /// The rings of polygon `id`: the exterior ring followed by any holes. Returns `None` if
+    /// there is no such polygon.
+    ///
+    /// The vertices of all polygons are copied out of the geometry on the first call, and kept
+    /// for the lifetime of the shapes.
+    pub fn polygon_rings(&self, id: PolygonId) -> Option<&[Ring]> {
+        self.inner
+            .rings
+            .get_or_init(|| extract_rings(&self.inner.geom).unwrap())
+            .get(id.0)
+            .map(Vec::as_slice)
+    }
+
+    /// Number of rings, exterior or interior, of all polygons that (x, y) is inside.
+    ///
+    /// The polygons are scanned linearly, skipping those whose bounding box does not contain the
+    /// point.
+    pub fn rings_around(&self, x: f64, y: f64) -> usize {
+        self.candidates(x, y)
+            .flat_map(|id| self.polygon_rings(id).unwrap())
+            .filter(|ring| ring_contains(ring, x, y))
+            .count()
+    }
+
+    /// The polygons whose bounding box contains (x, y), which a point-in-polygon test has to
+    /// look at.
+    fn candidates(&self, x: f64, y: f64) -> impl Iterator<Item = PolygonId> + '_ {
+        (0..self.polygons()).map(PolygonId).filter(move |&id| {
+            let (x0, y0, x1, y1) = self.polygon_bounds(id).unwrap();
+            x >= x0 && x <= x1 && y >= y0 && y <= y1
+        })
+    }
+
+    /// Same as `contains`, but also returns the number of candidate polygons for (x, y): those
+    /// whose bounding box contains the point, which an index over the polygons cannot rule out.
+    /// Many candidates in a region mean that the index prunes poorly there, e.g. because of
+    /// large polygons with many vertices, and that simplifying the shapes may pay off.
+    ///
+    /// The candidates are found by scanning all polygons, so this is for diagnostics only.
+    pub fn contains_explain(&self, x: f64, y: f64) -> (bool, usize) {
+        let land = self.contains(x, y);
+
+        let x = super::modulate_longitude(x);
+        let candidates = if super::pole(y).is_some() {
+            0
+        } else {
+            self.candidates(x, y).count()
+        };
+
+        (land, candidates)
+    }
+
+    /// The GSHHG level of (x, y): 0 in the ocean, 1 on land, 2 in a lake, 3 on an island in a
+    /// lake and 4 in a pond on such an island. The level is the nesting depth of the point
+    /// within the rings of the polygons, so lakes must be holes in the land (or separate
+    /// polygons within them), see `rings_around`. Odd levels are land and even levels water.
+    ///
+    /// The bundled shapes only hold level 1 (lakes are part of the land), so for those the level
+    /// is either 0 or 1.
+    ///
+    /// Panics for latitudes outside [-90, 90] like `contains`. `RoaringLandmask::level` handles
+    /// those by its `OutOfDomain` policy instead.
+    pub fn level(&self, x: f64, y: f64) -> u8 {
+        let x = super::modulate_longitude(x);
+        assert!(y >= -90. && y <= 90.);
+
+        if let Some(land) = super::pole(y) {
+            return land as u8;
+        }
+
+        self.level_unchecked(x, y)
+    }
+
+    /// Same as `level`, but does not check for bounds or handle the poles, see
+    /// `contains_unchecked`.
+    pub(crate) fn level_unchecked(&self, x: f64, y: f64) -> u8 {
+        self.rings_around(off_antimeridian(x), y)
+            .min(u8::MAX as usize) as u8
+    }
+
+    /// The bounding box of polygon `id`, or `None` if there is no such polygon.
+    pub fn polygon_bounds(&self, id: PolygonId) -> Option<BBox> {
+        self.inner
+            .bounds
+            .get_or_init(|| {
+                (0..self.polygons())
+                    .map(|i| {
+                        self.polygon_rings(PolygonId(i)).unwrap()[0].iter().fold(
+                            (
+                                f64::INFINITY,
+                                f64::INFINITY,
+                                f64::NEG_INFINITY,
+                                f64::NEG_INFINITY,
+                            ),
+                            |(x0, y0, x1, y1), &(x, y)| {
+                                (x0.min(x), y0.min(y), x1.max(x), y1.max(y))
+                            },
+                        )
+                    })
+                    .collect()
+            })
+            .get(id.0)
+            .copied()
+    }
+
+    /// The polygon that (x, y) is inside, not counting points in its holes, or `None` if it is in
+    /// none of them. Points on the South Pole are looked up just north of it, where Antarctica
+    /// is closed along the pole, and the North Pole is in no polygon.
+    pub fn containing_polygon(&self, x: f64, y: f64) -> Option<PolygonId> {
+        let x = super::modulate_longitude(x);
+        assert!(y >= -90. && y <= 90.);
+
+        if y == 90. {
+            return None;
+        }
+        let y = y.max(-90. + 1e-9);
+
+        (0..self.polygons()).map(PolygonId).find(|&id| {
+            let (x0, y0, x1, y1) = self.polygon_bounds(id).unwrap();
+            if x < x0 || x > x1 || y < y0 || y > y1 {
+                return false;
+            }
+
+            let rings = self.polygon_rings(id).unwrap();
+            ring_contains(&rings[0], x, y) && !rings[1..].iter().any(|r| ring_contains(r, x, y))
+        })
+    }
+
+    /// The landmass that polygon `id` is part of, identified by its first polygon, or `None` if
+    /// there is no such polygon.
+    ///
+    /// GSHHG splits land which crosses the antimeridian, e.g. Eurasia at the Chukchi Peninsula,
+    /// into a polygon on either side. Polygons with edges along the antimeridian at 180 and -180
+    /// that overlap in latitude are joined into one landmass.
+    pub fn landmass(&self, id: PolygonId) -> Option<PolygonId> {
+        self.inner
+            .landmasses
+            .get_or_init(|| {
+                // The latitudes of the edges along the antimeridian, on either side.
+                let mut east = Vec::new();
+                let mut west = Vec::new();
+                for i in 0..self.polygons() {
+                    for w in self.polygon_rings(PolygonId(i)).unwrap()[0].windows(2) {
+                        let ((x0, y0), (x1, y1)) = (w[0], w[1]);
+                        let (a, b) = (y0.min(y1), y0.max(y1));
+
+                        if x0 == 180. && x1 == 180. && a < b {
+                            east.push((i, a, b));
+                        } else if x0 == -180. && x1 == -180. && a < b {
+                            west.push((i, a, b));
+                        }
+                    }
+                }
+
+                let mut parent: Vec<usize> = (0..self.polygons()).collect();
+                fn root(parent: &mut [usize], mut i: usize) -> usize {
+                    while parent[i] != i {
+                        parent[i] = parent[parent[i]];
+                        i = parent[i];
+                    }
+                    i
+                }
+
+                for &(i, a, b) in &east {
+                    for &(j, c, d) in &west {
+                        if a < d && c < b {
+                            let (i, j) = (root(&mut parent, i), root(&mut parent, j));
+                            parent[i.max(j)] = i.min(j);
+                        }
+                    }
+                }
+
+                (0..self.polygons())
+                    .map(|i| PolygonId(root(&mut parent, i)))
+                    .collect()
+            })
+            .get(id.0)
+            .copied()
+    }
+
+    /// Area of polygon `id` on the sphere in square meters, without its holes, or `None` if there
+    /// is no such polygon.
+    pub fn polygon_area_m2(&self, id: PolygonId) -> Option<f64> {
+        self.polygon_area_m2_on(id, Earth::Sphere)
+    }
+
+    /// Same as `polygon_area_m2`, but on `earth`, see `Earth::ring_area_m2`.
+    pub fn polygon_area_m2_on(&self, id: PolygonId, earth: Earth) -> Option<f64> {
+        let area = |ring: &Ring| earth.ring_area_m2(ring);
+
+        let rings = self.polygon_rings(id)?;
+        Some(area(&rings[0]) - rings[1..].iter().map(area).sum::<f64>())
+    }
+
+    /// Area-weighted centroid (longitude, latitude) of the outer ring of polygon `id` on the
+    /// sphere, e.g. to label the island that `containing_polygon` found, or `None` if there is no
+    /// such polygon. The holes are ignored, and a landmass split at the antimeridian (see
+    /// `landmass`) has a centroid for each part.
+    ///
+    /// The centroid is the direction of the first moment of the area, which is half the sum over
+    /// the edges of their length times the normal of their great circle. It is not necessarily
+    /// inside the polygon, e.g. for a crescent-shaped island.
+    pub fn polygon_centroid(&self, id: PolygonId) -> Option<(f64, f64)> {
+        let unit = |(x, y): (f64, f64)| {
+            let (x, y) = (x.to_radians(), y.to_radians());
+            [y.cos() * x.cos(), y.cos() * x.sin(), y.sin()]
+        };
+
+        let ring = self.polygon_rings(id)?[0]
+            .iter()
+            .map(|&p| unit(p))
+            .collect::<Vec<_>>();
+
+        let mut moment = [0.; 3];
+        let mut mean = [0.; 3];
+        for w in ring.windows(2) {
+            let (a, b) = (w[0], w[1]);
+            let n = [
+                a[1] * b[2] - a[2] * b[1],
+                a[2] * b[0] - a[0] * b[2],
+                a[0] * b[1] - a[1] * b[0],
+            ];
+            let sin = n.iter().map(|v| v * v).sum::<f64>().sqrt();
+            let cos = (0..3).map(|k| a[k] * b[k]).sum::<f64>();
+
+            for k in 0..3 {
+                mean[k] += a[k];
+                if sin > 0. {
+                    moment[k] += n[k] / sin * sin.atan2(cos) / 2.;
+                }
+            }
+        }
+
+        // The moment points into the polygon if the ring is counter-clockwise, and out of it
+        // otherwise.
+        if (0..3).map(|k| moment[k] * mean[k]).sum::<f64>() < 0. {
+            moment = moment.map(|v| -v);
+        }
+
+        let [x, y, z] = moment;
+        Some((
+            y.atan2(x).to_degrees(),
+            z.atan2((x * x + y * y).sqrt()).to_degrees(),
+        ))
+    }
+
+    /// The edge of the polygons closest to (x, y) within `radius`, and its distance. Distances
+    /// are in degrees of latitude on a plane tangent at (x, y), with longitudes scaled by the
+    /// cosine of the latitude, so they are only accurate for small radii. Returns `None` if no
+    /// edge is that close.
+    ///
+    /// Edges along the antimeridian and the South Pole are skipped, GSHHG only has them to close
+    /// the polygons that cross them. The polygons are scanned linearly, skipping those whose
+    /// bounding box is further away than `radius`.
+    pub fn nearest_edge(&self, x: f64, y: f64, radius: f64) -> Option<(Segment, f64)> {
+        let x = super::modulate_longitude(x);
+        let k = y.to_radians().cos().max(1e-6);
+        let rx = radius / k;
+
+        let project = |(px, py): (f64, f64)| {
+            let dx = (px - x + 180.).rem_euclid(360.) - 180.;
+            (dx * k, py - y)
+        };
+        let mut best: Option<(Segment, f64)> = None;
+
+        for id in (0..self.polygons()).map(PolygonId) {
+            let (x0, y0, x1, y1) = self.polygon_bounds(id).unwrap();
+            if y0 > y + radius || y1 < y - radius {
+                continue;
+            }
+            if rx < 180.
+                && ![-360., 0., 360.]
+                    .iter()
+                    .any(|s| x0 + s <= x + rx && x1 + s >= x - rx)
+            {
+                continue;
+            }
+
+            for w in self
+                .polygon_rings(id)
+                .unwrap()
+                .iter()
+                .flat_map(|r| r.windows(2))
+            {
+                let (a, b) = (project(w[0]), project(w[1]));
+                if (a.1 > radius && b.1 > radius) || (a.1 < -radius && b.1 < -radius) {
+                    continue;
+                }
+                if closing_edge(w[0], w[1]) {
+                    continue;
+                }
+
+                // The distance from the origin to the closest point on a-b.
+                let (ex, ey) = (b.0 - a.0, b.1 - a.1);
+                let len = ex * ex + ey * ey;
+                let t = if len > 0. {
+                    (-(a.0 * ex + a.1 * ey) / len).clamp(0., 1.)
+                } else {
+                    0.
+                };
+                let d = (a.0 + t * ex).hypot(a.1 + t * ey);
+
+                if d <= radius && best.map_or(true, |(_, bd)| d < bd) {
+                    best = Some(((w[0], w[1]), d));
+                }
+            }
+        }
+
+        best
+    }
+
+    /// The coastline of the polygons which intersect `bbox` (see `landmasses_in_bbox`) as
+    /// `geo` lines, with the coastline of lakes and other holes if `holes` is set. Requires the
+    /// `geo` feature.
+    ///
+    /// The rings are split where they run along the antimeridian or the South Pole (see
+    /// `nearest_edge`), so that lines do not run along the edge of a map. A ring without such
+    /// edges is a single closed line.
+    #[cfg(feature = "geo")]
+    pub fn coastlines(&self, bbox: BBox, holes: bool) -> Vec<geo_types::LineString<f64>> {
+        let mut lines = Vec::new();
+
+        for id in self.landmasses_in_bbox(bbox) {
+            let rings = self.polygon_rings(id).unwrap();
+            let rings = if holes { rings } else { &rings[..1] };
+
+            for ring in rings {
+                let m = ring.len().saturating_sub(1);
+
+                // Start after a closing edge, so that no line is split at the start of the ring.
+                let start = (0..m)
+                    .find(|&j| closing_edge(ring[j], ring[j + 1]))
+                    .map_or(0, |j| j + 1);
+
+                let mut line: Vec<(f64, f64)> = Vec::new();
+                for j in (0..m).map(|t| (start + t) % m) {
+                    let (a, b) = (ring[j], ring[j + 1]);
+
+                    if closing_edge(a, b) {
+                        if line.len() > 1 {
+                            lines.push(std::mem::take(&mut line).into());
+                        }
+                        line.clear();
+                        continue;
+                    }
+
+                    if line.is_empty() {
+                        line.push(a);
+                    }
+                    line.push(b);
+                }
+
+                if line.len() > 1 {
+                    lines.push(line.into());
+                }
+            }
+        }
+
+        lines
+    }
+
+    /// The polygons which intersect `bbox` (min longitude, min latitude, max longitude, max
+    /// latitude). A box with a min longitude greater than its max longitude crosses the
+    /// antimeridian.
+    ///
+    /// The polygons are first filtered by their bounding boxes, and the remaining ones are
+    /// checked exactly.
+    pub fn landmasses_in_bbox(&self, bbox: BBox) -> Vec<PolygonId> {
+        let (x0, y0, x1, y1) = bbox;
+
+        let boxes = if x0 > x1 {
+            vec![(x0, y0, 180., y1), (-180., y0, x1, y1)]
+        } else {
+            vec![bbox]
+        };
+
+        let overlaps = |a: BBox, b: BBox| a.0 <= b.2 && b.0 <= a.2 && a.1 <= b.3 && b.1 <= a.3;
+
+        let geoms = boxes
+            .iter()
+            .map(|&(x0, y0, x1, y1)| {
+                Geometry::new_from_wkt(&format!(
+                    "POLYGON(({x0} {y0}, {x1} {y0}, {x1} {y1}, {x0} {y1}, {x0} {y0}))",
+                ))
+                .unwrap()
+            })
+            .collect::<Vec<_>>();
+
+        (0..self.polygons())
+            .map(PolygonId)
+            .filter(|&id| {
+                let bounds = self.polygon_bounds(id).unwrap();
+
+                boxes.iter().zip(&geoms).any(|(b, g)| {
+                    overlaps(bounds, *b)
+                        && self
+                            .inner
+                            .geom
+                            .get_geometry_n(id.0)
+                            .and_then(|p| p.intersects(g))
+                            .unwrap()
+                })
+            })
+            .collect()
+    }
+
+    /// Replace the Antarctic `ice_front` (GSHHG level 5) in the shapes with the
+    /// `grounding_line` (GSHHG level 6), so that the ice shelves become ocean. Neither level is
+    /// bundled, they can be converted from the GSHHG shapefiles.
+    pub fn with_grounding_line(
+        &self,
+        ice_front: &Geometry,
+        grounding_line: &Geometry,
+    ) -> io::Result<Gshhg> {
+        let geom = self
+            .inner
+            .geom
+            .difference(ice_front)
+            .and_then(|g| g.union(grounding_line))
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "cannot replace Antarctica"))?;
+
+        let mut shapes = Gshhg::from_geom(geom)?;
+        shapes.predicate = Arc::clone(&self.predicate);
+        Ok(shapes)
+    }
+
+    /// Remove the `rivermouths` polygons from the shapes, so that estuaries which GSHHG closes
+    /// across the river mouth become water. The polygons can be drawn, taken from e.g. the WDBII
+    /// rivers, or made from the closures found by `rivermouth_closures`, see `open_rivermouths`.
+    /// The `RoaringMask` still covers the removed land, so the shapes decide for points in the
+    /// river mouths.
+    pub fn with_rivermouths_as_water(&self, rivermouths: &Geometry) -> io::Result<Gshhg> {
+        let geom = self.inner.geom.difference(rivermouths).map_err(|_| {
+            io::Error::new(io::ErrorKind::InvalidData, "cannot remove river mouths")
+        })?;
+
+        let mut shapes = Gshhg::from_geom(geom)?;
+        shapes.predicate = Arc::clone(&self.predicate);
+        Ok(shapes)
+    }
+
+    /// The edges of the exterior rings which are at least `min_width_m` meters long, as
+    /// (start, end) with the land on the left.
+    ///
+    /// At full resolution the shoreline has a vertex every few hundred meters, where GSHHG
+    /// closes it across a river mouth or another inlet it is a single straight edge. These are
+    /// the candidates, but straight shorelines which are digitized coarsely (e.g. harbour walls
+    /// or the Antarctic ice front) are found as well, so check them before opening them with
+    /// `open_rivermouths`. The edges along the antimeridian and the South Pole, where the
+    /// polygons are cut, are skipped.
+    pub fn rivermouth_closures(&self, min_width_m: f64) -> Vec<Segment> {
+        let cut = |((x0, y0), (x1, y1)): Segment| {
+            (x0.abs() == 180. && x0 == x1) || y0 == -90. || y1 == -90.
+        };
+
+        (0..self.polygons())
+            .filter_map(|i| self.polygon_rings(PolygonId(i)))
+            .filter_map(|rings| rings.first())
+            .flat_map(|ring| {
+                // Shoelace formula, the interior is on the left of a counter-clockwise ring.
+                let ccw = ring
+                    .windows(2)
+                    .map(|w| w[0].0 * w[1].1 - w[1].0 * w[0].1)
+                    .sum::<f64>()
+                    > 0.;
+
+                ring.windows(2)
+                    .map(move |w| if ccw { (w[0], w[1]) } else { (w[1], w[0]) })
+            })
+            .filter(|&e| !cut(e))
+            .filter(|&((x0, y0), (x1, y1))| haversine(x0, y0, x1, y1) >= min_width_m)
+            .collect()
+    }
+
+    /// Open the closures found by `rivermouth_closures`: the land in the half-disc behind each
+    /// closing edge, with the edge as its diameter, becomes water. This is a rough estimate of
+    /// the estuary, to open specific river mouths properly pass their polygons to
+    /// `with_rivermouths_as_water`.
+    pub fn open_rivermouths(&self, min_width_m: f64) -> io::Result<Gshhg> {
+        const STEPS: u32 = 16;
+
+        let half_disc = |((x0, y0), (x1, y1)): Segment| -> Vec<Ring> {
+            let (mx, my) = ((x0 + x1) / 2., (y0 + y1) / 2.);
+            // Longitudes are scaled to be comparable to latitudes around the edge.
+            let k = my.to_radians().cos().max(1e-6);
+            let (ux, uy) = ((x1 - mx) * k, y1 - my);
+
+            let mut ring: Ring = (0..=STEPS)
+                .map(|i| f64::from(i) / f64::from(STEPS) * std::f64::consts::PI)
+                .map(|a| {
+                    // From the end of the edge around its left side to the start.
+                    let (c, s) = (a.cos(), a.sin());
+                    (mx + (ux * c - uy * s) / k, my + uy * c + ux * s)
+                })
+                .collect();
+            ring.push(ring[0]);
+
+            vec![ring]
+        };
+
+        let discs = self
+            .rivermouth_closures(min_width_m)
+            .into_iter()
+            .map(half_disc)
+            .collect::<Vec<_>>();
+
+        let rivermouths = multipolygon(&discs)
+            .and_then(|g| g.unary_union())
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "cannot open river mouths"))?;
+
+        self.with_rivermouths_as_water(&rivermouths)
+    }
+
+    /// These shapes with the land within `bbox` (west, south, east, north) replaced by the land
+    /// of `other` within it, e.g. authoritative regional coastlines. `bbox` must not cross the
+    /// antimeridian (east > west). Coastlines which cross the edge of `bbox` are cut there, so
+    /// where the two sources disagree the seam has a step along the edge.
+    ///
+    /// Only the polygons whose bounding box meets `bbox` are cut and merged, the others are
+    /// copied as they are, so the time depends on the polygons around `bbox` and not on all of
+    /// the shapes.
+    pub fn overlay(&self, other: &Gshhg, bbox: BBox) -> io::Result<Gshhg> {
+        let (w, s, e, n) = bbox;
+        let rect = Geometry::new_from_wkt(&format!(
+            "POLYGON(({w} {s}, {e} {s}, {e} {n}, {w} {n}, {w} {s}))"
+        ))
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "invalid bbox"))?;
+
+        let meets = |(x0, y0, x1, y1): BBox| x0 <= e && w <= x1 && y0 <= n && s <= y1;
+        let near = |shapes: &Gshhg| {
+            (0..shapes.polygons())
+                .map(PolygonId)
+                .partition::<Vec<_>, _>(|&id| meets(shapes.polygon_bounds(id).unwrap()))
+        };
+        fn rings<'a>(shapes: &'a Gshhg, ids: &[PolygonId]) -> Vec<&'a [Ring]> {
+            ids.iter()
+                .map(|&id| shapes.polygon_rings(id).unwrap())
+                .collect()
+        }
+
+        let (cut, kept) = near(self);
+        let (regional, _) = near(other);
+
+        let merged = multipolygon(&rings(self, &cut))
+            .and_then(|g| g.difference(&rect))
+            .and_then(|outside| {
+                let inside = multipolygon(&rings(other, &regional))?.intersection(&rect)?;
+                outside.union(&inside)
+            })
+            .and_then(|g| extract_rings(&g))
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "cannot overlay shapes"))?;
+
+        let mut polygons = rings(self, &kept);
+        polygons.extend(merged.iter().map(Vec::as_slice));
+
+        let geom = multipolygon(&polygons)
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "cannot overlay shapes"))?;
+
+        let mut shapes = Gshhg::from_geom(geom)?;
+        shapes.predicate = Arc::clone(&self.predicate);
+        Ok(shapes)
+    }
+
+    /// Simplify the shapes so that no vertex is moved more than about `tolerance_m` meters.
+    ///
+    /// The simplification is done with Douglas-Peucker in degrees while preserving the topology
+    /// of the polygons, the tolerance is converted using the length of a degree of latitude. This
+    /// trades accuracy of the coastline for faster and smaller shapes.
+    ///
+    /// The simplified coastline can move out into cells which the mask has as ocean, so the mask
+    /// must be rasterized again from the simplified shapes, see `RoaringLandmask::with_shapes`.
+    pub fn simplify(&self, tolerance_m: f64) -> io::Result<Gshhg> {
+        let tolerance = (tolerance_m / crate::distance::EARTH_RADIUS).to_degrees();
+
+        let geom = self
+            .inner
+            .geom
+            .topology_preserve_simplify(tolerance)
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "cannot simplify geometry"))?;
+
+        let mut shapes = Gshhg::from_geom(geom)?;
+        shapes.predicate = Arc::clone(&self.predicate);
+        Ok(shapes)
+    }
+
+    /// The shapes as WKB, uncompressed.
+    pub fn to_wkb(&self) -> io::Result<Vec<u8>> {
+        let wkb = self
+            .inner
+            .geom
+            .to_wkb()
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "cannot write geometry"))?;
+
+        Ok(wkb.as_ref().to_vec())
+    }
+
+    /// Write the index of the polygons to `path`, so that it can be loaded with `load_index`
+    /// instead of being built again. The index is the bounding box and landmass of every
+    /// polygon, which `containing_polygon`, `rings_around` and `landmasses_in_bbox` use to skip
+    /// polygons, see `polygon_bounds` and `landmass`. It is built first if needed.
+    ///
+    /// The file holds the number of polygons and vertices of the shapes, which are cheap to
+    /// check, so that it is only loaded for shapes of the same size, and the CRC32 of the
+    /// entries, so that a damaged file is not loaded. Other shapes of the same size are not
+    /// detected, keep the index together with the shapes it was saved for.
+    pub fn save_index<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let n = self.polygons();
+
+        let mut entries = Vec::with_capacity(n * 40);
+        for id in (0..n).map(PolygonId) {
+            let (x0, y0, x1, y1) = self.polygon_bounds(id).unwrap();
+            for v in [x0, y0, x1, y1] {
+                entries.extend_from_slice(&v.to_le_bytes());
+            }
+            entries.extend_from_slice(&(self.landmass(id).unwrap().0 as u64).to_le_bytes());
+        }
+
+        let mut fd = io::BufWriter::new(File::create(path)?);
+        fd.write_all(INDEX_MAGIC)?;
+        fd.write_all(&INDEX_VERSION.to_le_bytes())?;
+        fd.write_all(&crc32fast::hash(&entries).to_le_bytes())?;
+        fd.write_all(&(n as u64).to_le_bytes())?;
+        fd.write_all(&(self.vertices() as u64).to_le_bytes())?;
+        fd.write_all(&entries)?;
+
+        fd.flush()
+    }
+
+    /// Read an index written by `save_index` from `path`. Fails if the file is not an index of
+    /// these shapes. Does nothing for the parts of the index which have already been built.
+    pub fn load_index<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let invalid = |msg| io::Error::new(io::ErrorKind::InvalidData, msg);
+
+        let mut buf = Vec::new();
+        File::open(path)?.read_to_end(&mut buf)?;
+
+        if buf.len() < INDEX_HEADER || &buf[..4] != INDEX_MAGIC {
+            return Err(invalid("not a polygon index"));
+        }
+
+        let u32_at = |i: usize| u32::from_le_bytes(buf[i..i + 4].try_into().unwrap());
+        let u64_at = |i: usize| u64::from_le_bytes(buf[i..i + 8].try_into().unwrap());
+
+        if u32_at(4) != INDEX_VERSION {
+            return Err(invalid("unsupported polygon index version"));
+        }
+
+        let n = self.polygons();
+        if u64_at(12) != n as u64
+            || u64_at(20) != self.vertices() as u64
+            || buf.len() != INDEX_HEADER + n * 40
+        {
+            return Err(invalid("polygon index does not match the shapes"));
+        }
+
+        if u32_at(8) != crc32fast::hash(&buf[INDEX_HEADER..]) {
+            return Err(invalid("polygon index is damaged"));
+        }
+
+        let entry = |i: usize| INDEX_HEADER + 40 * i;
+        let f64_at = |i: usize| f64::from_bits(u64_at(i));
+
+        let bounds = (0..n)
+            .map(|i| {
+                let e = entry(i);
+                (f64_at(e), f64_at(e + 8), f64_at(e + 16), f64_at(e + 24))
+            })
+            .collect();
+        let landmasses = (0..n)
+            .map(|i| PolygonId(u64_at(entry(i) + 32) as usize))
+            .collect();
+
+        let _ = self.inner.bounds.set(bounds);
+        let _ = self.inner.landmasses.set(landmasses);
+
+        Ok(())
+    }
 }
 
 #[pymethods]
 impl Gshhg {
     /// Make a new Gshhg shapes instance.
     #[staticmethod]
-    pub fn new(py: Python) -> io::Result<Self> {
-        let buf = Gshhg::wkb(py)?;
-        let g = geos::Geometry::new_from_wkb(buf.as_bytes()).unwrap();
+    pub fn new(_py: Python) -> io::Result<Self> {
+        Gshhg::embedded()
+    }
+
+    /// Make shapes from polygons given as WKB, e.g. to overlay custom zones with
+    /// `RoaringLandmask::contains_with_extra`.
+    #[staticmethod]
+    pub fn from_wkb(wkb: &[u8]) -> io::Result<Gshhg> {
+        let g = geos::Geometry::new_from_wkb(wkb)
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "cannot read geometry"))?;
         Gshhg::from_geom(g)
     }
 
     /// Get the WKB for the GSHHG shapes (full resolution).
     #[staticmethod]
     pub fn wkb(py: Python) -> io::Result<&PyBytes> {
-        use crate::GsshgData;
+        Ok(PyBytes::new(py, &Gshhg::embedded_wkb()?))
+    }
 
-        let buf = GsshgData::get(&GSHHS_F)
-            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "cannot find shapes"))?;
-        let buf: &[u8] = buf.data.borrow();
-        let mut fd = xz2::read::XzDecoder::new(buf);
+    /// Make shapes from polygons given as lists of rings of (longitude, latitude) vertices, see
+    /// `Gshhg::from_rings`.
+    #[staticmethod]
+    #[pyo3(name = "from_rings", signature = (polygons, close_rings = false))]
+    fn py_from_rings(polygons: Vec<Vec<Ring>>, close_rings: bool) -> io::Result<Gshhg> {
+        Gshhg::from_rings(polygons, close_rings)
+    }
 
-        let mut buf = Vec::new();
-        fd.read_to_end(&mut buf)?;
+    /// Describe the issues found by `Gshhg::validate`, one per line of the list.
+    #[pyo3(name = "validate")]
+    fn py_validate(&self) -> Vec<String> {
+        self.validate().iter().map(ToString::to_string).collect()
+    }
+
+    #[pyo3(name = "simplify")]
+    fn py_simplify(&self, tolerance_m: f64) -> io::Result<Gshhg> {
+        self.simplify(tolerance_m)
+    }
+
+    #[pyo3(name = "rivermouth_closures")]
+    fn py_rivermouth_closures(&self, min_width_m: f64) -> Vec<Segment> {
+        self.rivermouth_closures(min_width_m)
+    }
+
+    #[pyo3(name = "open_rivermouths")]
+    fn py_open_rivermouths(&self, min_width_m: f64) -> io::Result<Gshhg> {
+        self.open_rivermouths(min_width_m)
+    }
 
-        Ok(PyBytes::new(py, &buf))
+    #[pyo3(name = "polygon_rings")]
+    fn py_polygon_rings(&self, id: usize) -> Option<Vec<Ring>> {
+        self.polygon_rings(PolygonId(id)).map(<[Ring]>::to_vec)
+    }
+
+    #[pyo3(name = "contains_explain")]
+    fn py_contains_explain(&self, x: f64, y: f64) -> (bool, usize) {
+        self.contains_explain(x, y)
+    }
+
+    #[pyo3(name = "containing_polygon")]
+    fn py_containing_polygon(&self, x: f64, y: f64) -> Option<usize> {
+        self.containing_polygon(x, y).map(|id| id.0)
+    }
+
+    #[pyo3(name = "landmass")]
+    fn py_landmass(&self, id: usize) -> Option<usize> {
+        self.landmass(PolygonId(id)).map(|id| id.0)
+    }
+
+    #[pyo3(name = "polygon_centroid")]
+    fn py_polygon_centroid(&self, id: usize) -> Option<(f64, f64)> {
+        self.polygon_centroid(PolygonId(id))
+    }
+
+    #[pyo3(name = "save_index")]
+    fn py_save_index(&self, path: std::path::PathBuf) -> io::Result<()> {
+        self.save_index(path)
+    }
+
+    #[pyo3(name = "load_index")]
+    fn py_load_index(&self, path: std::path::PathBuf) -> io::Result<()> {
+        self.load_index(path)
+    }
+
+    #[pyo3(name = "nearest_edge")]
+    fn py_nearest_edge(&self, x: f64, y: f64, radius: f64) -> Option<(Segment, f64)> {
+        self.nearest_edge(x, y, radius)
+    }
+
+    #[pyo3(name = "landmasses_in_bbox")]
+    fn py_landmasses_in_bbox(&self, bbox: BBox) -> Vec<usize> {
+        self.landmasses_in_bbox(bbox)
+            .into_iter()
+            .map(|id| id.0)
+            .collect()
+    }
+
+    /// Number of polygons in the shapes.
+    #[getter]
+    pub fn polygons(&self) -> usize {
+        self.inner.geom.get_num_geometries().unwrap()
+    }
+
+    /// Number of vertices in the shapes.
+    #[getter]
+    pub fn vertices(&self) -> usize {
+        self.inner.geom.get_num_coordinates().unwrap()
     }
 
     /// Check if point (x, y) is on land.
@@ -110,22 +1341,28 @@ impl Gshhg {
     /// `x` is longitude, [-180, 180] east
     /// `y` is latitude,  [- 90,  90] north
     ///
-    /// Returns `true` if the point is on land.
+    /// Returns `true` if the point is on land. The North Pole is always in the ocean and the
+    /// South Pole is always on land.
+    ///
+    /// GSHHG sometimes closes the shoreline across river mouths, so that water in the inner part
+    /// of an estuary is land. Use `rivermouth_closures` to find such closures and
+    /// `open_rivermouths` or `with_rivermouths_as_water` to open them.
     pub fn contains(&self, x: f64, y: f64) -> bool {
         let x = super::modulate_longitude(x);
         debug_assert!(x >= -180. && x <= 180.);
-        assert!(y > -90. && y <= 90.);
+        assert!(y >= -90. && y <= 90.);
 
-        let point = CoordSeq::new_from_vec(&[&[x as f64, y as f64]]).unwrap();
-        let point = Geometry::create_point(point).unwrap();
-        self.prepped.contains(&point).unwrap()
+        if let Some(land) = super::pole(y) {
+            return land;
+        }
+
+        self.contains_unchecked(x, y)
     }
 
-    /// Same as `contains`, but does not check for bounds.
+    /// Same as `contains`, but does not check for bounds or handle the poles. Points on the
+    /// antimeridian are moved off it, see `off_antimeridian`.
     pub(crate) fn contains_unchecked(&self, x: f64, y: f64) -> bool {
-        let point = CoordSeq::new_from_vec(&[&[x, y]]).unwrap();
-        let point = Geometry::create_point(point).unwrap();
-        self.prepped.contains(&point).unwrap()
+        self.predicate.contains(self, off_antimeridian(x), y)
     }
 
     pub fn contains_many(
@@ -133,15 +1370,16 @@ impl Gshhg {
         py: Python,
         x: PyReadonlyArrayDyn<f64>,
         y: PyReadonlyArrayDyn<f64>,
-    ) -> Py<PyArray<bool, numpy::Ix1>> {
+    ) -> PyResult<Py<PyArray<bool, numpy::Ix1>>> {
         let x = x.as_array();
         let y = y.as_array();
+        crate::check_shape(x.shape(), y.shape())?;
 
-        PyArray::from_iter(
+        Ok(PyArray::from_iter(
             py,
             x.iter().zip(y.iter()).map(|(x, y)| self.contains(*x, *y)),
         )
-        .to_owned()
+        .to_owned())
     }
 
     pub fn contains_many_par(
@@ -149,15 +1387,16 @@ impl Gshhg {
         py: Python,
         x: PyReadonlyArrayDyn<f64>,
         y: PyReadonlyArrayDyn<f64>,
-    ) -> Py<PyArray<bool, numpy::IxDyn>> {
+    ) -> PyResult<Py<PyArray<bool, numpy::IxDyn>>> {
         let x = x.as_array();
         let y = y.as_array();
+        crate::check_shape(x.shape(), y.shape())?;
 
         use ndarray::Zip;
         let contains = Zip::from(&x)
             .and(&y)
             .par_map_collect(|x, y| self.contains(*x, *y));
-        PyArray::from_owned_array(py, contains).to_owned()
+        Ok(PyArray::from_owned_array(py, contains).to_owned())
     }
 }
 
@@ -194,9 +1433,493 @@ mod tests {
         Python::with_gil(|py| {
             let mask = Gshhg::new(py).unwrap();
             assert!(mask.contains(5., -89.99));
+            assert!(mask.contains(5., -90.));
+            assert!(mask.contains(-175., -90.));
         })
     }
 
+    #[test]
+    fn test_polygon_rings() {
+        let g = Geometry::new_from_wkt(
+            "MULTIPOLYGON(((0 0, 4 0, 4 4, 0 4, 0 0), (1 1, 1 2, 2 2, 2 1, 1 1)), ((10 10, 11 10, 11 11, 10 10)))",
+        )
+        .unwrap();
+        let s = Gshhg::from_geom(g).unwrap();
+
+        assert_eq!(s.polygons(), 2);
+
+        let rings = s.polygon_rings(PolygonId(0)).unwrap();
+        assert_eq!(rings.len(), 2);
+        assert_eq!(rings[0].len(), 5);
+        assert_eq!(rings[1][1], (1., 2.));
+
+        let rings = s.polygon_rings(PolygonId(1)).unwrap();
+        assert_eq!(
+            rings,
+            &[vec![(10., 10.), (11., 10.), (11., 11.), (10., 10.)]]
+        );
+
+        assert!(s.polygon_rings(PolygonId(2)).is_none());
+    }
+
+    #[test]
+    fn test_validate() {
+        let square = vec![(0., 0.), (4., 0.), (4., 4.), (0., 4.), (0., 0.)];
+        let hole = vec![(1., 1.), (1., 2.), (2., 2.), (2., 1.), (1., 1.)];
+        assert!(ring_issues(&[vec![square.clone(), hole.clone()]]).is_empty());
+
+        // A bow tie crossing itself at (2, 2).
+        let bowtie = vec![(0., 0.), (4., 4.), (4., 0.), (0., 4.), (0., 0.)];
+        let unclosed = vec![(10., 10.), (11., 10.), (11., 11.)];
+        let line = vec![(20., 20.), (21., 21.), (20., 20.)];
+
+        assert_eq!(
+            ring_issues(&[vec![bowtie.clone()], vec![unclosed.clone()], vec![line]]),
+            vec![
+                GeometryIssue::SelfIntersection {
+                    polygon: PolygonId(0),
+                    rings: (0, 0),
+                    at: (2., 2.),
+                },
+                GeometryIssue::UnclosedRing {
+                    polygon: PolygonId(1),
+                    ring: 0,
+                },
+                GeometryIssue::TooFewPoints {
+                    polygon: PolygonId(2),
+                    ring: 0,
+                    points: 3,
+                },
+            ]
+        );
+
+        // A hole sticking out of the polygon, and one touching its corner.
+        let outside = vec![(3., 3.), (3., 5.), (5., 5.), (5., 3.), (3., 3.)];
+        assert_eq!(ring_issues(&[vec![square.clone(), outside]]).len(), 2);
+        let corner = vec![(0., 0.), (1., 2.), (2., 1.), (0., 0.)];
+        assert!(ring_issues(&[vec![square.clone(), corner]]).is_empty());
+
+        assert!(Gshhg::from_rings(vec![vec![unclosed.clone()]], false).is_err());
+        let s = Gshhg::from_rings(vec![vec![unclosed], vec![bowtie]], true).unwrap();
+        assert_eq!(s.polygons(), 2);
+        assert!(s.contains(10.8, 10.5));
+        assert_eq!(s.validate().len(), 1);
+
+        let s = Gshhg::from_rings(vec![vec![square, hole]], false).unwrap();
+        assert!(s.validate().is_empty());
+        assert!(!s.contains(1.5, 1.5));
+    }
+
+    #[test]
+    fn test_landmasses_in_bbox() {
+        let g = Geometry::new_from_wkt(
+            "MULTIPOLYGON(((0 0, 4 0, 4 4, 0 4, 0 0)), ((10 10, 11 10, 11 11, 10 10)), ((178 -1, 180 -1, 180 1, 178 1, 178 -1)))",
+        )
+        .unwrap();
+        let s = Gshhg::from_geom(g).unwrap();
+
+        assert_eq!(s.polygon_bounds(PolygonId(1)), Some((10., 10., 11., 11.)));
+        assert_eq!(s.polygon_bounds(PolygonId(3)), None);
+
+        assert_eq!(
+            s.landmasses_in_bbox((-1., -1., 12., 12.)),
+            vec![PolygonId(0), PolygonId(1)]
+        );
+
+        // Within the bounding box of the triangle, but not within the triangle.
+        assert_eq!(s.landmasses_in_bbox((10., 10.6, 10.4, 10.9)), vec![]);
+
+        // Across the antimeridian.
+        assert_eq!(
+            s.landmasses_in_bbox((179., -5., -179., 5.)),
+            vec![PolygonId(2)]
+        );
+        assert_eq!(s.landmasses_in_bbox((170., -5., 3., 5.)).len(), 2);
+    }
+
+    #[test]
+    fn test_landmass() {
+        // Land split at the antimeridian, a square with a lake holding an island, and a separate
+        // polygon touching the antimeridian further north.
+        let g = Geometry::new_from_wkt(
+            "MULTIPOLYGON(((170 -5, 180 -5, 180 5, 170 5, 170 -5)), ((0 0, 4 0, 4 4, 0 4, 0 0), (1 1, 3 1, 3 3, 1 3, 1 1)), ((1.5 1.5, 2.5 1.5, 2.5 2.5, 1.5 2.5, 1.5 1.5)), ((-180 -1, -175 -1, -175 1, -180 1, -180 -1)), ((-180 10, -175 10, -175 11, -180 11, -180 10)))",
+        )
+        .unwrap();
+        let s = Gshhg::from_geom(g).unwrap();
+
+        assert_eq!(s.containing_polygon(175., 0.), Some(PolygonId(0)));
+        assert_eq!(s.containing_polygon(-177., 0.), Some(PolygonId(3)));
+        assert_eq!(s.containing_polygon(0.5, 0.5), Some(PolygonId(1)));
+        assert_eq!(s.containing_polygon(1.2, 1.2), None);
+        assert_eq!(s.containing_polygon(2., 2.), Some(PolygonId(2)));
+        assert_eq!(s.containing_polygon(50., 50.), None);
+        assert_eq!(s.containing_polygon(0., 90.), None);
+
+        assert_eq!(s.landmass(PolygonId(3)), Some(PolygonId(0)));
+        assert_eq!(s.landmass(PolygonId(0)), Some(PolygonId(0)));
+        assert_eq!(s.landmass(PolygonId(2)), Some(PolygonId(2)));
+        assert_eq!(s.landmass(PolygonId(4)), Some(PolygonId(4)));
+        assert_eq!(s.landmass(PolygonId(5)), None);
+
+        // Five by two degrees across the equator, and a square with a quarter as a hole.
+        let a = s.polygon_area_m2(PolygonId(3)).unwrap() / 1e6;
+        assert!((a - 10. * 12_364.).abs() < 10. * 50., "{a}");
+        let a = s.polygon_area_m2(PolygonId(1)).unwrap();
+        let b = s.polygon_area_m2(PolygonId(2)).unwrap();
+        assert!((a / b - 12.).abs() < 0.1);
+        assert_eq!(s.polygon_area_m2(PolygonId(5)), None);
+
+        // On the ellipsoid a square at the equator is smaller.
+        let a = s.polygon_area_m2_on(PolygonId(3), Earth::Wgs84).unwrap() / 1e6;
+        assert!((a - 10. * 12_308.).abs() < 10. * 50., "{a}");
+
+        // The hole is ignored, and the ten by ten degrees are centered on the 175th meridian.
+        let (x, y) = s.polygon_centroid(PolygonId(1)).unwrap();
+        assert!((x - 2.).abs() < 1e-9 && (y - 2.).abs() < 0.01, "{x}, {y}");
+        let (x, y) = s.polygon_centroid(PolygonId(0)).unwrap();
+        assert!((x - 175.).abs() < 1e-9 && y.abs() < 1e-9, "{x}, {y}");
+        assert_eq!(s.polygon_centroid(PolygonId(5)), None);
+
+        // The edges are great circles, which bulge towards the pole between the corners, so the
+        // centroid is north of the middle latitude.
+        let g = Geometry::new_from_wkt("POLYGON((-10 50, -10 70, 30 70, 30 50, -10 50))").unwrap();
+        let (x, y) = Gshhg::from_geom(g)
+            .unwrap()
+            .polygon_centroid(PolygonId(0))
+            .unwrap();
+        assert!((x - 10.).abs() < 1e-9 && y > 60. && y < 61., "{x}, {y}");
+    }
+
+    #[test]
+    fn test_index() {
+        let g = Geometry::new_from_wkt(
+            "MULTIPOLYGON(((170 -5, 180 -5, 180 5, 170 5, 170 -5)), ((-180 -1, -175 -1, -175 1, -180 1, -180 -1)), ((10 10, 11 10, 11 11, 10 10)))",
+        )
+        .unwrap();
+        let s = Gshhg::from_geom(g.clone()).unwrap();
+
+        let path = std::env::temp_dir().join("roaring-landmask-test.index");
+        s.save_index(&path).unwrap();
+
+        let loaded = Gshhg::from_geom(g).unwrap();
+        loaded.load_index(&path).unwrap();
+        assert_eq!(loaded.inner.bounds.get().unwrap().len(), 3);
+        assert_eq!(
+            loaded.polygon_bounds(PolygonId(2)),
+            Some((10., 10., 11., 11.))
+        );
+        assert_eq!(loaded.landmass(PolygonId(1)), Some(PolygonId(0)));
+        assert_eq!(loaded.containing_polygon(-177., 0.), Some(PolygonId(1)));
+
+        // A damaged entry.
+        let mut damaged = std::fs::read(&path).unwrap();
+        damaged[INDEX_HEADER + 3] ^= 1;
+        let damaged_path = std::env::temp_dir().join("roaring-landmask-test-damaged.index");
+        std::fs::write(&damaged_path, damaged).unwrap();
+        let fresh = Gshhg::from_geom(s.inner.geom.clone()).unwrap();
+        let err = fresh.load_index(&damaged_path).err().unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert!(fresh.inner.bounds.get().is_none());
+        std::fs::remove_file(&damaged_path).unwrap();
+
+        // Other shapes with the same number of polygons, but not of vertices.
+        let other = Geometry::new_from_wkt(
+            "MULTIPOLYGON(((0 0, 1 0, 1 1, 0 0)), ((2 0, 3 0, 3 1, 2 0)), ((4 0, 5 0, 5 1, 4 0)))",
+        )
+        .unwrap();
+        let other = Gshhg::from_geom(other).unwrap();
+        let err = other.load_index(&path).err().unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert!(other.inner.bounds.get().is_none());
+
+        std::fs::write(&path, b"RLMB").unwrap();
+        assert!(s.load_index(&path).is_err());
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_nearest_edge() {
+        let g = Geometry::new_from_wkt(
+            "MULTIPOLYGON(((0 0, 4 0, 4 4, 0 4, 0 0)), ((170 -5, 180 -5, 180 5, 170 5, 170 -5)))",
+        )
+        .unwrap();
+        let s = Gshhg::from_geom(g).unwrap();
+
+        let (edge, d) = s.nearest_edge(5., 2., 2.).unwrap();
+        assert_eq!(edge, ((4., 0.), (4., 4.)));
+        assert!((d - 1. * 2f64.to_radians().cos()).abs() < 1e-9);
+
+        // From inside.
+        assert_eq!(s.nearest_edge(2., 3.5, 1.).unwrap().0, ((4., 4.), (0., 4.)));
+
+        assert!(s.nearest_edge(5., 2., 0.5).is_none());
+
+        // The edge along the antimeridian is not coast.
+        let (edge, _) = s.nearest_edge(-179., 0., 10.).unwrap();
+        assert_ne!(edge, ((180., -5.), (180., 5.)));
+        assert!(edge.0 .1.abs() == 5. && edge.1 .1.abs() == 5.);
+    }
+
+    #[cfg(feature = "geo")]
+    #[test]
+    fn test_coastlines() {
+        let g = Geometry::new_from_wkt(
+            "MULTIPOLYGON(((0 0, 4 0, 4 4, 0 4, 0 0), (1 1, 1 2, 2 2, 2 1, 1 1)), ((180 -5, 180 5, 170 5, 170 -5, 180 -5)))",
+        )
+        .unwrap();
+        let s = Gshhg::from_geom(g).unwrap();
+
+        let lines = s.coastlines((-1., -1., 5., 5.), false);
+        assert_eq!(lines.len(), 1);
+        assert!(lines[0].is_closed());
+        assert_eq!(lines[0].0.len(), 5);
+
+        assert_eq!(s.coastlines((-1., -1., 5., 5.), true).len(), 2);
+
+        // The edge along the antimeridian is left out, and the rest is a single line.
+        let lines = s.coastlines((175., -1., -175., 1.), false);
+        assert_eq!(lines.len(), 1);
+        let coords = lines[0].coords().map(|c| (c.x, c.y)).collect::<Vec<_>>();
+        assert_eq!(
+            coords,
+            vec![(180., 5.), (170., 5.), (170., -5.), (180., -5.)]
+        );
+    }
+
+    #[test]
+    fn test_predicate() {
+        struct Everywhere;
+
+        impl PointInPolygon for Everywhere {
+            fn contains(&self, _shapes: &Gshhg, _x: f64, _y: f64) -> bool {
+                true
+            }
+        }
+
+        let g = Geometry::new_from_wkt(
+            "MULTIPOLYGON(((0 0, 4 0, 4 4, 0 4, 0 0), (1 1, 1 2, 2 2, 2 1, 1 1)), ((10 10, 11 10, 11 11, 10 10)))",
+        )
+        .unwrap();
+        let s = Gshhg::from_geom(g).unwrap();
+        let ray = s.clone().with_predicate(RayCasting);
+
+        for x in (-20..40).map(|x| x as f64 * 0.3 + 0.05) {
+            for y in (-20..40).map(|y| y as f64 * 0.3 + 0.07) {
+                assert_eq!(s.contains(x, y), ray.contains(x, y));
+            }
+        }
+
+        let s = s.with_predicate(Everywhere);
+        assert!(s.contains(50., 50.));
+        assert!(!s.contains(50., 90.));
+    }
+
+    #[test]
+    fn test_candidates() {
+        // Two overlapping bounding boxes, and one apart.
+        let g = Geometry::new_from_wkt(
+            "MULTIPOLYGON(((0 0, 4 0, 4 4, 0 4, 0 0)), ((3 3, 6 3, 6 6, 3 3)), ((10 10, 11 10, 11 11, 10 10)))",
+        )
+        .unwrap();
+        let s = Gshhg::from_geom(g).unwrap();
+
+        assert_eq!(s.contains_explain(1., 1.), (true, 1));
+        assert_eq!(s.contains_explain(3.5, 3.9), (true, 2));
+        assert_eq!(s.contains_explain(20., 20.), (false, 0));
+        assert_eq!(s.contains_explain(0., -90.), (true, 0));
+    }
+
+    #[test]
+    fn test_level() {
+        // Land with a lake, an island in the lake and a pond on the island.
+        let g = Geometry::new_from_wkt(
+            "MULTIPOLYGON(((0 0, 10 0, 10 10, 0 10, 0 0), (2 2, 8 2, 8 8, 2 8, 2 2)), ((3 3, 7 3, 7 7, 3 7, 3 3), (4 4, 6 4, 6 6, 4 6, 4 4)))",
+        )
+        .unwrap();
+        let s = Gshhg::from_geom(g).unwrap();
+
+        for (x, y, level) in [
+            (20., 20., 0),
+            (1., 1., 1),
+            (2.5, 2.5, 2),
+            (3.5, 3.5, 3),
+            (5., 5., 4),
+        ] {
+            assert_eq!(s.level(x, y), level);
+            assert_eq!(s.contains(x, y), level % 2 == 1);
+        }
+
+        assert_eq!(s.level(0., 90.), 0);
+        assert_eq!(s.level(0., -90.), 1);
+    }
+
+    #[test]
+    fn test_ray_casting_gshhg() {
+        // Along the coast of Norway and across the antimeridian at Fiji.
+        let s = Gshhg::from_compressed(
+            "gshhs/gshhs_f_-180.000000E-90.000000N180.000000E90.000000N.wkb.xz",
+        )
+        .unwrap();
+        let ray = s.clone().with_predicate(RayCasting);
+
+        for (x0, y0) in [(4.5, 59.), (179., -17.5), (-180., -17.5)] {
+            for i in 0..20 {
+                for j in 0..20 {
+                    let (x, y) = (x0 + i as f64 * 0.0513, y0 + j as f64 * 0.0497);
+                    assert_eq!(s.contains(x, y), ray.contains(x, y), "({}, {})", x, y);
+                }
+            }
+        }
+
+        // On the split edges, also when calling the predicates directly.
+        for x in [-180., 180.] {
+            for j in 0..20 {
+                let y = -17.5 + j as f64 * 0.0497;
+                assert_eq!(
+                    s.contains(x, y),
+                    RayCasting.contains(&s, x, y),
+                    "({}, {})",
+                    x,
+                    y
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_default_predicate() {
+        // The default predicate depends on the `index` feature, the results must not.
+        let g = Geometry::new_from_wkt(
+            "MULTIPOLYGON(((0 0, 10 0, 10 10, 0 10, 0 0), (2 2, 4 2, 4 4, 2 4, 2 2)), ((170 -5, 180 -5, 180 5, 170 5, 170 -5)))",
+        )
+        .unwrap();
+        let s = Gshhg::from_geom(g).unwrap();
+        let prepared = s.clone().with_predicate(Prepared);
+        let ray = s.clone().with_predicate(RayCasting);
+
+        for (x, y) in [
+            (1., 1.),
+            (3., 3.),
+            (5., 5.),
+            (11., 1.),
+            (175., 0.),
+            (-175., 0.),
+        ] {
+            assert_eq!(s.contains(x, y), prepared.contains(x, y), "({}, {})", x, y);
+            assert_eq!(s.contains(x, y), ray.contains(x, y), "({}, {})", x, y);
+        }
+        assert!(s.contains(1., 1.));
+        assert!(!s.contains(3., 3.));
+        assert!(s.contains(175., 0.));
+    }
+
+    #[test]
+    fn test_grounding_line() {
+        // An ice sheet with a shelf to the west, and an island.
+        let g = Geometry::new_from_wkt(
+            "MULTIPOLYGON(((0 -80, 10 -80, 10 -70, 0 -70, 0 -80)), ((20 -62, 21 -62, 21 -61, 20 -61, 20 -62)))",
+        )
+        .unwrap();
+        let ice_front =
+            Geometry::new_from_wkt("POLYGON((0 -80, 10 -80, 10 -70, 0 -70, 0 -80))").unwrap();
+        let grounding_line =
+            Geometry::new_from_wkt("POLYGON((2 -80, 10 -80, 10 -70, 2 -70, 2 -80))").unwrap();
+
+        let s = Gshhg::from_geom(g).unwrap();
+        let grounded = s.with_grounding_line(&ice_front, &grounding_line).unwrap();
+
+        // On the ice shelf.
+        assert!(s.contains(1., -75.));
+        assert!(!grounded.contains(1., -75.));
+
+        // On grounded ice and on the island.
+        assert!(s.contains(5., -75.) && grounded.contains(5., -75.));
+        assert!(s.contains(20.5, -61.5) && grounded.contains(20.5, -61.5));
+    }
+
+    #[test]
+    fn test_rivermouths_as_water() {
+        // The coast at the Elbe estuary, closed across the mouth.
+        let g = Geometry::new_from_wkt("POLYGON((8.6 53.5, 10 53.5, 10 54.2, 8.6 54.2, 8.6 53.5))")
+            .unwrap();
+        let estuary =
+            Geometry::new_from_wkt("POLYGON((8.6 53.8, 9.5 53.8, 9.5 53.95, 8.6 53.95, 8.6 53.8))")
+                .unwrap();
+
+        let s = Gshhg::from_geom(g).unwrap();
+        let opened = s.with_rivermouths_as_water(&estuary).unwrap();
+
+        // In the estuary off Brunsbüttel.
+        assert!(s.contains(9.0, 53.88));
+        assert!(!opened.contains(9.0, 53.88));
+
+        // On both banks.
+        assert!(opened.contains(9.0, 53.7));
+        assert!(opened.contains(9.0, 54.1));
+    }
+
+    #[test]
+    fn test_open_rivermouths() {
+        // A square with a vertex every 0.01 degrees, except across a river mouth on the east
+        // coast which is closed by one 0.2 degree (22 km) edge. The ring is clockwise.
+        let side = |(x0, y0): (f64, f64), (x1, y1): (f64, f64)| {
+            (0..100).map(move |i| {
+                let t = f64::from(i) / 100.;
+                (x0 + (x1 - x0) * t, y0 + (y1 - y0) * t)
+            })
+        };
+        let mut ring: Ring = side((0., 0.), (1., 0.))
+            .chain(side((1., 0.), (1., 0.4)))
+            .chain(std::iter::once((1., 0.4)))
+            .chain(side((1., 0.6), (1., 1.)))
+            .chain(side((1., 1.), (0., 1.)))
+            .chain(side((0., 1.), (0., 0.)))
+            .chain(std::iter::once((0., 0.)))
+            .collect();
+        ring.reverse();
+
+        let s = Gshhg::from_rings(vec![vec![ring]], false).unwrap();
+
+        assert_eq!(s.rivermouth_closures(10_000.), vec![((1., 0.4), (1., 0.6))]);
+        assert!(s.rivermouth_closures(50_000.).is_empty());
+
+        let opened = s.open_rivermouths(10_000.).unwrap();
+
+        // In the mouth, and on the banks beside and behind it.
+        assert!(s.contains(0.95, 0.5));
+        assert!(!opened.contains(0.95, 0.5));
+        assert!(opened.contains(0.95, 0.3));
+        assert!(opened.contains(0.95, 0.7));
+        assert!(opened.contains(0.85, 0.5));
+
+        // Nothing is long enough to be opened.
+        assert!(s.open_rivermouths(50_000.).unwrap().contains(0.95, 0.5));
+    }
+
+    #[test]
+    fn test_simplify() {
+        // A circle with a radius of 1 degree and a vertex every degree.
+        let ring = (0..=360)
+            .map(|a| f64::from(a % 360).to_radians())
+            .map(|a| format!("{} {}", 10. + a.cos(), 60. + a.sin()))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let g = Geometry::new_from_wkt(&format!("POLYGON(({}))", ring)).unwrap();
+        let s = Gshhg::from_geom(g).unwrap();
+
+        let simple = s.simplify(5_000.).unwrap();
+
+        assert!(
+            simple.vertices() < s.vertices(),
+            "vertices: {} -> {}",
+            s.vertices(),
+            simple.vertices()
+        );
+        assert!(simple.contains(10., 60.));
+        assert!(!simple.contains(12., 60.));
+    }
+
     #[cfg(feature = "nightly")]
     mod benches {
         use super::*;
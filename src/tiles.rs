@@ -0,0 +1,366 @@
+//! A tiled landmask which is loaded lazily.
+//!
+//! The global bitmap is split into square tiles of `TILE` cells which are compressed separately
+//! and stored in one file together with an index of their offsets. Opening a `TiledMask` only
+//! reads the index, the tiles are decoded on first access and kept until the mask is dropped.
+//! Workloads that only touch a region of the globe therefore only pay for the tiles they need.
+//!
+//! With the `tiles` feature the full mask is embedded in tiled form as well, `TiledMask::new`
+//! uses it without decompressing the whole mask:
+//!
+//! ```no_run
+//! # use roaring_landmask::TiledMask;
+//! let tiled = TiledMask::new().unwrap();
+//! assert!(tiled.contains(15., 65.6).unwrap());
+//! assert_eq!(tiled.loaded(), 1);
+//! ```
+//!
+//! Other masks can be tiled with `TiledMask::write` and opened with `TiledMask::open`. The
+//! embedded `mask.tiles` is made from `mask.tbmap.xz` by `examples/make_tiles.rs`.
+//!
+//! A `TiledMask` only answers the raster stage. `RoaringLandmask` is not built on it: `new` still
+//! decodes the full mask and the shapes, which are not tiled either.
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use roaring::RoaringBitmap;
+use std::borrow::{Borrow, Cow};
+use std::fs::File;
+use std::io::{self, prelude::*, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use crate::mask::{RoaringMask, NX, NY, TRANSFORM};
+
+/// Number of cells along each side of a tile (10 degrees).
+pub const TILE: u64 = 2400;
+
+/// Number of tiles along longitude.
+pub const NTX: u64 = NX / TILE;
+
+/// Number of tiles along latitude.
+pub const NTY: u64 = NY / TILE;
+
+static MAGIC: &[u8; 4] = b"RLMT";
+const VERSION: u32 = 1;
+
+/// Where the tiles are read from.
+enum Source {
+    File(PathBuf),
+    Embedded(Cow<'static, [u8]>),
+}
+
+#[pyclass]
+pub struct TiledMask {
+    source: Source,
+    index: Vec<(u64, u64)>,
+    tiles: Vec<OnceLock<RoaringBitmap>>,
+}
+
+fn read_u32(fd: &mut impl Read) -> io::Result<u32> {
+    let mut buf = [0u8; 4];
+    fd.read_exact(&mut buf)?;
+    Ok(u32::from_le_bytes(buf))
+}
+
+fn read_u64(fd: &mut impl Read) -> io::Result<u64> {
+    let mut buf = [0u8; 8];
+    fd.read_exact(&mut buf)?;
+    Ok(u64::from_le_bytes(buf))
+}
+
+fn invalid(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+impl TiledMask {
+    /// Split `mask` into tiles and write them to `path`.
+    pub fn write<P: AsRef<Path>>(mask: &RoaringMask, path: P) -> io::Result<()> {
+        let mut tiles = vec![RoaringBitmap::new(); (NTX * NTY) as usize];
+
+        // The mask is iterated in row-major order, so the local index within each tile is
+        // always increasing.
+        for i in mask.tmap.iter() {
+            let (x, y) = (i % NX, i / NX);
+            let t = (y / TILE) * NTX + x / TILE;
+            let l = (y % TILE) * TILE + x % TILE;
+            tiles[t as usize].push(l as u32);
+        }
+
+        let mut data = Vec::new();
+        let mut index = Vec::with_capacity(tiles.len());
+
+        for tile in &tiles {
+            let mut enc = xz2::write::XzEncoder::new(Vec::new(), 9);
+            tile.serialize_into(&mut enc)?;
+            let buf = enc.finish()?;
+
+            index.push((data.len() as u64, buf.len() as u64));
+            data.extend_from_slice(&buf);
+        }
+
+        let mut fd = io::BufWriter::new(File::create(path)?);
+        fd.write_all(MAGIC)?;
+        fd.write_all(&VERSION.to_le_bytes())?;
+        fd.write_all(&(TILE as u32).to_le_bytes())?;
+        fd.write_all(&(index.len() as u32).to_le_bytes())?;
+
+        for (offset, len) in index {
+            fd.write_all(&offset.to_le_bytes())?;
+            fd.write_all(&len.to_le_bytes())?;
+        }
+
+        fd.write_all(&data)?;
+        fd.flush()
+    }
+
+    /// Check if point (x, y) is on land, loading the containing tile if necessary.
+    ///
+    /// `x` is longitude, [-180, 180] east
+    /// `y` is latitude,  [- 90,  90] north
+    ///
+    /// Same as `RoaringMask::contains`, but returns an `InvalidInput` error for latitudes outside
+    /// [-90, 90] instead of panicking.
+    pub fn contains(&self, x: f64, y: f64) -> io::Result<bool> {
+        if !(y >= -90. && y <= 90.) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("({}, {}) is outside the landmask", x, y),
+            ));
+        }
+
+        let x = super::modulate_longitude(x);
+
+        let (x, y) = TRANSFORM.apply(x, y);
+        // The easternmost half-cell wraps around to the first column.
+        let x = x as u64 % NX;
+        let y = y as u64;
+
+        // Special case where we are in northernmost cell. North Pole is always in ocean anyway.
+        if y == NY {
+            return Ok(false);
+        }
+
+        assert!(y < NY);
+
+        let t = (y / TILE) * NTX + x / TILE;
+        let l = (y % TILE) * TILE + x % TILE;
+
+        Ok(self.tile(t as usize)?.contains(l as u32))
+    }
+
+    fn tile(&self, t: usize) -> io::Result<&RoaringBitmap> {
+        if let Some(tile) = self.tiles[t].get() {
+            return Ok(tile);
+        }
+
+        let tile = self.read_tile(t)?;
+        Ok(self.tiles[t].get_or_init(|| tile))
+    }
+
+    /// Open the tiled mask embedded with the `tiles` feature. Only the tile index is read.
+    pub fn new() -> io::Result<TiledMask> {
+        use crate::GsshgData;
+
+        let buf = GsshgData::get("mask.tiles").ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                "cannot find tiled mask, built without the tiles feature?",
+            )
+        })?;
+
+        let data: &[u8] = buf.data.borrow();
+        let index = TiledMask::read_index(data)?;
+
+        Ok(TiledMask::from_index(Source::Embedded(buf.data), index))
+    }
+
+    /// Open a tiled mask written by `TiledMask::write`. Only the tile index is read.
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<TiledMask> {
+        let path = path.as_ref().to_path_buf();
+        let index = TiledMask::read_index(io::BufReader::new(File::open(&path)?))?;
+
+        Ok(TiledMask::from_index(Source::File(path), index))
+    }
+
+    fn from_index(source: Source, index: Vec<(u64, u64)>) -> TiledMask {
+        let tiles = (0..index.len()).map(|_| OnceLock::new()).collect();
+
+        TiledMask {
+            source,
+            index,
+            tiles,
+        }
+    }
+
+    fn read_index(mut fd: impl Read) -> io::Result<Vec<(u64, u64)>> {
+        let mut magic = [0u8; 4];
+        fd.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(invalid("not a tiled mask"));
+        }
+
+        if read_u32(&mut fd)? != VERSION {
+            return Err(invalid("unsupported tiled mask version"));
+        }
+
+        if read_u32(&mut fd)? as u64 != TILE {
+            return Err(invalid("unsupported tile size"));
+        }
+
+        let n = read_u32(&mut fd)? as usize;
+        if n as u64 != NTX * NTY {
+            return Err(invalid("wrong number of tiles"));
+        }
+
+        let header = 4 * 4 + 16 * n as u64;
+        let mut index = Vec::with_capacity(n);
+        for _ in 0..n {
+            let offset = read_u64(&mut fd)?;
+            let len = read_u64(&mut fd)?;
+            index.push((header + offset, len));
+        }
+
+        Ok(index)
+    }
+
+    fn read_tile(&self, t: usize) -> io::Result<RoaringBitmap> {
+        let (offset, len) = self.index[t];
+
+        match &self.source {
+            Source::File(path) => {
+                let mut fd = File::open(path)?;
+                fd.seek(SeekFrom::Start(offset))?;
+                let fd = io::BufReader::new(fd.take(len));
+
+                RoaringBitmap::deserialize_from(xz2::bufread::XzDecoder::new(fd))
+            }
+            Source::Embedded(data) => {
+                let buf = data
+                    .get(offset as usize..(offset + len) as usize)
+                    .ok_or_else(|| invalid("tile is outside the data"))?;
+
+                RoaringBitmap::deserialize_from(xz2::bufread::XzDecoder::new(buf))
+            }
+        }
+    }
+}
+
+#[pymethods]
+impl TiledMask {
+    #[staticmethod]
+    #[pyo3(name = "new")]
+    fn py_new() -> io::Result<TiledMask> {
+        TiledMask::new()
+    }
+
+    #[staticmethod]
+    #[pyo3(name = "open")]
+    fn py_open(path: PathBuf) -> io::Result<TiledMask> {
+        TiledMask::open(path)
+    }
+
+    #[staticmethod]
+    #[pyo3(name = "write")]
+    fn py_write(mask: &RoaringMask, path: PathBuf) -> io::Result<()> {
+        TiledMask::write(mask, path)
+    }
+
+    #[pyo3(name = "contains")]
+    fn py_contains(&self, x: f64, y: f64) -> PyResult<bool> {
+        self.contains(x, y).map_err(|e| match e.kind() {
+            io::ErrorKind::InvalidInput => PyValueError::new_err(e.to_string()),
+            _ => e.into(),
+        })
+    }
+
+    /// Number of tiles that have been loaded.
+    #[getter]
+    pub fn loaded(&self) -> usize {
+        self.tiles.iter().filter(|t| t.get().is_some()).count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use roaring::RoaringTreemap;
+
+    #[test]
+    fn write_and_open() {
+        // A band along the equator and a block across the dateline.
+        let mut tmap = RoaringTreemap::new();
+        tmap.insert_range((NY / 2) * NX..(NY / 2 + 10) * NX);
+        for y in 960..1250 {
+            tmap.insert_range(y * NX..y * NX + 500);
+            tmap.insert_range(y * NX + NX - 500..(y + 1) * NX);
+        }
+        let mask = RoaringMask::from(tmap);
+
+        let path = std::env::temp_dir().join(format!(
+            "roaring-landmask-test-{}.tiles",
+            std::process::id()
+        ));
+        TiledMask::write(&mask, &path).unwrap();
+
+        let tiled = TiledMask::open(&path).unwrap();
+        assert_eq!(tiled.loaded(), 0);
+
+        assert!(tiled.contains(15., 0.).unwrap());
+        assert!(tiled.contains(-180., -86.).unwrap());
+        assert!(tiled.contains(179., -85.).unwrap());
+        assert!(!tiled.contains(15., 65.6).unwrap());
+        assert_eq!(
+            tiled.contains(15., 95.).unwrap_err().kind(),
+            io::ErrorKind::InvalidInput
+        );
+
+        for x in (-180..180).map(f64::from) {
+            for y in (-89..90).map(f64::from) {
+                assert_eq!(mask.contains(x, y), tiled.contains(x, y).unwrap());
+            }
+        }
+
+        assert_eq!(tiled.loaded() as u64, NTX * NTY);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[cfg(feature = "tiles")]
+    #[test]
+    fn embedded() {
+        let tiled = TiledMask::new().unwrap();
+        assert_eq!(tiled.loaded(), 0);
+
+        assert!(tiled.contains(15., 65.6).unwrap());
+        assert_eq!(tiled.loaded(), 1);
+        assert!(!tiled.contains(5., 65.6).unwrap());
+        assert_eq!(tiled.loaded(), 2);
+
+        let mask = RoaringMask::new().unwrap();
+        for x in (-180..180).step_by(7).map(f64::from) {
+            for y in (-89..90).step_by(3).map(f64::from) {
+                assert_eq!(mask.contains(x, y), tiled.contains(x, y).unwrap());
+            }
+        }
+
+        assert_eq!(tiled.loaded() as u64, NTX * NTY);
+    }
+
+    #[cfg(feature = "tiles")]
+    #[test]
+    fn generated_from_mask() {
+        use crate::GsshgData;
+
+        let path = std::env::temp_dir().join(format!(
+            "roaring-landmask-test-{}-generated.tiles",
+            std::process::id()
+        ));
+        TiledMask::write(&RoaringMask::new().unwrap(), &path).unwrap();
+
+        let embedded = GsshgData::get("mask.tiles").unwrap();
+        assert_eq!(std::fs::read(&path).unwrap(), embedded.data.as_ref());
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
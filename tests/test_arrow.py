@@ -0,0 +1,18 @@
+import pytest
+from roaring_landmask import RoaringLandmask
+
+pa = pytest.importorskip('pyarrow')
+
+l = RoaringLandmask.new()
+pytestmark = pytest.mark.skipif(not hasattr(l, 'contains_many_arrow'),
+                                reason='built without the arrow feature')
+
+def test_contains_many_arrow():
+    x = pa.array([15., 5., None])
+    y = pa.array([65.6, 65.6, 65.6])
+
+    land = l.contains_many_arrow(x, y)
+    assert land.to_pylist() == [True, False, None]
+
+    with pytest.raises(ValueError):
+        l.contains_many_arrow(pa.array([1, 2, 3]), y)
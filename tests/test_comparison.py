@@ -0,0 +1,46 @@
+import numpy as np
+import pytest
+from roaring_landmask import Gshhg, RoaringLandmask
+
+shapely = pytest.importorskip('shapely', minversion = '2')
+
+# The same points on every run, uniform over the sphere. Run with `--benchmark-enable` and
+# compare the landmask to shapely within the same run, see `benches/comparison.rs`.
+N = 100_000
+SEED = 42
+
+def points():
+    rng = np.random.default_rng(SEED)
+    x = rng.uniform(-180., 180., N)
+    y = np.degrees(np.arcsin(rng.uniform(-1., 1., N)))
+    return x, y
+
+@pytest.fixture(scope = 'module')
+def land():
+    geom = shapely.from_wkb(Gshhg.wkb())
+    shapely.prepare(geom)
+    return geom
+
+@pytest.mark.benchmark(group = 'comparison')
+def test_roaring_landmask(benchmark):
+    l = RoaringLandmask.new()
+    x, y = points()
+    benchmark(l.contains_many_par, x, y)
+
+@pytest.mark.benchmark(group = 'comparison')
+def test_shapely_contains_xy(benchmark, land):
+    x, y = points()
+    benchmark(shapely.contains_xy, land, x, y)
+
+@pytest.mark.benchmark(group = 'comparison')
+def test_shapely_strtree(benchmark, land):
+    tree = shapely.STRtree(shapely.get_parts(land))
+    pts = shapely.points(*points())
+    benchmark(tree.query, pts, predicate = 'within')
+
+def test_agrees_with_shapely(land):
+    l = RoaringLandmask.new()
+    x, y = points()
+
+    agree = l.contains_many_par(x, y) == shapely.contains_xy(land, x, y)
+    assert agree.mean() > 0.999
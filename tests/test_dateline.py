@@ -22,3 +22,11 @@ def test_dateline():
 
     np.testing.assert_array_equal(mm, MM)
 
+
+def test_poles():
+    mask = RoaringLandmask.new()
+
+    x = np.linspace(-360, 540, 100)
+
+    assert not mask.contains_many(x, np.full(x.shape, 90.)).any()
+    assert mask.contains_many(x, np.full(x.shape, -90.)).all()
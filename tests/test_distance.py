@@ -0,0 +1,160 @@
+import pytest
+import numpy as np
+from roaring_landmask import RoaringLandmask, Unit
+
+def test_distance_on_land():
+    l = RoaringLandmask.new()
+    assert l.distance_to_shore(15., 65.6) == 0.
+
+def test_distance_units():
+    l = RoaringLandmask.new()
+
+    d = l.distance_to_shore(5., 65.6)
+    assert d > 10_000
+
+    assert l.distance_to_shore(5., 65.6, Unit.Meters) == d
+    assert l.distance_to_shore(5., 65.6, Unit.Kilometers) == d / 1000.
+    assert l.distance_to_shore(5., 65.6, unit = Unit.NauticalMiles) == d / 1852.
+
+def test_nearest_land_many():
+    l = RoaringLandmask.new()
+
+    x = np.array([15., 5.])
+    y = np.array([65.6, 65.6])
+
+    nx, ny = l.nearest_land_many(x, y)
+    assert nx[0] == 15. and ny[0] == 65.6
+    assert 5. < nx[1] < 15.
+
+    nx, ny = l.nearest_land_many(x, y, max_dist = 1000.)
+    assert np.isnan(nx[1]) and np.isnan(ny[1])
+
+def test_distance_to_shore_many():
+    l = RoaringLandmask.new()
+
+    x = np.array([[15., 5.], [4., 3.]])
+    y = np.full((2, 2), 65.6)
+    d = l.distance_to_shore_many(x, y, Unit.Kilometers)
+
+    assert d.shape == (2, 2)
+    assert d[0, 0] == 0.
+    assert abs(d[0, 1] - l.distance_to_shore(5., 65.6, Unit.Kilometers)) < 1e-9
+
+def test_contains_and_distance():
+    l = RoaringLandmask.new()
+
+    assert l.contains_and_distance(15., 65.6) == (True, 0.)
+
+    land, d = l.contains_and_distance(5., 65.6, Unit.Kilometers)
+    assert not land
+    assert d == l.distance_to_shore(5., 65.6, Unit.Kilometers)
+
+    x = np.array([[15., 5.], [4., 3.]])
+    y = np.full((2, 2), 65.6)
+    land, d = l.contains_and_distance_many(x, y, Unit.Kilometers)
+
+    assert land.shape == d.shape == (2, 2)
+    assert (land == l.contains_many(x.ravel(), y.ravel()).reshape(2, 2)).all()
+    assert np.allclose(d, l.distance_to_shore_many(x, y, Unit.Kilometers))
+
+def test_distance_contours():
+    from shapely import wkb
+
+    l = RoaringLandmask.new()
+
+    contours = l.distance_contours([1., 5.], (4., 65., 8., 66.))
+    assert len(contours) == 2
+
+    for c in contours:
+        c = wkb.loads(c)
+        assert c.geom_type == 'MultiLineString'
+        assert not c.is_empty
+
+def test_nearest_coast_segment():
+    l = RoaringLandmask.new()
+
+    (x0, y0), (x1, y1) = l.nearest_coast_segment(5., 65.6)
+    assert 5. < x0 < 15. and 5. < x1 < 15.
+
+def test_within_distance_many():
+    l = RoaringLandmask.new()
+
+    d = l.distance_to_shore(5., 65.6, Unit.Kilometers)
+    x = np.array([15., 5., 5.])
+    y = np.full(3, 65.6)
+
+    w = l.within_distance_many(x, y, np.array([0., d - 1., d + 1.]))
+    assert list(w) == [True, False, True]
+
+    with pytest.raises(ValueError):
+        l.within_distance_many(x, y, np.array([1., 2.]))
+
+def test_circle_intersects_land():
+    l = RoaringLandmask.new()
+
+    d = l.distance_to_shore(5., 65.6, Unit.Kilometers)
+    assert l.circle_intersects_land(15., 65.6, 0.)
+    assert not l.circle_intersects_land(5., 65.6, d - 1.)
+    assert l.circle_intersects_land(5., 65.6, d + 1.)
+
+    x = np.array([15., 5., -123.4])
+    y = np.array([65.6, 65.6, -48.9])
+    assert list(l.circle_intersects_land_many(x, y, d + 1.)) == [True, True, False]
+
+def test_nearest_point():
+    from roaring_landmask import PointSet
+
+    ports = PointSet([(10.75, 59.9), (5.32, 60.39), (-179.5, 0.)])
+    assert len(ports) == 3
+
+    i, d = ports.nearest(5., 60., Unit.Kilometers)
+    assert i == 1
+    assert d < 50.
+
+    x = np.array([[5., 179.9], [11., 10.]])
+    y = np.array([[60., 0.], [59.5, 59.9]])
+    i, d = ports.nearest_many(x, y)
+    assert i.dtype == np.int64
+    assert (i == [[1, 2], [0, 0]]).all()
+    assert d.shape == (2, 2)
+
+    # Along the parallel from Oslo, in meters.
+    assert abs(d[1, 1] - 41824) < 100
+
+    with pytest.raises(ValueError):
+        PointSet([])
+
+def test_signed_distance_to_shore():
+    l = RoaringLandmask.new()
+
+    ocean = l.signed_distance_to_shore(5., 65.6, Unit.Kilometers)
+    land = l.signed_distance_to_shore(15., 65.6, Unit.Kilometers)
+    assert ocean == l.distance_to_shore(5., 65.6, Unit.Kilometers)
+    assert land < -10.
+
+    d = l.signed_distance_to_shore_many(np.array([5., 15.]), np.array([65.6, 65.6]), Unit.Kilometers)
+    assert list(d) == [ocean, land]
+
+def test_distance_cache():
+    l = RoaringLandmask.new()
+    assert not l.distance_cache
+
+    x = np.linspace(4., 5., 50)
+    y = np.full(50, 60.)
+    d = l.distance_to_shore_many(x, y)
+
+    l.distance_cache = True
+    assert l.distance_cache
+    assert (l.distance_to_shore_many(x, y) == d).all()
+    assert l.distance_to_shore(x[3], y[3]) == d[3]
+
+def test_earth():
+    from roaring_landmask import Earth
+
+    l = RoaringLandmask.new()
+    assert l.earth == Earth.Sphere
+    d = l.distance_to_shore(5., 65.6)
+
+    l.earth = Earth.Wgs84
+    e = l.distance_to_shore(5., 65.6)
+    assert 0.994 < d / e < 0.999
@@ -0,0 +1,96 @@
+import pytest
+import numpy as np
+from shapely.geometry import box
+from roaring_landmask import Gshhg, RoaringLandmask
+
+def test_contains_with_extra():
+    l = RoaringLandmask.new()
+    extra = Gshhg.from_wkb(box(4., 65., 6., 66.).wkb)
+
+    assert l.contains_with_extra(5., 65.6, extra)
+    assert not l.contains(5., 65.6)
+
+    x = np.array([15., 5., 3.])
+    y = np.array([65.6, 65.6, 65.6])
+    assert list(l.contains_many_with_extra(x, y, extra)) == [True, True, False]
+
+def test_classify_regions():
+    from roaring_landmask import LAND, OCEAN
+
+    l = RoaringLandmask.new()
+    regions = [
+        Gshhg.from_wkb(box(4., 65., 6., 66.).wkb),
+        Gshhg.from_wkb(box(5., 65., 16., 66.).wkb),
+    ]
+
+    x = np.array([5., 15., 10., 0.])
+    y = np.array([65.6, 65.6, 60., 65.6])
+
+    assert (l.classify_regions_many(x, y, regions) == [0, 1, LAND, OCEAN]).all()
+    assert l.classify_regions(15., 65.6, regions) == 1
+
+def test_enclosed_water():
+    from shapely.geometry import MultiPolygon, Polygon
+    from roaring_landmask import EnclosedWater
+
+    # The bundled shapes have no lakes, so cut a lake with an island out of the land around Lake
+    # Victoria, where the embedded mask has land.
+    lake = box(32., -2., 34., 0.)
+    land = Polygon(box(30., -4., 36., 2.).exterior.coords, [lake.exterior.coords])
+    island = box(32.5, -1.5, 33.5, -0.5)
+    l = RoaringLandmask.from_shapes(Gshhg.from_wkb(MultiPolygon([land, island]).wkb))
+    assert l.enclosed_water == EnclosedWater.AsSeparate
+
+    # On land, in the lake and on the island.
+    x = np.array([31., 32.2, 33.])
+    y = np.array([1., -1.8, -1.])
+    for policy, expected in [
+            (EnclosedWater.AsSeparate, [True, False, True]),
+            (EnclosedWater.AsLand, [True, True, True]),
+            (EnclosedWater.AsOcean, [True, False, False])]:
+        l.enclosed_water = policy
+        assert l.enclosed_water == policy
+        assert list(l.contains_many(x, y)) == expected
+
+def test_overlay():
+    # Around the Azores, where only the polygons of the islands are cut.
+    l = RoaringLandmask.new()
+    merged = l.overlay(RoaringLandmask.new(), (-32., 36., -24., 40.))
+    assert merged.contains(-25.5, 37.77) and not merged.contains(-27., 38.5)
+    assert merged.contains(15., 65.6) and not merged.contains(5., 65.6)
+
+    with pytest.raises(ValueError):
+        l.overlay(l, (15., 60., 5., 70.))
+
+def test_zones():
+    l = RoaringLandmask.new()
+    l.load_zones(Gshhg.from_wkb(box(4., 65., 6., 66.).wkb), "inner")
+    l.load_zones(Gshhg.from_wkb(box(0., 64., 8., 67.).wkb), "outer")
+    assert l.zone_labels == ["inner", "outer"]
+
+    assert l.zone_of(5., 65.6) == "inner"
+    assert l.zone_of(1., 65.6) == "outer"
+    assert l.zone_of(-10., 65.6) is None
+    assert l.zone_of(5., -90.) is None
+
+    x = np.array([[5., 1., -10.]])
+    y = np.array([[65.6, 65.6, 65.6]])
+    zones = l.zone_of_many(x, y)
+    assert zones.shape == (1, 3)
+    assert zones.tolist() == [["inner", "outer", None]]
+
+def test_validate():
+    bowtie = [(0., 0.), (4., 4.), (4., 0.), (0., 4.), (0., 0.)]
+    s = Gshhg.from_wkb(box(4., 65., 6., 66.).wkb)
+    assert s.validate() == []
+
+    s = Gshhg.from_rings([[bowtie]])
+    assert len(s.validate()) == 1
+    assert 'intersects itself at (2, 2)' in s.validate()[0]
+
+    unclosed = [(10., 10.), (11., 10.), (11., 11.)]
+    with pytest.raises(OSError):
+        Gshhg.from_rings([[unclosed]])
+
+    s = Gshhg.from_rings([[unclosed]], close_rings = True)
+    assert s.contains(10.8, 10.5)
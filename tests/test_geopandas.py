@@ -0,0 +1,22 @@
+import pytest
+import numpy as np
+from roaring_landmask import RoaringLandmask, contains_geodataframe
+
+gpd = pytest.importorskip('geopandas')
+
+def test_contains_geodataframe():
+    l = RoaringLandmask.new()
+
+    gdf = gpd.GeoDataFrame(
+        { 'name': ['norway', 'norwegian sea'] },
+        geometry = gpd.points_from_xy([15., 5.], [65.6, 65.6]),
+        index = [10, 20],
+        crs = 'EPSG:4326')
+
+    land = contains_geodataframe(gdf, l)
+    assert list(land.index) == [10, 20]
+    assert list(land) == [True, False]
+
+    # Web Mercator
+    land = contains_geodataframe(gdf.to_crs(epsg = 3857), l)
+    assert list(land) == [True, False]
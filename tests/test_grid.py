@@ -0,0 +1,46 @@
+import numpy as np
+from roaring_landmask import RoaringLandmask
+
+def test_precompute_grid():
+    l = RoaringLandmask.new()
+
+    x = np.arange(-180, 180, 2.)
+    y = np.arange(-89, 90, 2.)
+    xx, yy = np.meshgrid(x, y)
+
+    grid = l.precompute_grid(xx, yy)
+    assert grid.shape == list(xx.shape)
+    assert len(grid) == xx.size
+
+    np.testing.assert_array_equal(grid.contains(), l.contains_many_par(xx, yy))
+
+def test_contains_rotated():
+    l = RoaringLandmask.new()
+
+    # The COSMO-DE grid, with the origin at (10, 50) in Germany.
+    assert l.contains_rotated(0., 0., -170., 40.)
+
+    rlon, rlat = np.meshgrid(np.linspace(-5., 5.5, 50), np.linspace(-5., 6.5, 60))
+    land = l.contains_rotated_many(rlon, rlat, -170., 40.)
+    assert land.shape == rlon.shape
+    assert 0.5 < land.mean() < 1.
+
+def test_contains_int():
+    l = RoaringLandmask.new()
+
+    for lon, lat in [(15, 65), (5, 65), (10, 50), (-30, 40), (0, -90), (0, 90)]:
+        assert l.contains_int(lon, lat) == l.contains(float(lon), float(lat))
+
+    assert l.contains_int(375, 65) == l.contains_int(15, 65)
+
+def test_contains_int_settings():
+    from roaring_landmask import Sampling
+
+    l = RoaringLandmask.new()
+    assert l.contains_int(15, 65)
+
+    # The table is made again with the new settings.
+    l.sampling = Sampling.Corner
+    assert l.sampling == Sampling.Corner
+    for lon, lat in [(15, 65), (5, 65), (10, 50), (-30, 40)]:
+        assert l.contains_int(lon, lat) == l.contains(float(lon), float(lat))
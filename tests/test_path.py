@@ -0,0 +1,102 @@
+import numpy as np
+import pytest
+from roaring_landmask import RoaringLandmask
+
+def test_crossed_onto_land():
+    l = RoaringLandmask.new()
+
+    x, y = l.crossed_onto_land((5., 65.6), (15., 65.6))
+    assert 5. < x < 15.
+    assert l.contains(x, y)
+
+def test_stays_in_ocean():
+    l = RoaringLandmask.new()
+    assert l.crossed_onto_land((179., .5), (-179., .5)) is None
+
+def test_coastline_crossings():
+    l = RoaringLandmask.new()
+
+    assert l.coastline_crossings([(5., 65.6), (15., 65.6)]) % 2 == 1
+    assert l.coastline_crossings([(179., .5), (-179., .5)]) == 0
+
+def test_first_landfall():
+    l = RoaringLandmask.new()
+
+    x, y = l.first_landfall((5., 65.6), 90., 1000.)
+    assert 5. < x < 15.
+    assert l.contains(x, y)
+
+    assert l.first_landfall((5., 65.6), 90., 10.) is None
+
+def test_land_time_fraction():
+    l = RoaringLandmask.new()
+
+    assert 0. < l.land_time_fraction([(5., 65.6), (15., 65.6)]) < 1.
+    assert l.land_time_fraction([(179., .5), (-179., .5)]) == 0.
+
+def test_same_landmass():
+    l = RoaringLandmask.new()
+
+    assert l.same_landmass((-3.7, 40.4), (11.0, 60.0))
+    assert not l.same_landmass((-3.7, 40.4), (-0.1, 51.5))
+    assert not l.same_landmass((5., 65.6), (5., 65.6))
+
+def test_land_fraction():
+    from roaring_landmask import COVERAGE_SAMPLES, Coverage
+
+    l = RoaringLandmask.new()
+
+    assert l.land_fraction((20., 49., 22., 51.)) == 1.
+    assert 0. < l.land_fraction((5., 65., 15., 66.), COVERAGE_SAMPLES * 2) < 1.
+
+    assert l.classify_polygon([(20., 49.), (22., 49.), (22., 51.), (20., 49.)]) == Coverage.Land
+    assert l.classify_polygon([(5., 65.), (15., 65.), (15., 66.), (5., 65.)]) == Coverage.Mixed
+    assert l.classify_polygon([(-30., 0.), (-29., 0.), (-29., 1.), (-30., 0.)], 4) == Coverage.Ocean
+
+def test_bbox_is_all_ocean():
+    l = RoaringLandmask.new()
+
+    assert l.bbox_is_all_ocean((-130., -50., -120., -40.))
+    assert not l.bbox_is_all_ocean((0., 60., 10., 70.))
+    assert l.bbox_is_all_ocean((175., 40., -175., 45.))
+
+    with pytest.raises(ValueError):
+        l.bbox_is_all_ocean((0., 70., 10., 60.))
+
+def test_land_weight():
+    l = RoaringLandmask.new()
+
+    assert l.land_weight(15., 65.6) == 1.
+    assert l.land_weight(5., 65.6) == 0.
+
+    x = np.linspace(5., 15., 10_000).reshape(100, 100)
+    y = np.full(x.shape, 65.6)
+    w = l.land_weight_many(x, y)
+
+    assert w.shape == x.shape
+    assert ((w >= 0.) & (w <= 1.)).all()
+    assert ((w > 0.) & (w < 1.)).any()
+
+def test_coverage_summary():
+    l = RoaringLandmask.new()
+    s = l.coverage_summary()
+
+    assert s['extent'] == (-180., -90., 180., 90.)
+    assert s['south_pole'] and not s['north_pole']
+    assert s['land_lat'][1] > 83.
+    assert 0 < s['tiles'] <= 36 * 18
+
+def test_classify_tracks():
+    from roaring_landmask import densify_track
+
+    l = RoaringLandmask.new()
+    track = [(5., 65.6), (15., 65.6), (5., 65.6)]
+    runs, = l.classify_tracks([track], 1000.)
+
+    points = densify_track(track, 1000.)
+    assert runs[0][:2] == (False, 0)
+    assert runs[-1][2] == len(points) - 1
+    assert any(land for land, _, _ in runs)
+
+    with pytest.raises(ValueError):
+        l.classify_tracks([track], 0.)
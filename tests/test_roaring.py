@@ -1,3 +1,4 @@
+import pytest
 import numpy as np
 from roaring_landmask import RoaringLandmask
 
@@ -61,3 +62,307 @@ def test_landmask_many_par_few(benchmark):
   print ("points:", len(xx.ravel()))
   benchmark(l.contains_many_par, xx.ravel(), yy.ravel())
 
+
+def test_landmask_close():
+    l = RoaringLandmask.new()
+    assert l.contains(15., 65.6)
+
+    l.close()
+    assert l.closed
+
+    with pytest.raises(ValueError):
+        l.contains(15., 65.6)
+
+def test_landmask_context_manager():
+    with RoaringLandmask.new() as l:
+        assert l.contains(15., 65.6)
+
+    assert l.closed
+
+    with pytest.raises(ValueError):
+        l.contains_many(np.array([15.]), np.array([65.6]))
+
+def test_landmask_many_mismatched():
+    l = RoaringLandmask.new()
+
+    x = np.array([15., 5.])
+    y = np.array([65.6])
+
+    with pytest.raises(ValueError):
+        l.contains_many(x, y)
+
+    with pytest.raises(ValueError):
+        l.contains_many_par(x, y)
+
+@pytest.mark.slow
+def test_landmask_with_buffer():
+    l = RoaringLandmask.with_buffer(1)
+
+    assert l.contains(15., 65.6)
+    assert not l.contains(5., 65.6)
+
+def test_contains_iter():
+    l = RoaringLandmask.new()
+
+    x = np.array([15., 5., 10.])
+    y = np.array([65.6, 65.6, 60.])
+
+    it = l.contains_iter(x, y)
+    assert next(it)
+    assert list(it) == [False, True]
+
+    # The points are paired up in C order whatever the memory layout.
+    x = np.asfortranarray([[15., 5.], [10., 0.]])
+    y = np.array([[65.6, 65.6], [60., 60.]])
+    assert list(l.contains_iter(x, y)) == [True, False, True, False]
+
+def test_self_test():
+    l = RoaringLandmask.new()
+    assert l.self_test() == []
+
+def test_bundle(tmp_path):
+    l = RoaringLandmask.new()
+    l.save_bundle(tmp_path / 'landmask.bundle')
+
+    b = RoaringLandmask.load_bundle(tmp_path / 'landmask.bundle')
+    assert b.contains(15., 65.6)
+    assert not b.contains(5., 65.6)
+
+    (tmp_path / 'corrupt.bundle').write_bytes(b'RLMB' + b'\0' * 100)
+    with pytest.raises(OSError):
+        RoaringLandmask.load_bundle(tmp_path / 'corrupt.bundle')
+
+def test_contains_rad():
+    l = RoaringLandmask.new()
+
+    assert l.contains_rad(np.radians(15.), np.radians(65.6))
+    assert not l.contains_rad(np.radians(5.), np.radians(65.6))
+
+    x = np.radians(np.array([15., 5.]))
+    y = np.radians(np.array([65.6, 65.6]))
+    assert list(l.contains_many_rad(x, y)) == [True, False]
+
+def test_metadata():
+    m = RoaringLandmask.new().metadata()
+    assert m.gshhg_version == '2.3.7'
+    assert m.resolution == 'f'
+    assert len(m.mask_sha256) == 64
+
+def test_level_many():
+    l = RoaringLandmask.new()
+
+    levels = l.level_many(np.array([15., 5.]), np.array([65.6, 65.6]))
+    assert levels.dtype == np.uint8
+    assert list(levels) == [1, 0]
+
+def test_out_of_domain():
+    from roaring_landmask import OutOfDomain
+
+    l = RoaringLandmask.new()
+    l.out_of_domain = OutOfDomain.ReturnFalse
+    assert not l.contains(15., 95.)
+    assert list(l.contains_many(np.array([15., 15.]), np.array([65.6, 95.]))) == [True, False]
+    assert list(l.level_many(np.array([15., 15.]), np.array([65.6, 95.]))) == [1, 0]
+
+    s = l.classify_many_struct(np.array([15., 15.]), np.array([65.6, 95.]))
+    assert list(s['on_land']) == [True, False]
+    assert list(s['surface']) == [1, 0]
+    assert np.isnan(s['dist_m'][1])
+
+    l.out_of_domain = OutOfDomain.Error
+    with pytest.raises(ValueError):
+        l.contains(15., 95.)
+    with pytest.raises(ValueError):
+        l.contains_many_par(np.array([15.]), np.array([-95.]))
+    with pytest.raises(ValueError):
+        l.level_many(np.array([15., 15.]), np.array([65.6, 95.]))
+    with pytest.raises(ValueError):
+        l.classify_many_struct(np.array([15., 15.]), np.array([65.6, 95.]))
+    with pytest.raises(ValueError):
+        l.classify_regions_many(np.array([15.]), np.array([95.]), [])
+    for query in [l.level, l.contains_explain, l.contains_exact]:
+        with pytest.raises(ValueError):
+            query(15., 95.)
+    with pytest.raises(ValueError):
+        l.contains_tol(15., 95., 4)
+
+def test_to_cells():
+    m = RoaringLandmask.new().mask
+
+    cols, rows = m.to_cells(np.array([-180., 0., 0.]), np.array([-90., 0., 90.]))
+    assert list(cols) == [0, m.nx // 2, -1]
+    assert list(rows) == [0, m.ny // 2, -1]
+    assert m.to_cell(0., 0.) == (m.nx // 2, m.ny // 2)
+
+def test_sampling():
+    from roaring_landmask import Sampling
+
+    l = RoaringLandmask.new()
+    assert l.sampling == Sampling.Center
+
+    l.sampling = Sampling.Corner
+    assert l.contains(15., 65.6)
+
+def test_dedupe():
+    l = RoaringLandmask.new()
+
+    x = np.tile([15., 5., 10.], 100)
+    y = np.tile([65.6, 65.6, 60.], 100)
+
+    assert (l.contains_many(x, y, dedupe=True) == l.contains_many(x, y)).all()
+    assert (l.contains_many(x, y, exact=False, dedupe=True) == l.contains_many(x, y, exact=False)).all()
+    assert (l.contains_many_par(x, y, dedupe=True) == l.contains_many_par(x, y)).all()
+    assert (l.level_many(x, y, dedupe=True) == l.level_many(x, y)).all()
+    assert (l.distance_to_shore_many(x, y, dedupe=True) == l.distance_to_shore_many(x, y)).all()
+    assert (l.signed_distance_to_shore_many(x, y, dedupe=True) == l.signed_distance_to_shore_many(x, y)).all()
+
+def test_query_stats():
+    from roaring_landmask import QueryStats
+
+    l = RoaringLandmask.new()
+
+    x = np.tile([15., 5., 10.], 100)
+    y = np.tile([65.6, 65.6, 60.], 100)
+
+    stats = QueryStats()
+    l.contains_many(x, y, stats=stats)
+    l.contains_many_par(x, y, stats=stats)
+
+    assert stats.total == 600
+    assert stats.rejected_by_mask == 200
+    assert stats.passed_mask == stats.land_by_shapes + stats.ocean_by_shapes == 400
+
+def test_mask_from_numpy():
+    from roaring_landmask import RoaringMask, Affine
+
+    land = np.zeros((180, 360), dtype=bool)
+    land[150, 190] = True
+    mask = RoaringMask.from_numpy(land, Affine(1., 0., 180., 0., 1., 90.))
+
+    assert mask.contains(10.5, 60.5)
+    assert not mask.contains(11.5, 60.5)
+
+    with pytest.raises(ValueError):
+        RoaringMask.from_numpy(land.astype(float), Affine(1., 0., 180., 0., 1., 90.))
+
+    with pytest.raises(ValueError):
+        RoaringMask.from_numpy(land[0], Affine(1., 0., 180., 0., 1., 90.))
+
+def test_preload_shared():
+    from roaring_landmask import preload_shared
+
+    preload_shared()
+
+    a = RoaringLandmask.new()
+    b = RoaringLandmask.new()
+    b.close()
+
+    assert a.contains(15., 65.6)
+
+def test_effective_resolution_m():
+    l = RoaringLandmask.new()
+
+    assert 463. < l.effective_resolution_m(0.) < 464.
+    assert l.effective_resolution_m(60.) == l.effective_resolution_m(0.)
+
+def test_mask_pyramid():
+    from roaring_landmask import RoaringMask, Affine
+
+    land = np.zeros((180, 360), dtype=bool)
+    land[150, 190] = True
+    mask = RoaringMask.from_numpy(land, Affine(1., 0., 180., 0., 1., 90.))
+
+    pyramid = mask.build_pyramid(4)
+    assert pyramid.pyramid_levels == 4
+    assert pyramid.pyramid_bytes > 0
+    assert mask.pyramid_levels == 0
+
+    for x, y in [(10.5, 60.5), (11.5, 60.5), (10.5, 61.5), (-100., -40.)]:
+        assert pyramid.contains(x, y) == mask.contains(x, y)
+
+    with pytest.raises(ValueError):
+        mask.build_pyramid(100)
+
+def test_validate_coords():
+    x = np.array([0., np.nan, 200., 10., np.inf])
+    y = np.array([0., 0., 45., 91., 0.])
+
+    invalid = RoaringLandmask.validate_coords(x, y)
+    assert invalid.dtype == np.int64
+    assert list(invalid) == [1, 3, 4]
+    assert len(RoaringLandmask.validate_coords(x[:1], y[:1])) == 0
+
+    with pytest.raises(ValueError):
+        RoaringLandmask.validate_coords(x, y[:2])
+
+def test_contains_many_to_mmap(tmp_path):
+    l = RoaringLandmask.new()
+
+    x = np.tile([15., 5.], 1000)
+    y = np.tile([65.6, 65.6], 1000)
+
+    # Into an existing memmap, at an offset within it.
+    out = tmp_path / "contains.bin"
+    c = np.memmap(out, dtype=bool, mode='w+', shape=len(x) + 20)
+    c[:] = True
+    c.flush()
+
+    l.contains_many_to_mmap(x, y, str(out), offset=10)
+
+    np.testing.assert_array_equal(c[10:-10], l.contains_many(x, y))
+    assert c[:10].all() and c[-10:].all()
+
+    with pytest.raises(ValueError):
+        l.contains_many_to_mmap(x, y, str(out), offset=30)
+
+def test_diff():
+    a = RoaringLandmask.new()
+    b = RoaringLandmask.new()
+
+    assert a.diff(b, (4., 65., 6., 66.)) == []
+
+def test_classify_many_struct():
+    l = RoaringLandmask.new()
+
+    x = np.array([15., 5.])
+    y = np.array([65.6, 65.6])
+    c = l.classify_many_struct(x, y)
+
+    assert c.dtype.names == ('on_land', 'surface', 'dist_m')
+    assert list(c['on_land']) == [True, False]
+    assert list(c['surface']) == [1, 0]
+    assert c['dist_m'][0] == 0.
+    assert abs(c['dist_m'][1] - l.distance_to_shore(5., 65.6)) < 1.
+
+def test_contains_many_par_same_as_serial():
+    l = RoaringLandmask.new()
+
+    rng = np.random.default_rng(42)
+    x = rng.uniform(-180, 180, 500_000)
+    y = rng.uniform(-90, 90, 500_000)
+
+    np.testing.assert_array_equal(l.contains_many(x, y), l.contains_many_par(x, y))
+
+def test_instant():
+    m = RoaringLandmask.instant()
+
+    assert m.contains(15., 65.6)
+    assert not m.contains(5., 65.6)
+    assert list(m.contains_many(np.array([15., 5.]), np.array([65.6, 65.6]))) == [True, False]
+
+def test_contains_not_exact():
+    l = RoaringLandmask.new()
+
+    assert l.contains(15., 65.6, exact = False)
+    assert not l.contains(5., 65.6, exact = False)
+
+    x = np.arange(5., 15., 0.001)
+    y = np.full(x.shape, 65.6)
+
+    exact = l.contains_many(x, y)
+    approx = l.contains_many(x, y, exact = False)
+    np.testing.assert_array_equal(approx, l.contains_many_par(x, y, exact = False))
+
+    # Near the shore the bitmap over-reports land, but never misses any.
+    assert (approx | ~exact).all()
+    assert (approx & ~exact).any()
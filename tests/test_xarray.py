@@ -0,0 +1,17 @@
+import pytest
+import numpy as np
+from roaring_landmask import RoaringLandmask, to_xarray
+
+xr = pytest.importorskip('xarray')
+
+def test_to_xarray():
+    l = RoaringLandmask.new()
+    da = to_xarray(l, step = 240)
+
+    assert da.shape == (180, 360)
+    assert da.attrs['crs'] == 'EPSG:4326'
+    np.testing.assert_allclose(da.lon, np.arange(-180, 180, 1.))
+    np.testing.assert_allclose(da.lat, np.arange(-90, 90, 1.))
+
+    assert da.sel(lon = 15., lat = 65., method = 'nearest')
+    assert not da.sel(lon = 5., lat = 65., method = 'nearest')